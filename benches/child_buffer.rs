@@ -0,0 +1,66 @@
+//! Compares collecting small sets of children into a `Vec` versus a `SmallVec` before linking them,
+//! as `with_children_now` does, and measures `with_children_now` itself with the crate's child buffer.
+//!
+//! Run with `cargo bench --features smallvec`.
+
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::BuildWorldChildren;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use i_cant_believe_its_not_bsn::EntityWorldMutExt;
+use smallvec::SmallVec;
+
+#[derive(Component)]
+struct A;
+
+const PARENTS: u32 = 1_000;
+
+fn spawn_parents<C: FromIterator<Entity> + AsRef<[Entity]>>(world: &mut World, child_count: u32) {
+    for _ in 0..PARENTS {
+        let children: C = (0..child_count).map(|_| world.spawn(A).id()).collect();
+        world.spawn(A).push_children(children.as_ref());
+    }
+}
+
+fn child_buffer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("child_buffer");
+    for child_count in [1, 2, 4, 8] {
+        group.bench_with_input(
+            BenchmarkId::new("vec", child_count),
+            &child_count,
+            |b, &n| {
+                b.iter(|| {
+                    let mut world = World::new();
+                    spawn_parents::<Vec<Entity>>(&mut world, black_box(n));
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("smallvec", child_count),
+            &child_count,
+            |b, &n| {
+                b.iter(|| {
+                    let mut world = World::new();
+                    spawn_parents::<SmallVec<[Entity; 4]>>(&mut world, black_box(n));
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("with_children_now", child_count),
+            &child_count,
+            |b, &n| {
+                b.iter(|| {
+                    let mut world = World::new();
+                    for _ in 0..PARENTS {
+                        world
+                            .spawn(A)
+                            .with_children_now((0..black_box(n)).map(|_| A));
+                    }
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, child_buffer);
+criterion_main!(benches);
@@ -0,0 +1,114 @@
+//! Compares resolving many `Maybe` components on the same entity, which share a single command,
+//! against applying one `MaybeCommand` per component, as was needed before they were coalesced.
+//!
+//! Run with `cargo bench --bench maybe_coalescing`.
+
+use bevy_ecs::prelude::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use i_cant_believe_its_not_bsn::{ManualResolution, Maybe, MaybeCommand};
+
+#[derive(Component)]
+struct C<const N: usize>;
+
+const ENTITIES: usize = 1_000;
+
+/// Spawns an entity with `count` optional components, each wrapped in a `Maybe`.
+fn spawn_maybes(world: &mut World, count: usize) -> Entity {
+    match count {
+        1 => world.spawn(Maybe::new(C::<0>)).id(),
+        4 => world
+            .spawn((
+                Maybe::new(C::<0>),
+                Maybe::new(C::<1>),
+                Maybe::new(C::<2>),
+                Maybe::new(C::<3>),
+            ))
+            .id(),
+        12 => world
+            .spawn((
+                (
+                    Maybe::new(C::<0>),
+                    Maybe::new(C::<1>),
+                    Maybe::new(C::<2>),
+                    Maybe::new(C::<3>),
+                    Maybe::new(C::<4>),
+                    Maybe::new(C::<5>),
+                ),
+                (
+                    Maybe::new(C::<6>),
+                    Maybe::new(C::<7>),
+                    Maybe::new(C::<8>),
+                    Maybe::new(C::<9>),
+                    Maybe::new(C::<10>),
+                    Maybe::new(C::<11>),
+                ),
+            ))
+            .id(),
+        _ => unreachable!(),
+    }
+}
+
+/// Resolves each entity's `Maybe` components with the single command queued by their hooks.
+///
+/// Spawning flushes the world, so coalescing is enabled as soon as the first entity has been resolved.
+fn coalesced(world: &mut World, count: usize) {
+    for _ in 0..ENTITIES {
+        spawn_maybes(world, count);
+    }
+    world.flush();
+}
+
+/// Resolves each entity's `Maybe` components with one `MaybeCommand` each.
+///
+/// The commands queued by the hooks are captured and discarded using `ManualResolution`.
+fn per_component_commands(world: &mut World, count: usize) {
+    world.init_resource::<ManualResolution>();
+    let entities: Vec<Entity> = (0..ENTITIES).map(|_| spawn_maybes(world, count)).collect();
+    world.remove_resource::<ManualResolution>();
+
+    let queue: [fn(&mut Commands, Entity); 12] = [
+        queue_maybe::<0>,
+        queue_maybe::<1>,
+        queue_maybe::<2>,
+        queue_maybe::<3>,
+        queue_maybe::<4>,
+        queue_maybe::<5>,
+        queue_maybe::<6>,
+        queue_maybe::<7>,
+        queue_maybe::<8>,
+        queue_maybe::<9>,
+        queue_maybe::<10>,
+        queue_maybe::<11>,
+    ];
+    let mut commands = world.commands();
+    for entity in entities {
+        for queue_maybe in &queue[..count] {
+            queue_maybe(&mut commands, entity);
+        }
+    }
+    world.flush();
+}
+
+fn queue_maybe<const N: usize>(commands: &mut Commands, entity: Entity) {
+    commands.add(MaybeCommand::<C<N>>::new(entity));
+}
+
+fn maybe_coalescing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("maybe_coalescing");
+    for count in [1, 4, 12] {
+        group.bench_with_input(BenchmarkId::new("coalesced", count), &count, |b, &n| {
+            b.iter(|| coalesced(&mut World::new(), n));
+        });
+        group.bench_with_input(
+            BenchmarkId::new("per_component_commands", count),
+            &count,
+            |b, &n| {
+                b.iter(|| per_component_commands(&mut World::new(), n));
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, maybe_coalescing);
+criterion_main!(benches);
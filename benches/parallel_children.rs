@@ -0,0 +1,51 @@
+//! Compares building the bundles of many children serially with `WithChildren`,
+//! against building them in parallel with `WithChildrenParallel`, when each bundle is expensive to build.
+//!
+//! Run with `cargo bench --bench parallel_children --features parallel`.
+
+use core::hint::black_box;
+
+use bevy_ecs::prelude::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use i_cant_believe_its_not_bsn::{spawn_children_parallel, WithChildren};
+
+#[derive(Component)]
+struct Item;
+
+/// A bundle that takes a while to build, standing in for procedural generation.
+fn expensive(index: usize) -> Item {
+    let mut state = index as u64;
+    for _ in 0..10_000 {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+    }
+    black_box(state);
+    Item
+}
+
+fn serial(world: &mut World, count: usize) {
+    world.spawn(WithChildren((0..count).map(expensive)));
+    world.flush();
+}
+
+fn parallel(world: &mut World, count: usize) {
+    world.spawn(spawn_children_parallel(count, expensive));
+    world.flush();
+}
+
+fn parallel_children(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parallel_children");
+    for count in [16, 1_000] {
+        group.bench_with_input(BenchmarkId::new("serial", count), &count, |b, &n| {
+            b.iter(|| serial(&mut World::new(), n));
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", count), &count, |b, &n| {
+            b.iter(|| parallel(&mut World::new(), n));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parallel_children);
+criterion_main!(benches);
@@ -0,0 +1,39 @@
+//! Compares spawning many children with `WithChildren` when the iterator's length is known,
+//! which spawns them as a batch with storage reserved up front, against an iterator that hides its length.
+//!
+//! Run with `cargo bench --bench reserve_entities`.
+
+use bevy_ecs::prelude::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use i_cant_believe_its_not_bsn::WithChildren;
+
+#[derive(Component)]
+struct Item;
+
+/// Spawns `count` children from an exact-size iterator, so that storage is reserved before spawning.
+fn reserved(world: &mut World, count: u32) {
+    world.spawn(WithChildren((0..count).map(|_| Item)));
+    world.flush();
+}
+
+/// Spawns `count` children from an iterator whose lower size bound is zero, so that nothing is reserved.
+fn unreserved(world: &mut World, count: u32) {
+    world.spawn(WithChildren((0..count).map(|_| Item).filter(|_| true)));
+    world.flush();
+}
+
+fn reserve_entities(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reserve_entities");
+    for count in [100, 10_000] {
+        group.bench_with_input(BenchmarkId::new("reserved", count), &count, |b, &n| {
+            b.iter(|| reserved(&mut World::new(), n));
+        });
+        group.bench_with_input(BenchmarkId::new("unreserved", count), &count, |b, &n| {
+            b.iter(|| unreserved(&mut World::new(), n));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, reserve_entities);
+criterion_main!(benches);
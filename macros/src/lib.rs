@@ -0,0 +1,90 @@
+//! Derive macros for `i-cant-believe-its-not-bsn`.
+//!
+//! These are re-exported by the main crate when its `derive` feature is enabled, and should be used from there.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, Type};
+
+/// The largest number of fields supported, matching the largest tuple that implements `OptionalBundles`.
+const MAX_FIELDS: usize = 8;
+
+/// Derives conversion of a struct of `Option` fields into a `MaybeChain` component.
+///
+/// See the documentation of the re-export in `i_cant_believe_its_not_bsn` for details.
+#[proc_macro_derive(MaybeBundle)]
+pub fn derive_maybe_bundle(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_maybe_bundle(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_maybe_bundle(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            Span::call_site(),
+            "`MaybeBundle` can only be derived for structs",
+        ));
+    };
+
+    let fields: Vec<_> = match &data.fields {
+        Fields::Named(fields) => fields.named.iter().collect(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+        Fields::Unit => Vec::new(),
+    };
+    if fields.is_empty() || fields.len() > MAX_FIELDS {
+        return Err(Error::new(
+            data.fields.span(),
+            format!("`MaybeBundle` requires between 1 and {MAX_FIELDS} fields"),
+        ));
+    }
+
+    for field in &fields {
+        if !is_option(&field.ty) {
+            return Err(Error::new(
+                field.ty.span(),
+                "every field of a `MaybeBundle` must be an `Option` of a bundle",
+            ));
+        }
+    }
+
+    let members = data.fields.members();
+    let field_types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<#name #type_generics>
+            for ::i_cant_believe_its_not_bsn::MaybeChain<(#(#field_types,)*)>
+            #where_clause
+        {
+            fn from(value: #name #type_generics) -> Self {
+                ::i_cant_believe_its_not_bsn::MaybeChain((#(value.#members,)*))
+            }
+        }
+
+        impl #impl_generics #name #type_generics #where_clause {
+            /// Converts this into a `MaybeChain` component,
+            /// which inserts each `Some` field into the entity it is added to, in declaration order.
+            pub fn into_maybe_chain(
+                self,
+            ) -> ::i_cant_believe_its_not_bsn::MaybeChain<(#(#field_types,)*)> {
+                ::core::convert::From::from(self)
+            }
+        }
+    })
+}
+
+/// Returns whether the type is syntactically an `Option`, such as `Option<T>` or `core::option::Option<T>`.
+fn is_option(ty: &Type) -> bool {
+    let Type::Path(type_path) = ty else {
+        return false;
+    };
+    type_path
+        .path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "Option")
+}
@@ -0,0 +1,308 @@
+use core::fmt::Debug;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use crate::deps::bevy_ecs::{
+    component::{ComponentHooks, ComponentId, StorageType},
+    prelude::*,
+    world::{Command, DeferredWorld},
+};
+use crate::deps::bevy_utils::synccell::SyncCell;
+use futures_core::Stream;
+
+use crate::{
+    mark_deferred, on_cancelled, on_missing, queue_deferred, Link, LinkChild, SpawnLinkedCommand,
+    SpawnsChildrenMarker, WithChildError,
+};
+
+/// A boxed [`Stream`] of bundles, as stored by [`WithChildrenStream`] and [`StreamChildren`].
+type BoxedStream<B> = Pin<Box<dyn Stream<Item = B> + Send>>;
+
+/// A component that, when added to an entity, will add a child entity for each item produced by the [`Stream`].
+///
+/// This integrates long-running asynchronous child generation, such as results arriving from `bevy_tasks` futures,
+/// with the ECS.
+/// Children are spawned in the order the stream produces their bundles.
+///
+/// When the component is resolved, the stream is polled until it is no longer ready,
+/// and a child is spawned for each item it produced.
+/// If the stream hasn't completed, it is moved into a [`StreamChildren`] component on the parent:
+/// add the [`poll_stream_children`] system to your schedule to poll it again each time that system runs,
+/// typically once per frame.
+///
+/// Streams are polled with a no-op waker, as they are polled on every run regardless:
+/// wake-ups are not needed, and waking has no effect.
+/// A stream is complete once it returns `Poll::Ready(None)`,
+/// at which point the [`StreamChildren`] component is removed from the parent.
+/// Streams that never complete are polled for as long as the parent exists.
+///
+/// This is only available with the `async` feature.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{poll_stream_children, StreamChildren, WithChildrenStream};
+///
+/// #[derive(Component)]
+/// struct Message(&'static str);
+///
+/// // Any `Stream` works: this one is always ready
+/// let messages = futures_lite::stream::iter([Message("hello"), Message("world")]);
+///
+/// let mut world = World::new();
+/// let mut schedule = Schedule::default();
+/// schedule.add_systems(poll_stream_children::<Message>);
+///
+/// let inbox = world.spawn(WithChildrenStream::new(messages)).id();
+/// schedule.run(&mut world);
+///
+/// assert_eq!(world.get::<Children>(inbox).unwrap().len(), 2);
+/// assert!(!world.entity(inbox).contains::<StreamChildren<Message>>());
+/// ```
+pub struct WithChildrenStream<B: Bundle>(pub SyncCell<BoxedStream<B>>);
+
+impl<B: Bundle> Debug for WithChildrenStream<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("WithChildrenStream").finish_non_exhaustive()
+    }
+}
+
+impl<B: Bundle> WithChildrenStream<B> {
+    /// Creates a new [`WithChildrenStream`] component, spawning a child for each item of the given stream.
+    pub fn new(stream: impl Stream<Item = B> + Send + 'static) -> Self {
+        Self(SyncCell::new(Box::pin(stream)))
+    }
+}
+
+impl<B: Bundle> Component for WithChildrenStream<B> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_children_stream_hook::<B>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildrenStream`] is added to an entity.
+///
+/// Generates a [`WithChildrenStreamCommand`].
+fn with_children_stream_hook<B: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildrenStreamCommand::<B>::new,
+    );
+}
+
+/// A [`Command`] that spawns the children that the stream of the [`WithChildrenStream<B>`] component of an entity has ready.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildrenStreamCommand<B> {
+    /// The entity holding the [`WithChildrenStream`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> WithChildrenStreamCommand<B> {
+    /// Creates a new [`WithChildrenStreamCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle> Command for WithChildrenStreamCommand<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithChildrenStream(mut stream)) = entity_mut.take::<WithChildrenStream<B>>()
+        else {
+            on_cancelled("WithChildrenStream");
+            return;
+        };
+        entity_mut.insert(SpawnsChildrenMarker);
+
+        let mut still_open = true;
+        let parent_exists = Link::spawn_linked(
+            world,
+            self.parent_entity,
+            poll_ready(stream.get().as_mut(), &mut still_open),
+            |_| {},
+        );
+
+        if parent_exists && still_open {
+            world
+                .entity_mut(self.parent_entity)
+                .insert(StreamChildren(stream));
+        }
+    }
+}
+
+/// A component holding a stream that hasn't completed yet, whose items will be spawned as children of this entity
+/// by the [`poll_stream_children`] system.
+///
+/// This is added by [`WithChildrenStream`], and removed once the stream has completed.
+#[derive(Component)]
+pub struct StreamChildren<B: Bundle>(pub SyncCell<BoxedStream<B>>);
+
+impl<B: Bundle> Debug for StreamChildren<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("StreamChildren").finish_non_exhaustive()
+    }
+}
+
+/// A system that polls each [`StreamChildren`] stream, spawning a child for each item it has ready.
+///
+/// Once a stream has completed, the [`StreamChildren`] component is removed.
+pub fn poll_stream_children<B: Bundle>(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut StreamChildren<B>)>,
+) {
+    for (parent_entity, mut stream_children) in query.iter_mut() {
+        let mut still_open = true;
+        let bundles: Vec<B> =
+            poll_ready(stream_children.0.get().as_mut(), &mut still_open).collect();
+        if !bundles.is_empty() {
+            commands.add(SpawnLinkedCommand {
+                parent_entity,
+                bundles,
+            });
+        }
+
+        if !still_open {
+            commands.entity(parent_entity).remove::<StreamChildren<B>>();
+        }
+    }
+}
+
+/// Returns an iterator over each item the stream has ready, which stops as soon as the stream is pending.
+///
+/// If the iterator stops because the stream has completed, `still_open` is set to `false`.
+fn poll_ready<'a, B>(
+    mut stream: Pin<&'a mut (dyn Stream<Item = B> + Send)>,
+    still_open: &'a mut bool,
+) -> impl Iterator<Item = B> + 'a {
+    core::iter::from_fn(move || {
+        if !*still_open {
+            return None;
+        }
+
+        let waker = noop_waker();
+        let mut context = Context::from_waker(&waker);
+        match stream.as_mut().poll_next(&mut context) {
+            Poll::Ready(Some(bundle)) => Some(bundle),
+            Poll::Ready(None) => {
+                *still_open = false;
+                None
+            }
+            Poll::Pending => None,
+        }
+    })
+}
+
+/// Returns a [`Waker`] that does nothing when woken.
+///
+/// This is `Waker::noop`, which is too recent for our minimum supported Rust version.
+fn noop_waker() -> Waker {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(
+        // Cloning returns another no-op waker
+        |_| RawWaker::new(core::ptr::null(), &VTABLE),
+        // Waking does nothing
+        |_| {},
+        |_| {},
+        // There is no data to drop
+        |_| {},
+    );
+
+    // SAFETY: none of the vtable functions use the data pointer, so the contract of `RawWaker` is trivially upheld.
+    unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &VTABLE)) }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::bevy_ecs::system::RunSystemOnce;
+    use crate::deps::bevy_hierarchy::Children;
+
+    use super::*;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct B(u8);
+
+    /// A stream that produces `0..len`, only becoming ready on every other poll.
+    struct Alternating {
+        next: u8,
+        len: u8,
+        ready: bool,
+    }
+
+    impl Stream for Alternating {
+        type Item = B;
+
+        fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<B>> {
+            if self.next == self.len {
+                return Poll::Ready(None);
+            }
+
+            self.ready = !self.ready;
+            if !self.ready {
+                return Poll::Pending;
+            }
+
+            self.next += 1;
+            Poll::Ready(Some(B(self.next - 1)))
+        }
+    }
+
+    fn children_values(world: &World, parent: Entity) -> Vec<u8> {
+        world
+            .get::<Children>(parent)
+            .map(|children| {
+                children
+                    .iter()
+                    .map(|&child| world.get::<B>(child).unwrap().0)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn stream_to_completion() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(WithChildrenStream::new(Alternating {
+                next: 0,
+                len: 3,
+                ready: false,
+            }))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        // The first poll is ready, the second is pending
+        assert_eq!(children_values(&world, parent), vec![0]);
+        assert!(world.entity(parent).contains::<StreamChildren<B>>());
+
+        world.run_system_once(poll_stream_children::<B>);
+        assert_eq!(children_values(&world, parent), vec![0, 1]);
+
+        // The stream reports that it has completed as soon as it's polled after its last item
+        world.run_system_once(poll_stream_children::<B>);
+        assert_eq!(children_values(&world, parent), vec![0, 1, 2]);
+        assert!(!world.entity(parent).contains::<StreamChildren<B>>());
+        assert!(world.entity(parent).contains::<SpawnsChildrenMarker>());
+    }
+}
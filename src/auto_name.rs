@@ -0,0 +1,94 @@
+//! Automatic [`Name`] assignment for spawned children, to aid debugging.
+//!
+//! This is enabled by the `auto_name` feature.
+
+use crate::deps::bevy_ecs::prelude::*;
+use crate::deps::bevy_hierarchy::Children;
+use bevy_core::Name;
+
+/// Names children after their parent and their position among the parent's children,
+/// such as `"Parent/child[0]"`.
+///
+/// Children are only named if the parent has a [`Name`], and the child does not already have one.
+pub(crate) struct ChildNamer {
+    parent_name: Option<String>,
+    next_index: usize,
+}
+
+impl ChildNamer {
+    /// Creates a [`ChildNamer`] for children that will be appended to the given parent.
+    pub(crate) fn new(parent: &EntityWorldMut) -> Self {
+        Self {
+            parent_name: parent.get::<Name>().map(|name| name.as_str().to_owned()),
+            next_index: parent
+                .get::<Children>()
+                .map_or(0, |children| children.len()),
+        }
+    }
+
+    /// Names the next child, which must be appended to the parent's children in order.
+    pub(crate) fn name_next(&mut self, mut child: EntityWorldMut) {
+        let index = self.next_index;
+        self.next_index += 1;
+
+        let Some(parent_name) = &self.parent_name else {
+            return;
+        };
+        if !child.contains::<Name>() {
+            child.insert(Name::new(format!("{parent_name}/child[{index}]")));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{WithChild, WithChildren};
+
+    #[derive(Component)]
+    struct A;
+
+    fn child_names(world: &World, parent: Entity) -> Vec<Option<&str>> {
+        world
+            .get::<Children>(parent)
+            .unwrap()
+            .iter()
+            .map(|&child| world.get::<Name>(child).map(Name::as_str))
+            .collect()
+    }
+
+    #[test]
+    fn named_parent() {
+        let mut world = World::default();
+
+        let parent = world.spawn((Name::new("Menu"), WithChild(A))).id();
+        world.flush();
+        world.entity_mut(parent).insert(WithChildren(vec![A, A]));
+        world.flush();
+        // Existing names are never overwritten
+        world
+            .entity_mut(parent)
+            .insert(WithChild((A, Name::new("Custom"))));
+        world.flush();
+
+        assert_eq!(
+            child_names(&world, parent),
+            vec![
+                Some("Menu/child[0]"),
+                Some("Menu/child[1]"),
+                Some("Menu/child[2]"),
+                Some("Custom")
+            ]
+        );
+    }
+
+    #[test]
+    fn anonymous_parent() {
+        let mut world = World::default();
+
+        let parent = world.spawn(WithChild(A)).id();
+        world.flush();
+
+        assert_eq!(child_names(&world, parent), vec![None]);
+    }
+}
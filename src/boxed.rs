@@ -0,0 +1,289 @@
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+use crate::deps::bevy_ecs::{
+    component::{ComponentHooks, ComponentId, StorageType},
+    prelude::*,
+    world::{Command, DeferredWorld},
+};
+
+use crate::{
+    mark_deferred, on_cancelled, on_missing, queue_deferred, Link, LinkChild, WithChildError,
+};
+
+/// A type-erased [`Bundle`], whose concrete type can be chosen at runtime.
+///
+/// This trait is implemented for all bundles, and is used by [`WithChildBoxed`].
+pub trait BoxedBundle: Send + Sync + 'static {
+    /// Inserts the boxed bundle into the given entity.
+    fn insert_into(self: Box<Self>, entity_mut: &mut EntityWorldMut);
+}
+
+impl<B: Bundle> BoxedBundle for B {
+    fn insert_into(self: Box<Self>, entity_mut: &mut EntityWorldMut) {
+        entity_mut.insert(*self);
+    }
+}
+
+/// A component that, when added to an entity, will add a child entity with the given boxed bundle.
+///
+/// Unlike [`WithChild`](crate::WithChild), the type of the child's bundle does not need to be known statically,
+/// which is useful when choosing between several different kinds of children at runtime.
+/// This comes at the cost of a heap allocation and dynamic dispatch.
+///
+/// As [`WithChildBoxed`] is not generic, each entity can only hold one at a time.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::WithChildBoxed;
+///
+/// #[derive(Component)]
+/// struct Goblin;
+///
+/// #[derive(Component)]
+/// struct Dragon { wingspan: f32 }
+///
+/// fn spawn_enemy(mut commands: Commands, is_boss: bool) {
+///     let enemy = if is_boss {
+///         WithChildBoxed::new(Dragon { wingspan: 12.0 })
+///     } else {
+///         WithChildBoxed::new(Goblin)
+///     };
+///
+///     commands.spawn(enemy);
+/// }
+/// ```
+pub struct WithChildBoxed(pub Box<dyn BoxedBundle>);
+
+impl WithChildBoxed {
+    /// Creates a new [`WithChildBoxed`] component, boxing the given bundle.
+    pub fn new(bundle: impl Bundle) -> Self {
+        Self(Box::new(bundle))
+    }
+}
+
+impl Debug for WithChildBoxed {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("WithChildBoxed").finish_non_exhaustive()
+    }
+}
+
+impl Component for WithChildBoxed {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_child_boxed_hook);
+    }
+}
+
+/// A hook that runs whenever [`WithChildBoxed`] is added to an entity.
+///
+/// Generates a [`WithChildBoxedCommand`].
+fn with_child_boxed_hook(mut world: DeferredWorld<'_>, entity: Entity, component_id: ComponentId) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(&mut world, component_id, entity, WithChildBoxedCommand::new);
+}
+
+/// A [`Command`] that spawns the child stored in the [`WithChildBoxed`] component of an entity.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildBoxedCommand {
+    /// The entity holding the [`WithChildBoxed`] component, which will become the parent.
+    pub parent_entity: Entity,
+}
+
+impl WithChildBoxedCommand {
+    /// Creates a new [`WithChildBoxedCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self { parent_entity }
+    }
+}
+
+impl Command for WithChildBoxedCommand {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(with_child_component) = entity_mut.take::<WithChildBoxed>() else {
+            on_cancelled("WithChildBoxed");
+            return;
+        };
+
+        let mut child_entity_mut = Link::spawn_child(world, ());
+        with_child_component.0.insert_into(&mut child_entity_mut);
+        let child_entity = child_entity_mut.id();
+        Link::link_child(world, self.parent_entity, child_entity);
+    }
+}
+
+/// A component that, when added to an entity, will add a child entity for each item generated by the boxed iterator.
+///
+/// Unlike [`WithChildren`](crate::WithChildren), which is generic over the exact iterator type,
+/// this type-erases the iterator so that all call sites producing the same bundle type share one command implementation.
+/// This reduces monomorphization bloat when many distinct generators (such as closures) are used,
+/// at the cost of a heap allocation and dynamic dispatch for each item.
+///
+/// Usually constructed via [`spawn_children_boxed`].
+pub struct WithChildrenBoxed<B: Bundle>(pub Box<dyn Iterator<Item = B> + Send + Sync>);
+
+impl<B: Bundle> Debug for WithChildrenBoxed<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("WithChildrenBoxed").finish_non_exhaustive()
+    }
+}
+
+impl<B: Bundle> Component for WithChildrenBoxed<B> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_children_boxed_hook::<B>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildrenBoxed`] is added to an entity.
+///
+/// Generates a [`WithChildrenBoxedCommand`].
+fn with_children_boxed_hook<B: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildrenBoxedCommand::<B>::new,
+    );
+}
+
+/// A [`Command`] that spawns the children generated by the [`WithChildrenBoxed<B>`] component of an entity.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildrenBoxedCommand<B> {
+    /// The entity holding the [`WithChildrenBoxed`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> WithChildrenBoxedCommand<B> {
+    /// Creates a new [`WithChildrenBoxedCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle> Command for WithChildrenBoxedCommand<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(with_children_component) = entity_mut.take::<WithChildrenBoxed<B>>() else {
+            on_cancelled("WithChildrenBoxed");
+            return;
+        };
+
+        for child_bundle in with_children_component.0 {
+            let child_entity = Link::spawn_child(world, child_bundle).id();
+            if !Link::link_child(world, self.parent_entity, child_entity) {
+                return;
+            }
+        }
+    }
+}
+
+/// Creates a [`WithChildrenBoxed`] component, boxing the given iterator.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::spawn_children_boxed;
+///
+/// #[derive(Component)]
+/// struct Slot(usize);
+///
+/// let mut world = World::new();
+/// // Both generators share the same component type, and thus the same command implementation
+/// let inventory = world.spawn(spawn_children_boxed((0..4).map(Slot))).id();
+/// let hotbar = world.spawn(spawn_children_boxed((0..2).map(|i| Slot(i + 10)))).id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(inventory).unwrap().len(), 4);
+/// assert_eq!(world.get::<Children>(hotbar).unwrap().len(), 2);
+/// ```
+pub fn spawn_children_boxed<B: Bundle>(
+    iter: impl IntoIterator<Item = B, IntoIter: Send + Sync + 'static>,
+) -> WithChildrenBoxed<B> {
+    WithChildrenBoxed(Box::new(iter.into_iter()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::bevy_hierarchy::Children;
+
+    use super::*;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct A;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct B(u8);
+
+    #[test]
+    fn with_child_boxed() {
+        let mut world = World::default();
+
+        let bundles = [WithChildBoxed::new(A), WithChildBoxed::new((B(1), A))];
+        let parents: Vec<Entity> = bundles
+            .into_iter()
+            .map(|bundle| world.spawn(bundle).id())
+            .collect();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        for parent in parents.iter() {
+            assert!(!world.entity(*parent).contains::<WithChildBoxed>());
+        }
+
+        let children = world.get::<Children>(parents[0]).unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(world.get::<A>(children[0]), Some(&A));
+        assert_eq!(world.get::<B>(children[0]), None);
+
+        let children = world.get::<Children>(parents[1]).unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(world.get::<A>(children[0]), Some(&A));
+        assert_eq!(world.get::<B>(children[0]), Some(&B(1)));
+    }
+    #[test]
+    fn with_children_boxed() {
+        let mut world = World::default();
+
+        let even = world
+            .spawn(spawn_children_boxed((0..3).map(|i| B(i * 2))))
+            .id();
+        let odd = world.spawn(spawn_children_boxed(vec![B(1), B(3)])).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world.entity(even).contains::<WithChildrenBoxed<B>>());
+        let children = world.get::<Children>(even).unwrap();
+        assert_eq!(children.len(), 3);
+        for (i, &child) in children.iter().enumerate() {
+            assert_eq!(world.get::<B>(child), Some(&B(i as u8 * 2)));
+        }
+
+        assert_eq!(world.get::<Children>(odd).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,283 @@
+use core::fmt::Debug;
+
+use crate::deps::bevy_ecs::{
+    component::{ComponentHooks, ComponentId, StorageType},
+    prelude::*,
+    world::{Command, DeferredWorld},
+};
+
+use crate::{
+    mark_deferred, on_cancelled, on_missing, queue_deferred, spawn_node, BoxedBundle, Link,
+    LinkChild, SpawnableNode, WithChildError,
+};
+
+/// A fluent builder for entity hierarchies, as an alternative to nesting [`WithChild`](crate::WithChild)
+/// and [`WithChildren`](crate::WithChildren) by hand.
+///
+/// Each method adds children to the entity being built, in order.
+/// Calling [`build`](Self::build) produces a bundle containing the root bundle,
+/// along with a [`WithBoxedDescendants`] component which spawns the whole hierarchy beneath it.
+///
+/// As children are boxed, their bundle types don't need to match,
+/// and several children can share a bundle type without their components colliding on the parent,
+/// unlike [`WithChild`](crate::WithChild).
+/// This comes at the cost of a heap allocation and dynamic dispatch for each child.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::HierarchyBuilder;
+///
+/// #[derive(Component)]
+/// struct Menu;
+///
+/// #[derive(Component)]
+/// struct Button(&'static str);
+///
+/// #[derive(Component)]
+/// struct Icon;
+///
+/// let menu = HierarchyBuilder::new(Menu)
+///     .child_with(Button("Play"), |button| button.child(Icon))
+///     .children(["Options", "Quit"].map(Button))
+///     .build();
+///
+/// let mut world = World::new();
+/// let menu = world.spawn(menu).id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(menu).unwrap().len(), 3);
+/// ```
+pub struct HierarchyBuilder<R: Bundle> {
+    root: R,
+    children: Vec<BoxedNode>,
+}
+
+impl<R: Bundle> HierarchyBuilder<R> {
+    /// Creates a new [`HierarchyBuilder`] for an entity with the given bundle, and no children.
+    pub const fn new(root: R) -> Self {
+        Self {
+            root,
+            children: Vec::new(),
+        }
+    }
+
+    /// Adds a child with the given bundle.
+    pub fn child(mut self, bundle: impl Bundle) -> Self {
+        self.children.push(BoxedNode::leaf(bundle));
+        self
+    }
+
+    /// Adds a child for each bundle generated by the iterator, in order.
+    ///
+    /// The iterator is consumed immediately.
+    pub fn children<B: Bundle>(mut self, bundles: impl IntoIterator<Item = B>) -> Self {
+        self.children
+            .extend(bundles.into_iter().map(BoxedNode::leaf));
+        self
+    }
+
+    /// Adds a child with the given bundle, whose own children are added by `build_child`.
+    pub fn child_with<B: Bundle>(
+        mut self,
+        bundle: B,
+        build_child: impl FnOnce(HierarchyBuilder<B>) -> HierarchyBuilder<B>,
+    ) -> Self {
+        let HierarchyBuilder { root, children } = build_child(HierarchyBuilder::new(bundle));
+        self.children.push(BoxedNode {
+            bundle: Box::new(root),
+            children,
+        });
+        self
+    }
+
+    /// Finishes building, returning a bundle that spawns the entity's descendants when added to an entity.
+    pub fn build(self) -> (R, WithBoxedDescendants) {
+        (self.root, WithBoxedDescendants(self.children))
+    }
+}
+
+impl<R: Bundle + Debug> Debug for HierarchyBuilder<R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HierarchyBuilder")
+            .field("root", &self.root)
+            .field("children", &self.children)
+            .finish()
+    }
+}
+
+/// A node in a tree of type-erased bundles, as built by [`HierarchyBuilder`].
+pub struct BoxedNode {
+    /// The bundle to insert on the entity spawned for this node.
+    pub bundle: Box<dyn BoxedBundle>,
+    /// The nodes to spawn as children of this node, in order.
+    pub children: Vec<BoxedNode>,
+}
+
+impl BoxedNode {
+    /// Creates a new [`BoxedNode`] with no children, boxing the given bundle.
+    pub fn leaf(bundle: impl Bundle) -> Self {
+        Self {
+            bundle: Box::new(bundle),
+            children: Vec::new(),
+        }
+    }
+}
+
+impl Debug for BoxedNode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BoxedNode")
+            .field("children", &self.children)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A component that, when added to an entity, will spawn each of the given [`BoxedNode`]s as children of that entity,
+/// along with all of their descendants.
+///
+/// The whole hierarchy is spawned by a single command, so it is complete after one flush, however deep it is.
+/// Nodes are spawned in the same way as those of [`WithDescendants`](crate::WithDescendants).
+///
+/// Usually constructed via [`HierarchyBuilder`].
+#[derive(Debug)]
+pub struct WithBoxedDescendants(pub Vec<BoxedNode>);
+
+impl Component for WithBoxedDescendants {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_boxed_descendants_hook);
+    }
+}
+
+/// A hook that runs whenever [`WithBoxedDescendants`] is added to an entity.
+///
+/// Generates a [`WithBoxedDescendantsCommand`].
+fn with_boxed_descendants_hook(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithBoxedDescendantsCommand::new,
+    );
+}
+
+/// A [`Command`] that spawns the nodes stored in the [`WithBoxedDescendants`] component of an entity.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithBoxedDescendantsCommand {
+    /// The entity holding the [`WithBoxedDescendants`] component, which will become the parent of its nodes.
+    pub parent_entity: Entity,
+}
+
+impl WithBoxedDescendantsCommand {
+    /// Creates a new [`WithBoxedDescendantsCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self { parent_entity }
+    }
+}
+
+impl Command for WithBoxedDescendantsCommand {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithBoxedDescendants(nodes)) = entity_mut.take::<WithBoxedDescendants>() else {
+            on_cancelled("WithBoxedDescendants");
+            return;
+        };
+
+        for node in nodes {
+            spawn_node(world, self.parent_entity, node);
+        }
+    }
+}
+
+impl SpawnableNode for BoxedNode {
+    fn spawn_entity(self, world: &mut World) -> (Entity, Vec<Self>) {
+        let BoxedNode { bundle, children } = self;
+        let mut node_entity_mut = Link::spawn_child(world, ());
+        bundle.insert_into(&mut node_entity_mut);
+        (node_entity_mut.id(), children)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assert_hierarchy, FailurePolicy};
+
+    #[derive(Component)]
+    struct Root;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct A(u8);
+
+    #[derive(Component, PartialEq, Debug)]
+    struct B(u8);
+
+    #[test]
+    fn two_level_hierarchy() {
+        let mut world = World::default();
+
+        let hierarchy = HierarchyBuilder::new(Root)
+            .child(A(0))
+            .child_with(B(1), |b| b.child(A(2)).children([B(3), B(4)]))
+            .children([A(5), A(6)])
+            .build();
+        let root = world.spawn(hierarchy).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world.entity(root).contains::<WithBoxedDescendants>());
+        assert_hierarchy!(world, root, [Root] {
+            [A = A(0)],
+            [B = B(1)] {
+                [A = A(2)],
+                [B = B(3)],
+                [B = B(4)],
+            },
+            [A = A(5)],
+            [A = A(6)],
+        });
+    }
+
+    #[test]
+    fn despawned_parent_no_orphan() {
+        /// Queues a despawn of the root, then immediately spawns its descendants
+        struct DespawnThenSpawnDescendants(Entity);
+
+        impl Command for DespawnThenSpawnDescendants {
+            fn apply(self, world: &mut World) {
+                world.commands().entity(self.0).despawn();
+                let (_, descendants) = HierarchyBuilder::new(())
+                    .child_with(A(0), |a| a.child(B(1)))
+                    .child(A(2))
+                    .build();
+                world.entity_mut(self.0).insert(descendants);
+                // The despawn is applied when spawning the first node flushes the world
+                WithBoxedDescendantsCommand::new(self.0).apply(world);
+            }
+        }
+
+        let mut world = World::default();
+        world.insert_resource(FailurePolicy::Ignore);
+
+        let root = world.spawn(Root).id();
+        world.commands().add(DespawnThenSpawnDescendants(root));
+        world.flush();
+
+        assert!(world.get_entity(root).is_none());
+        assert_eq!(world.query::<&A>().iter(&world).count(), 0);
+        assert_eq!(world.query::<&B>().iter(&world).count(), 0);
+    }
+}
@@ -0,0 +1,271 @@
+use core::fmt::Debug;
+use core::marker::PhantomData;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use crate::deps::bevy_ecs::{
+    component::{ComponentHooks, ComponentId, StorageType},
+    prelude::*,
+    world::{Command, DeferredWorld},
+};
+use crate::deps::bevy_utils::synccell::SyncCell;
+
+use crate::{
+    mark_deferred, on_cancelled, on_missing, queue_deferred, Link, LinkChild, SpawnLinkedCommand,
+    WithChildError,
+};
+
+/// A component that, when added to an entity, will add a child entity for each item
+/// currently buffered in the channel.
+///
+/// This bridges data generated on background tasks with ECS spawning.
+/// Items are drained without blocking, and children are spawned in the order the items were sent.
+/// No backpressure is applied by this crate: use a bounded [`sync_channel`](std::sync::mpsc::sync_channel)
+/// if producers should wait for items to be consumed.
+///
+/// If the channel is still open once all buffered items have been drained,
+/// the receiver is moved into a [`ChannelChildren`] component on the parent.
+/// Add the [`drain_channel_children`] system to your schedule to keep spawning children as new items arrive.
+///
+/// ```rust
+/// use std::sync::mpsc::channel;
+///
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::WithChildrenFromChannel;
+///
+/// #[derive(Component)]
+/// struct SearchResult(String);
+///
+/// let (sender, receiver) = channel();
+/// std::thread::spawn(move || {
+///     for result in ["apple", "apricot"] {
+///         sender.send(SearchResult(result.to_string())).unwrap();
+///     }
+/// })
+/// .join()
+/// .unwrap();
+///
+/// let mut world = World::new();
+/// let results = world.spawn(WithChildrenFromChannel::new(receiver)).id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(results).unwrap().len(), 2);
+/// ```
+pub struct WithChildrenFromChannel<B: Bundle>(pub SyncCell<Receiver<B>>);
+
+impl<B: Bundle> Debug for WithChildrenFromChannel<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("WithChildrenFromChannel")
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B: Bundle> WithChildrenFromChannel<B> {
+    /// Creates a new [`WithChildrenFromChannel`] component, reading from the given receiver.
+    pub fn new(receiver: Receiver<B>) -> Self {
+        Self(SyncCell::new(receiver))
+    }
+}
+
+impl<B: Bundle> Component for WithChildrenFromChannel<B> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_children_from_channel_hook::<B>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildrenFromChannel`] is added to an entity.
+///
+/// Generates a [`WithChildrenFromChannelCommand`].
+fn with_children_from_channel_hook<B: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildrenFromChannelCommand::<B>::new,
+    );
+}
+
+/// A [`Command`] that spawns the children buffered in the channel of the [`WithChildrenFromChannel<B>`] component of an entity.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildrenFromChannelCommand<B> {
+    /// The entity holding the [`WithChildrenFromChannel`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> WithChildrenFromChannelCommand<B> {
+    /// Creates a new [`WithChildrenFromChannelCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle> Command for WithChildrenFromChannelCommand<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithChildrenFromChannel(mut receiver)) =
+            entity_mut.take::<WithChildrenFromChannel<B>>()
+        else {
+            on_cancelled("WithChildrenFromChannel");
+            return;
+        };
+
+        let mut still_open = true;
+        let parent_exists = Link::spawn_linked(
+            world,
+            self.parent_entity,
+            drain(receiver.get(), &mut still_open),
+            |_| {},
+        );
+
+        if parent_exists && still_open {
+            world
+                .entity_mut(self.parent_entity)
+                .insert(ChannelChildren(receiver));
+        }
+    }
+}
+
+/// A component holding a still-open channel, whose items will be spawned as children of this entity
+/// by the [`drain_channel_children`] system.
+///
+/// This is added by [`WithChildrenFromChannel`], and removed once the channel is closed and fully drained.
+#[derive(Component)]
+pub struct ChannelChildren<B: Bundle>(pub SyncCell<Receiver<B>>);
+
+impl<B: Bundle> Debug for ChannelChildren<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("ChannelChildren").finish_non_exhaustive()
+    }
+}
+
+/// A system that spawns a child for each item that has arrived in a [`ChannelChildren`] channel.
+///
+/// Once a channel's senders have all been dropped and its buffer has been drained,
+/// the [`ChannelChildren`] component is removed.
+pub fn drain_channel_children<B: Bundle>(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut ChannelChildren<B>)>,
+) {
+    for (parent_entity, mut channel_children) in query.iter_mut() {
+        let mut still_open = true;
+        let bundles: Vec<B> = drain(channel_children.0.get(), &mut still_open).collect();
+        if !bundles.is_empty() {
+            commands.add(SpawnLinkedCommand {
+                parent_entity,
+                bundles,
+            });
+        }
+
+        if !still_open {
+            commands
+                .entity(parent_entity)
+                .remove::<ChannelChildren<B>>();
+        }
+    }
+}
+
+/// Returns an iterator over each item currently buffered in the channel, which never blocks.
+///
+/// If the iterator stops because the channel has been closed, `still_open` is set to `false`.
+fn drain<'a, B>(
+    receiver: &'a Receiver<B>,
+    still_open: &'a mut bool,
+) -> impl Iterator<Item = B> + 'a {
+    core::iter::from_fn(move || match receiver.try_recv() {
+        Ok(bundle) => Some(bundle),
+        Err(TryRecvError::Empty) => None,
+        Err(TryRecvError::Disconnected) => {
+            *still_open = false;
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::channel;
+
+    use crate::deps::bevy_ecs::system::RunSystemOnce;
+    use crate::deps::bevy_hierarchy::Children;
+
+    use super::*;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct B(u8);
+
+    fn children_values(world: &World, parent: Entity) -> Vec<u8> {
+        world
+            .get::<Children>(parent)
+            .map(|children| {
+                children
+                    .iter()
+                    .map(|&child| world.get::<B>(child).unwrap().0)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn buffered_items() {
+        let mut world = World::default();
+
+        let (sender, receiver) = channel();
+        for i in 0..4 {
+            sender.send(B(i)).unwrap();
+        }
+        drop(sender);
+
+        let parent = world.spawn(WithChildrenFromChannel::new(receiver)).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(children_values(&world, parent), vec![0, 1, 2, 3]);
+        assert!(!world
+            .entity(parent)
+            .contains::<WithChildrenFromChannel<B>>());
+        assert!(!world.entity(parent).contains::<ChannelChildren<B>>());
+    }
+
+    #[test]
+    fn open_channel() {
+        let mut world = World::default();
+
+        let (sender, receiver) = channel();
+        sender.send(B(0)).unwrap();
+
+        let parent = world.spawn(WithChildrenFromChannel::new(receiver)).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(children_values(&world, parent), vec![0]);
+        assert!(world.entity(parent).contains::<ChannelChildren<B>>());
+
+        sender.send(B(1)).unwrap();
+        sender.send(B(2)).unwrap();
+        world.run_system_once(drain_channel_children::<B>);
+        assert_eq!(children_values(&world, parent), vec![0, 1, 2]);
+        assert!(world.entity(parent).contains::<ChannelChildren<B>>());
+
+        drop(sender);
+        world.run_system_once(drain_channel_children::<B>);
+        assert!(!world.entity(parent).contains::<ChannelChildren<B>>());
+    }
+}
@@ -0,0 +1,59 @@
+//! Debug-only detection of unresolved [`WithChild`](crate::WithChild) components being overwritten.
+//!
+//! Inserting a component onto an entity that already has one of the same type replaces it.
+//! As the earlier value was never resolved, its child is silently lost.
+//! This is compiled out entirely in release builds.
+
+use core::cell::RefCell;
+
+use crate::deps::bevy_ecs::{component::ComponentId, prelude::*, world::DeferredWorld};
+use crate::deps::bevy_utils::tracing::warn;
+
+thread_local! {
+    /// Components whose `on_add` hook has run, but whose `on_insert` hook has not yet.
+    ///
+    /// Hooks run synchronously on the thread performing the insertion,
+    /// and every `on_add` hook of a bundle runs before any of its `on_insert` hooks,
+    /// so each entry is removed by the matching `on_insert` hook of the same insertion.
+    static FRESHLY_ADDED: RefCell<Vec<(Entity, ComponentId)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Records that the component was newly added to the entity. Call this from its `on_add` hook.
+pub(crate) fn mark_added(entity: Entity, component_id: ComponentId) {
+    FRESHLY_ADDED.with_borrow_mut(|added| added.push((entity, component_id)));
+}
+
+/// Warns if this insertion replaced an existing, unresolved copy of the component.
+/// Call this from its `on_insert` hook.
+///
+/// If the component has a slot, it is included in the warning.
+pub(crate) fn warn_if_replaced(
+    world: &DeferredWorld,
+    entity: Entity,
+    component_id: ComponentId,
+    slot: Option<u8>,
+) {
+    let was_added = FRESHLY_ADDED.with_borrow_mut(|added| {
+        added
+            .iter()
+            .position(|&entry| entry == (entity, component_id))
+            .map(|index| added.swap_remove(index))
+            .is_some()
+    });
+    if was_added {
+        return;
+    }
+
+    let name = world
+        .components()
+        .get_name(component_id)
+        .unwrap_or("<unknown component>");
+    match slot {
+        Some(slot) => warn!(
+            "{name} in slot {slot} was inserted on {entity:?}, replacing an unresolved {name}: the replaced child will never be spawned"
+        ),
+        None => warn!(
+            "{name} was inserted on {entity:?}, replacing an unresolved {name}: the replaced child will never be spawned"
+        ),
+    }
+}
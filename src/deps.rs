@@ -0,0 +1,10 @@
+//! The Bevy crates that this crate is built on.
+//!
+//! Every path to them goes through this module, so that, with the `bevy` feature,
+//! they are all sourced from the re-exports of the `bevy` umbrella crate instead of the granular crates.
+
+#[cfg(feature = "bevy")]
+pub(crate) use bevy::{ecs as bevy_ecs, hierarchy as bevy_hierarchy, utils as bevy_utils};
+
+#[cfg(not(feature = "bevy"))]
+pub(crate) use ::{bevy_ecs, bevy_hierarchy, bevy_utils};
@@ -0,0 +1,149 @@
+use crate::deps::bevy_ecs::{prelude::*, world::Command};
+
+use crate::{on_missing, ChildBuffer, Link, LinkChild, WithChildError};
+
+/// A [`Command`] that despawns the children of an entity, keeping the entity itself.
+///
+/// Afterwards, the entity no longer has a [`Children`](bevy_hierarchy::Children) component.
+/// If `recursive` is `true`, all descendants are despawned.
+/// Otherwise, only the direct children are despawned: their own children are kept,
+/// but are detached from the hierarchy, becoming roots.
+///
+/// This pairs with this crate's spawning components, so hierarchies can be torn down with the same vocabulary
+/// they were built with. Usually constructed via [`despawn_children`] or [`despawn_children_recursive`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DespawnChildren {
+    /// The entity whose children should be despawned.
+    pub parent_entity: Entity,
+    /// Whether the children's descendants should be despawned too.
+    pub recursive: bool,
+}
+
+impl DespawnChildren {
+    /// Creates a new [`DespawnChildren`] command targeting the given entity.
+    pub const fn new(parent_entity: Entity, recursive: bool) -> Self {
+        Self {
+            parent_entity,
+            recursive,
+        }
+    }
+}
+
+impl Command for DespawnChildren {
+    fn apply(self, world: &mut World) {
+        if world.get_entity(self.parent_entity).is_none() {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        }
+
+        let children = ChildBuffer::from(Link::children(world, self.parent_entity));
+        Link::unlink_children(world, self.parent_entity);
+
+        for child_entity in children {
+            if self.recursive {
+                Link::despawn_recursive(world, child_entity);
+            } else {
+                Link::unlink_children(world, child_entity);
+                world.despawn(child_entity);
+            }
+        }
+    }
+}
+
+/// Creates a [`DespawnChildren`] command, which despawns the direct children of `parent`.
+///
+/// Grandchildren are not despawned, but are detached from the hierarchy.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{despawn_children, WithChildren};
+///
+/// #[derive(Component)]
+/// struct Slot;
+///
+/// let mut world = World::new();
+/// let inventory = world.spawn(WithChildren([Slot, Slot])).id();
+/// world.flush();
+///
+/// world.commands().add(despawn_children(inventory));
+/// world.flush();
+///
+/// assert!(world.get::<Children>(inventory).is_none());
+/// ```
+pub const fn despawn_children(parent: Entity) -> DespawnChildren {
+    DespawnChildren::new(parent, false)
+}
+
+/// Creates a [`DespawnChildren`] command, which despawns all of the descendants of `parent`.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{despawn_children_recursive, WithChild};
+///
+/// #[derive(Component)]
+/// struct Menu;
+///
+/// let mut world = World::new();
+/// let root = world.spawn(WithChild((Menu, WithChild(Menu)))).id();
+/// world.flush();
+///
+/// world.commands().add(despawn_children_recursive(root));
+/// world.flush();
+///
+/// assert!(world.get::<Children>(root).is_none());
+/// assert_eq!(world.query::<&Menu>().iter(&world).count(), 0);
+/// ```
+pub const fn despawn_children_recursive(parent: Entity) -> DespawnChildren {
+    DespawnChildren::new(parent, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::bevy_hierarchy::{Children, Parent};
+
+    use super::*;
+    use crate::{WithChild, WithChildren};
+
+    #[derive(Component)]
+    struct A;
+
+    #[test]
+    fn despawn_direct_children() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn((A, WithChildren(vec![(A, WithChild(A)), (A, WithChild(A))])))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+        assert_eq!(world.entities().len(), 5);
+
+        world.commands().add(despawn_children(parent));
+        world.flush();
+
+        assert!(world.get::<Children>(parent).is_none());
+        // The grandchildren survive, as roots
+        assert_eq!(world.entities().len(), 3);
+        assert_eq!(world.query::<&Parent>().iter(&world).count(), 0);
+    }
+
+    #[test]
+    fn despawn_all_descendants() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn((A, WithChildren(vec![(A, WithChild(A)), (A, WithChild(A))])))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        world.commands().add(despawn_children_recursive(parent));
+        world.flush();
+
+        assert!(world.get::<Children>(parent).is_none());
+        assert_eq!(world.entities().len(), 1);
+        assert!(world.get_entity(parent).is_some());
+    }
+}
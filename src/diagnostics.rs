@@ -0,0 +1,225 @@
+use crate::deps::bevy_ecs::prelude::*;
+#[cfg(feature = "diagnostics")]
+use {
+    bevy_app::{App, Last, Plugin},
+    bevy_diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    core::sync::atomic::{AtomicU64, Ordering},
+};
+
+/// A resource that counts the work done by this crate's deferred commands, for observability.
+///
+/// Counting only happens while this resource exists, so there is little overhead for apps that don't use it,
+/// and none at all without the `diagnostics` feature.
+/// The counters accumulate until [`WithChildDiagnostics::reset`] is called:
+/// [`WithChildDiagnosticsPlugin`] reports and resets them once per frame.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::{WithChildDiagnostics, WithChildren};
+///
+/// #[derive(Component)]
+/// struct A;
+///
+/// let mut world = World::new();
+/// world.init_resource::<WithChildDiagnostics>();
+///
+/// world.spawn(WithChildren([A, A, A]));
+/// world.flush();
+///
+/// let diagnostics = world.resource::<WithChildDiagnostics>();
+/// assert_eq!(diagnostics.children_spawned(), 3);
+/// assert_eq!(diagnostics.commands_applied(), 1);
+/// ```
+#[cfg(feature = "diagnostics")]
+#[derive(Resource, Debug, Default)]
+pub struct WithChildDiagnostics {
+    children_spawned: AtomicU64,
+    commands_applied: AtomicU64,
+    missing_skips: AtomicU64,
+}
+
+#[cfg(feature = "diagnostics")]
+impl WithChildDiagnostics {
+    /// The [`DiagnosticPath`] under which [`WithChildDiagnostics::children_spawned`] is reported.
+    pub const CHILDREN_SPAWNED: DiagnosticPath =
+        DiagnosticPath::const_new("with_child/children_spawned");
+
+    /// The [`DiagnosticPath`] under which [`WithChildDiagnostics::commands_applied`] is reported.
+    pub const COMMANDS_APPLIED: DiagnosticPath =
+        DiagnosticPath::const_new("with_child/commands_applied");
+
+    /// The [`DiagnosticPath`] under which [`WithChildDiagnostics::missing_skips`] is reported.
+    pub const MISSING_SKIPS: DiagnosticPath = DiagnosticPath::const_new("with_child/missing_skips");
+
+    /// The number of child entities spawned by this crate's commands.
+    pub fn children_spawned(&self) -> u64 {
+        self.children_spawned.load(Ordering::Relaxed)
+    }
+
+    /// The number of commands queued by this crate's component hooks that have been applied.
+    pub fn commands_applied(&self) -> u64 {
+        self.commands_applied.load(Ordering::Relaxed)
+    }
+
+    /// The number of times a command skipped its work because its target entity was missing.
+    ///
+    /// This does not include failures that panicked due to the [`FailurePolicy`](crate::FailurePolicy).
+    pub fn missing_skips(&self) -> u64 {
+        self.missing_skips.load(Ordering::Relaxed)
+    }
+
+    /// Resets all counters to zero.
+    pub fn reset(&self) {
+        self.children_spawned.store(0, Ordering::Relaxed);
+        self.commands_applied.store(0, Ordering::Relaxed);
+        self.missing_skips.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Records that `count` children were spawned, if [`WithChildDiagnostics`] exists.
+///
+/// Without the `diagnostics` feature, this does nothing.
+#[inline]
+pub(crate) fn record_children_spawned(_world: &World, _count: usize) {
+    #[cfg(feature = "diagnostics")]
+    if let Some(diagnostics) = _world.get_resource::<WithChildDiagnostics>() {
+        diagnostics
+            .children_spawned
+            .fetch_add(_count as u64, Ordering::Relaxed);
+    }
+}
+
+/// Records that a command was applied, if [`WithChildDiagnostics`] exists.
+///
+/// Without the `diagnostics` feature, this does nothing.
+#[inline]
+pub(crate) fn record_command_applied(_world: &World) {
+    #[cfg(feature = "diagnostics")]
+    if let Some(diagnostics) = _world.get_resource::<WithChildDiagnostics>() {
+        diagnostics.commands_applied.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Records that a command was skipped due to missing data, if [`WithChildDiagnostics`] exists.
+///
+/// Without the `diagnostics` feature, this does nothing.
+#[inline]
+pub(crate) fn record_missing_skip(_world: &World) {
+    #[cfg(feature = "diagnostics")]
+    if let Some(diagnostics) = _world.get_resource::<WithChildDiagnostics>() {
+        diagnostics.missing_skips.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// A plugin that reports [`WithChildDiagnostics`] to Bevy's [`DiagnosticsStore`](bevy_diagnostic::DiagnosticsStore) once per frame,
+/// so that they show up alongside other diagnostics such as frame time.
+///
+/// The counters are reset after each report, so each measurement covers a single frame.
+#[cfg(feature = "diagnostics")]
+#[derive(Debug, Default)]
+pub struct WithChildDiagnosticsPlugin;
+
+#[cfg(feature = "diagnostics")]
+impl Plugin for WithChildDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WithChildDiagnostics>()
+            .register_diagnostic(Diagnostic::new(WithChildDiagnostics::CHILDREN_SPAWNED))
+            .register_diagnostic(Diagnostic::new(WithChildDiagnostics::COMMANDS_APPLIED))
+            .register_diagnostic(Diagnostic::new(WithChildDiagnostics::MISSING_SKIPS))
+            .add_systems(Last, report_with_child_diagnostics);
+    }
+}
+
+/// Reports the current [`WithChildDiagnostics`] counters, then resets them.
+#[cfg(feature = "diagnostics")]
+fn report_with_child_diagnostics(
+    mut diagnostics: Diagnostics,
+    counters: Res<WithChildDiagnostics>,
+) {
+    diagnostics.add_measurement(&WithChildDiagnostics::CHILDREN_SPAWNED, || {
+        counters.children_spawned() as f64
+    });
+    diagnostics.add_measurement(&WithChildDiagnostics::COMMANDS_APPLIED, || {
+        counters.commands_applied() as f64
+    });
+    diagnostics.add_measurement(&WithChildDiagnostics::MISSING_SKIPS, || {
+        counters.missing_skips() as f64
+    });
+    counters.reset();
+}
+
+#[cfg(all(test, feature = "diagnostics"))]
+mod tests {
+    use super::*;
+    use crate::deps::bevy_ecs::world::Command;
+    use crate::{FailurePolicy, WithChild, WithChildCommand, WithChildren};
+
+    #[derive(Component)]
+    struct A;
+
+    #[test]
+    fn counters() {
+        let mut world = World::new();
+        world.init_resource::<WithChildDiagnostics>();
+        world.insert_resource(FailurePolicy::Ignore);
+
+        world.spawn((WithChild(A), WithChildren([A, A])));
+        world.flush();
+
+        let missing = world.spawn_empty().id();
+        world.despawn(missing);
+        WithChildCommand::<A>::new(missing).apply(&mut world);
+
+        let diagnostics = world.resource::<WithChildDiagnostics>();
+        assert_eq!(diagnostics.children_spawned(), 3);
+        assert_eq!(diagnostics.commands_applied(), 2);
+        assert_eq!(diagnostics.missing_skips(), 1);
+
+        diagnostics.reset();
+        assert_eq!(diagnostics.children_spawned(), 0);
+    }
+
+    #[test]
+    fn systems_count_children() {
+        use crate::deps::bevy_ecs::system::RunSystemOnce;
+        use std::sync::mpsc::channel;
+
+        use crate::{drain_channel_children, WithChildrenFromChannel};
+
+        let mut world = World::new();
+        world.init_resource::<WithChildDiagnostics>();
+
+        let (sender, receiver) = channel();
+        world.spawn(WithChildrenFromChannel::new(receiver));
+        world.flush();
+        sender.send(A).unwrap();
+        sender.send(A).unwrap();
+        world.run_system_once(drain_channel_children::<A>);
+
+        let diagnostics = world.resource::<WithChildDiagnostics>();
+        assert_eq!(diagnostics.children_spawned(), 2);
+    }
+
+    #[test]
+    fn diagnostics_store() {
+        use bevy_diagnostic::DiagnosticsStore;
+
+        let mut app = App::new();
+        app.add_plugins(WithChildDiagnosticsPlugin);
+
+        app.world_mut().spawn(WithChildren([A, A, A]));
+        app.update();
+
+        let store = app.world().resource::<DiagnosticsStore>();
+        let children_spawned = store
+            .get(&WithChildDiagnostics::CHILDREN_SPAWNED)
+            .and_then(Diagnostic::value);
+        assert_eq!(children_spawned, Some(3.0));
+        assert_eq!(
+            app.world()
+                .resource::<WithChildDiagnostics>()
+                .children_spawned(),
+            0
+        );
+    }
+}
@@ -0,0 +1,260 @@
+use core::fmt::{self, Display};
+
+use crate::deps::bevy_ecs::prelude::*;
+use crate::deps::bevy_utils::tracing::{debug, warn};
+
+use crate::record_missing_skip;
+
+/// Controls what happens when one of this crate's commands can't find the data it expects.
+///
+/// This occurs when the entity that a command targets has been despawned before the command was applied.
+///
+/// Removing the component that generated the command is not a failure:
+/// it cancels the command, which then does nothing, whatever the policy.
+///
+/// Insert this as a resource to override the default behavior.
+/// By default, commands will panic in debug builds and silently do nothing in release builds.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::FailurePolicy;
+///
+/// let mut world = World::new();
+/// // Panic even in release builds, so that integration tests catch failures.
+/// world.insert_resource(FailurePolicy::Panic);
+/// ```
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailurePolicy {
+    /// Panic with a descriptive message.
+    Panic,
+    /// Log a warning, then skip the command.
+    Warn,
+    /// Silently skip the command.
+    Ignore,
+}
+
+impl Default for FailurePolicy {
+    /// Defaults to [`FailurePolicy::Panic`] in debug builds, and [`FailurePolicy::Ignore`] in release builds.
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            FailurePolicy::Panic
+        } else {
+            FailurePolicy::Ignore
+        }
+    }
+}
+
+/// A failure encountered while applying one of this crate's commands.
+///
+/// This is what is reported, according to the [`FailurePolicy`], when a command can't do its work:
+/// matching on it is more robust than parsing panic or log messages.
+///
+/// Each failure is also triggered as an [`Event`], whatever the policy,
+/// so that it can be handled programmatically by an observer.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::{FailurePolicy, WithChildError, WithParentCommand};
+///
+/// #[derive(Component)]
+/// struct A;
+///
+/// #[derive(Resource, Default)]
+/// struct Failures(Vec<WithChildError>);
+///
+/// let mut world = World::new();
+/// world.insert_resource(FailurePolicy::Ignore);
+/// world.init_resource::<Failures>();
+/// world.observe(|trigger: Trigger<WithChildError>, mut failures: ResMut<Failures>| {
+///     failures.0.push(*trigger.event());
+/// });
+///
+/// let child = world.spawn_empty().id();
+/// world.despawn(child);
+/// world.commands().add(WithParentCommand::<A>::new(child));
+/// world.flush();
+///
+/// assert_eq!(world.resource::<Failures>().0, [WithChildError::ChildMissing(child)]);
+/// ```
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum WithChildError {
+    /// The entity holding the component that generated the command, which usually becomes the parent, does not exist.
+    ParentMissing(Entity),
+    /// The entity holding the component that generated the command does not exist,
+    /// for commands that modify that entity rather than spawning children for it.
+    EntityMissing(Entity),
+    /// A child that the command should have linked or modified does not exist.
+    ChildMissing(Entity),
+    /// A child entity could not be used or spawned, as its index is in use by an entity of a different generation.
+    ChildGenerationMismatch(Entity),
+    /// The entity does not have the data the command needs, such as a component it reads.
+    ComponentMissing(Entity),
+    /// The command needs a resource that does not exist in the world.
+    ResourceMissing(&'static str),
+    /// The command would have made the entity its own child.
+    SelfParenting(Entity),
+    /// The command was applied on a different thread from the one that created the component of this entity,
+    /// so its thread-local data could not be accessed.
+    WrongThread(Entity),
+}
+
+impl Display for WithChildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WithChildError::ParentMissing(entity) => write!(f, "Parent entity not found: {entity}"),
+            WithChildError::EntityMissing(entity) => write!(f, "Entity not found: {entity}"),
+            WithChildError::ChildMissing(entity) => write!(f, "Child entity not found: {entity}"),
+            WithChildError::ChildGenerationMismatch(entity) => write!(
+                f,
+                "Child entity {entity} could not be spawned: its index is in use by another generation"
+            ),
+            WithChildError::ComponentMissing(entity) => {
+                write!(f, "Required component not found on entity {entity}")
+            }
+            WithChildError::ResourceMissing(resource) => {
+                write!(f, "Required resource not found: {resource}")
+            }
+            WithChildError::SelfParenting(entity) => {
+                write!(f, "Entity {entity} cannot be made its own child")
+            }
+            WithChildError::WrongThread(entity) => write!(
+                f,
+                "Thread-local data for entity {entity} not found: the command was applied on a different thread"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WithChildError {}
+
+/// Handles a failed command according to the [`FailurePolicy`] stored in the world,
+/// after triggering the error for any observers.
+///
+/// If no [`FailurePolicy`] resource is present, [`FailurePolicy::default`] is used.
+pub(crate) fn on_missing(world: &mut World, error: WithChildError) {
+    world.trigger(error);
+
+    let policy = world
+        .get_resource::<FailurePolicy>()
+        .copied()
+        .unwrap_or_default();
+
+    match policy {
+        FailurePolicy::Panic => panic!("{error}"),
+        FailurePolicy::Warn => {
+            warn!("{error}");
+            record_missing_skip(world);
+        }
+        FailurePolicy::Ignore => record_missing_skip(world),
+    }
+}
+
+/// Handles a command whose component was removed from its (still existing) entity before the command was applied.
+///
+/// Removing one of this crate's deferred components before it's resolved is how its work is cancelled,
+/// so this is not a failure, and the [`FailurePolicy`] is not consulted.
+pub(crate) fn on_cancelled(component: &str) {
+    debug!("{component} was removed before it was resolved: skipping");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Parent entity not found")]
+    fn panic_policy() {
+        let mut world = World::new();
+        world.insert_resource(FailurePolicy::Panic);
+        let parent = world.spawn_empty().id();
+        on_missing(&mut world, WithChildError::ParentMissing(parent));
+    }
+
+    #[test]
+    fn lenient_policies() {
+        let mut world = World::new();
+        let parent = world.spawn_empty().id();
+
+        world.insert_resource(FailurePolicy::Warn);
+        on_missing(&mut world, WithChildError::ParentMissing(parent));
+
+        world.insert_resource(FailurePolicy::Ignore);
+        on_missing(&mut world, WithChildError::ParentMissing(parent));
+    }
+
+    #[test]
+    fn observers_see_failures() {
+        #[derive(Resource, Default)]
+        struct Failures(Vec<WithChildError>);
+
+        let mut world = World::new();
+        world.insert_resource(FailurePolicy::Ignore);
+        world.init_resource::<Failures>();
+        world.observe(
+            |trigger: Trigger<WithChildError>, mut failures: ResMut<Failures>| {
+                failures.0.push(*trigger.event());
+            },
+        );
+
+        let entity = world.spawn_empty().id();
+        on_missing(&mut world, WithChildError::ChildMissing(entity));
+        on_missing(&mut world, WithChildError::EntityMissing(entity));
+
+        assert_eq!(
+            world.resource::<Failures>().0,
+            [
+                WithChildError::ChildMissing(entity),
+                WithChildError::EntityMissing(entity)
+            ]
+        );
+    }
+
+    #[test]
+    fn error_display() {
+        let entity = Entity::from_raw(7);
+
+        let cases = [
+            (
+                WithChildError::ParentMissing(entity),
+                format!("Parent entity not found: {entity}"),
+            ),
+            (
+                WithChildError::ChildMissing(entity),
+                format!("Child entity not found: {entity}"),
+            ),
+            (
+                WithChildError::ComponentMissing(entity),
+                format!("Required component not found on entity {entity}"),
+            ),
+            (
+                WithChildError::ResourceMissing("SceneSpawner"),
+                "Required resource not found: SceneSpawner".to_string(),
+            ),
+            (
+                WithChildError::SelfParenting(entity),
+                format!("Entity {entity} cannot be made its own child"),
+            ),
+            (
+                WithChildError::EntityMissing(entity),
+                format!("Entity not found: {entity}"),
+            ),
+            (
+                WithChildError::ChildGenerationMismatch(entity),
+                format!(
+                    "Child entity {entity} could not be spawned: its index is in use by another generation"
+                ),
+            ),
+            (
+                WithChildError::WrongThread(entity),
+                format!("Thread-local data for entity {entity} not found: the command was applied on a different thread"),
+            ),
+        ];
+        for (error, expected) in cases {
+            assert_eq!(error.to_string(), expected);
+            // Usable as a standard error
+            let boxed: Box<dyn std::error::Error> = Box::new(error);
+            assert_eq!(boxed.to_string(), expected);
+        }
+    }
+}
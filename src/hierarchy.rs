@@ -1,11 +1,26 @@
+use core::hash::Hash;
+use core::iter::{Empty, Filter, Map, StepBy};
 use core::marker::PhantomData;
+use core::ops::Range;
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::collections::HashSet;
+use std::sync::Arc;
 
-use bevy_ecs::{
+use crate::deps::bevy_ecs::{
     component::{ComponentHooks, ComponentId, StorageType},
+    entity::{EntityMapper, MapEntities},
     prelude::*,
-    world::{Command, DeferredWorld},
+    system::IntoObserverSystem,
+    world::{Command, CommandQueue, DeferredWorld},
+};
+#[cfg(feature = "trace")]
+use crate::deps::bevy_utils::tracing::{self, info_span};
+#[cfg(feature = "reflect")]
+use bevy_reflect::Reflect;
+
+use crate::{
+    mark_deferred, on_cancelled, on_missing, queue_deferred, Link, LinkChild, WithChildError,
 };
-use bevy_hierarchy::BuildWorldChildren;
 
 /// A component that, when added to an entity, will add a child entity with the given bundle.
 ///
@@ -13,8 +28,25 @@ use bevy_hierarchy::BuildWorldChildren;
 ///
 /// You can add multiple children in this way, if and only if their bundle types are distinct.
 /// See [`WithChildren`] for a version that supports adding multiple children of the same type.
+/// Inserting a [`WithChild`] onto an entity whose [`WithChild`] of the same type hasn't been resolved yet
+/// replaces it, and the replaced child is never spawned: debug builds log a warning when this happens.
 ///
 /// Under the hood, this is done using component lifecycle hooks.
+/// Each spawned copy of this component produces its own child:
+/// cloning a bundle containing a [`WithChild`] and spawning both copies yields two independent children.
+///
+/// The child is spawned by a command, so it won't exist until the world is next flushed:
+/// use [`is_pending`](crate::is_pending) to check whether this has happened yet.
+/// Removing the component before then cancels the spawn: the command does nothing, and no error is reported.
+/// Commands are applied in the order their components' hooks ran:
+/// see [`after_parent_ready`] if the child must be spawned after commands queued by the parent's other components.
+///
+/// Resolving this component moves the parent between archetypes, but doesn't mark its other components as changed:
+/// only [`Children`](bevy_hierarchy::Children) is added or changed, so `Changed<T>` filters on the parent's own components are unaffected.
+///
+/// The child is always spawned in the same [`World`] as its parent: the command is queued in, and applied to, the world that hosts the component.
+/// To spawn into a scratch world, such as an editor preview or a sub-app's world, insert the component there.
+/// Resources that configure resolution, like [`FailurePolicy`](crate::FailurePolicy), are read from that world too.
 ///
 /// ```rust
 /// use bevy_ecs::prelude::*;
@@ -35,7 +67,15 @@ use bevy_hierarchy::BuildWorldChildren;
 ///   ));
 /// }
 /// ```
-#[derive(Debug, Clone, Default)]
+///
+/// With the `reflect` feature enabled, [`WithChild`] implements `Reflect` when its bundle does,
+/// allowing it to be authored in scenes.
+/// Remember to register each concrete `WithChild<B>` type you use with the type registry.
+///
+/// [`WithChild`] implements [`PartialEq`], [`Eq`] and [`Hash`] whenever its bundle does,
+/// which is useful when comparing templates in tests or detecting changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "reflect", derive(Reflect), reflect(Component))]
 pub struct WithChild<B: Bundle>(pub B);
 
 impl<B: Bundle> Component for WithChild<B> {
@@ -43,7 +83,10 @@ impl<B: Bundle> Component for WithChild<B> {
     const STORAGE_TYPE: StorageType = StorageType::SparseSet;
 
     fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
         hooks.on_add(with_child_hook::<B>);
+        #[cfg(debug_assertions)]
+        hooks.on_insert(with_child_insert_hook);
     }
 }
 
@@ -53,238 +96,3835 @@ impl<B: Bundle> Component for WithChild<B> {
 fn with_child_hook<B: Bundle>(
     mut world: DeferredWorld<'_>,
     entity: Entity,
-    _component_id: ComponentId,
+    component_id: ComponentId,
 ) {
+    #[cfg(debug_assertions)]
+    crate::collision_guard::mark_added(entity, component_id);
+
     // Component hooks can't perform structural changes, so we need to rely on commands.
-    world.commands().add(WithChildCommand {
-        parent_entity: entity,
-        _phantom: PhantomData::<B>,
-    });
+    queue_deferred(&mut world, component_id, entity, WithChildCommand::<B>::new);
 }
 
-struct WithChildCommand<B> {
-    parent_entity: Entity,
+/// A hook that runs whenever [`WithChild`] is inserted on an entity, in debug builds.
+///
+/// Warns if this replaced a [`WithChild`] of the same type that had not been resolved yet.
+#[cfg(debug_assertions)]
+fn with_child_insert_hook(world: DeferredWorld<'_>, entity: Entity, component_id: ComponentId) {
+    crate::collision_guard::warn_if_replaced(&world, entity, component_id, None);
+}
+
+/// A [`Command`] that spawns the child stored in the [`WithChild<B>`] component of an entity.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildCommand<B> {
+    /// The entity holding the [`WithChild`] component, which will become the parent.
+    pub parent_entity: Entity,
     _phantom: PhantomData<B>,
 }
 
+impl<B> WithChildCommand<B> {
+    /// Creates a new [`WithChildCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
 impl<B: Bundle> Command for WithChildCommand<B> {
     fn apply(self, world: &mut World) {
-        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
-            #[cfg(debug_assertions)]
-            panic!("Parent entity not found");
+        #[cfg(feature = "trace")]
+        let _span = info_span!("with_child", parent = ?self.parent_entity).entered();
 
-            #[cfg(not(debug_assertions))]
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
             return;
         };
 
         let Some(with_child_component) = entity_mut.take::<WithChild<B>>() else {
-            #[cfg(debug_assertions)]
-            panic!("WithChild component not found");
-
-            #[cfg(not(debug_assertions))]
+            on_cancelled("WithChild");
             return;
         };
+        entity_mut.insert(SpawnsChildrenMarker);
+
+        #[cfg(feature = "auto_name")]
+        let mut namer = crate::auto_name::ChildNamer::new(&entity_mut);
 
-        let child_entity = world.spawn(with_child_component.0).id();
-        world.entity_mut(self.parent_entity).add_child(child_entity);
+        let child_entity = Link::spawn_child(world, with_child_component.0).id();
+        #[cfg(feature = "auto_name")]
+        namer.name_next(world.entity_mut(child_entity));
+
+        if Link::link_child(world, self.parent_entity, child_entity) {
+            #[cfg(all(feature = "recursion_guard", debug_assertions))]
+            crate::recursion_guard::check_recursion::<B>(world, child_entity);
+        }
     }
 }
 
-/// A component that, when added to an entity, will add a child entity with the given bundle.
+/// A marker component added to every entity whose [`WithChild`] or [`WithChildren`] component has been resolved,
+/// as well as its `WithChildrenStream` component, with the `async` feature.
 ///
-/// This component will be removed from the entity immediately upon being spawned,
-/// and the supplied iterator will be iterated to completion to generate the data needed for each child.
-/// See [`WithChild`] for a more convenient API when adding only one child (or multiple children with distinct bundle types).
+/// This makes it easy to find the roots of spawned hierarchies, using `Query<Entity, With<SpawnsChildrenMarker>>`.
+/// The marker is kept after the children are spawned, and isn't removed if they are later despawned.
 ///
-/// Under the hood, this is done using component lifecycle hooks.
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::{SpawnsChildrenMarker, WithChild};
 ///
-/// # Examples
+/// #[derive(Component)]
+/// struct Inventory;
 ///
-/// Just like when using [`Commands::spawn_batch`], any iterator that returns a bundle of the same type can be used.
+/// #[derive(Component)]
+/// struct Item;
 ///
-/// Working with vectors, arrays and other collections is straightforward:
+/// let mut world = World::new();
+/// let inventory = world.spawn((Inventory, WithChild(Item))).id();
+/// world.flush();
+///
+/// let roots: Vec<Entity> = world
+///     .query_filtered::<Entity, With<SpawnsChildrenMarker>>()
+///     .iter(&world)
+///     .collect();
+/// assert_eq!(roots, [inventory]);
+/// ```
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "reflect", derive(Reflect), reflect(Component))]
+pub struct SpawnsChildrenMarker;
+
+/// A component that, when added to an entity, will add a child entity with the given bundle,
+/// and attach an [`Observer`] watching for the event `E` on that child.
+///
+/// This is useful for reactive patterns, where each dynamically spawned child needs its own observer:
+/// without this component, the child's [`Entity`] must be known before the observer can be attached.
+/// The observer only watches the spawned child, and is despawned along with it.
+///
+/// Otherwise, this behaves just like [`WithChild`].
 ///
 /// ```rust
 /// use bevy_ecs::prelude::*;
-/// use i_cant_believe_its_not_bsn::WithChildren;
+/// use i_cant_believe_its_not_bsn::WithChildObserving;
 ///
 /// #[derive(Component)]
-/// struct Name(&'static str);
+/// struct Button;
 ///
-/// fn spawn_hierarchy_with_vector(mut commands: Commands) {
-///   commands.spawn(
-///    (Name("Zeus"),
-///     WithChildren([Name("Athena"), Name("Apollo"), Name("Hermes")])
-///   ));
+/// #[derive(Event)]
+/// struct Clicked;
+///
+/// fn spawn_menu(mut commands: Commands) {
+///     commands.spawn(WithChildObserving::new(Button, |trigger: Trigger<Clicked>| {
+///         println!("{:?} was clicked", trigger.entity());
+///     }));
 /// }
-///```
+/// ```
+pub struct WithChildObserving<B: Bundle, E: Event>(pub B, pub Observer<E, ()>);
+
+impl<B: Bundle, E: Event> WithChildObserving<B, E> {
+    /// Creates a new [`WithChildObserving`] component, which will spawn a child with `bundle`
+    /// and run `observer` whenever `E` is triggered on that child.
+    pub fn new<M>(bundle: B, observer: impl IntoObserverSystem<E, (), M>) -> Self {
+        Self(bundle, Observer::new(observer))
+    }
+}
+
+impl<B: Bundle, E: Event> Component for WithChildObserving<B, E> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_child_observing_hook::<B, E>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildObserving`] is added to an entity.
 ///
-/// However, generator-style iterators can also be used to dynamically vary the number and property of children:
+/// Generates a [`WithChildObservingCommand`].
+fn with_child_observing_hook<B: Bundle, E: Event>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildObservingCommand::<B, E>::new,
+    );
+}
+
+/// A [`Command`] that spawns the child stored in the [`WithChildObserving<B, E>`] component of an entity,
+/// along with its observer.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildObservingCommand<B, E> {
+    /// The entity holding the [`WithChildObserving`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, E)>,
+}
+
+impl<B, E> WithChildObservingCommand<B, E> {
+    /// Creates a new [`WithChildObservingCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, E: Event> Command for WithChildObservingCommand<B, E> {
+    fn apply(self, world: &mut World) {
+        #[cfg(feature = "trace")]
+        let _span = info_span!("with_child_observing", parent = ?self.parent_entity).entered();
+
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithChildObserving(bundle, observer)) =
+            entity_mut.take::<WithChildObserving<B, E>>()
+        else {
+            on_cancelled("WithChildObserving");
+            return;
+        };
+
+        let child_entity = Link::spawn_child(world, bundle).id();
+        if Link::link_child(world, self.parent_entity, child_entity) {
+            world.spawn(observer.with_entity(child_entity));
+        }
+    }
+}
+
+/// A component that, when added to an entity, will add a child entity with the given bundle,
+/// cloning the component `C` from the parent onto the child.
+///
+/// This is useful for data that should be shared by a parent and its children, such as a theme or scale.
+/// If the parent does not have a `C` component, the child is spawned with only the bundle `B`.
+///
+/// Otherwise, this behaves just like [`WithChild`].
 ///
 /// ```rust
 /// use bevy_ecs::prelude::*;
-/// use i_cant_believe_its_not_bsn::WithChildren;
+/// use i_cant_believe_its_not_bsn::WithChildInherit;
 ///
-/// #[derive(Component)]
-/// struct A;
+/// #[derive(Component, Clone)]
+/// struct Theme(&'static str);
 ///
 /// #[derive(Component)]
-/// struct ChildNumber(usize);
+/// struct Button;
 ///
-/// fn spawn_hierarchy_with_generator(mut commands: Commands) {
+/// fn spawn_hierarchy(mut commands: Commands) {
 ///   commands.spawn(
-///    (A, // Component on parent
-///      WithChildren((0..3).map(|i| (ChildNumber(i)))) // Each child will have a ChildNumber component
-///    ));
+///    (Theme("dark"), // Parent
+///     WithChildInherit::<_, Theme>::new(Button) // Child will have both Button and Theme("dark")
+///   ));
 /// }
-///```
+/// ```
 #[derive(Debug, Clone, Default)]
-pub struct WithChildren<B: Bundle, I: IntoIterator<Item = B>>(pub I);
+pub struct WithChildInherit<B: Bundle, C: Component + Clone>(pub B, pub PhantomData<C>);
 
-impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Component
-    for WithChildren<B, I>
-{
+impl<B: Bundle, C: Component + Clone> WithChildInherit<B, C> {
+    /// Creates a new [`WithChildInherit`] component, which will spawn a child with the given bundle.
+    pub const fn new(bundle: B) -> Self {
+        Self(bundle, PhantomData)
+    }
+}
+
+impl<B: Bundle, C: Component + Clone> Component for WithChildInherit<B, C> {
     /// This is a sparse set component as it's only ever added and removed, never iterated over.
     const STORAGE_TYPE: StorageType = StorageType::SparseSet;
 
     fn register_component_hooks(hooks: &mut ComponentHooks) {
-        hooks.on_add(with_children_hook::<B, I>);
+        mark_deferred::<Self>();
+        hooks.on_add(with_child_inherit_hook::<B, C>);
     }
 }
 
-/// A hook that runs whenever [`WithChildren`] is added to an entity.
+/// A hook that runs whenever [`WithChildInherit`] is added to an entity.
 ///
-/// Generates a [`WithChildrenCommand`].
-fn with_children_hook<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static>(
+/// Generates a [`WithChildInheritCommand`].
+fn with_child_inherit_hook<B: Bundle, C: Component + Clone>(
     mut world: DeferredWorld<'_>,
     entity: Entity,
-    _component_id: ComponentId,
+    component_id: ComponentId,
 ) {
     // Component hooks can't perform structural changes, so we need to rely on commands.
-    world.commands().add(WithChildrenCommand {
-        parent_entity: entity,
-        _phantom: PhantomData::<(B, I)>,
-    });
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildInheritCommand::<B, C>::new,
+    );
 }
 
-struct WithChildrenCommand<B, I> {
-    parent_entity: Entity,
-    _phantom: PhantomData<(B, I)>,
+/// A [`Command`] that spawns the child stored in the [`WithChildInherit<B, C>`] component of an entity,
+/// cloning the parent's `C` component onto it.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildInheritCommand<B, C> {
+    /// The entity holding the [`WithChildInherit`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, C)>,
 }
 
-impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Command
-    for WithChildrenCommand<B, I>
-{
+impl<B, C> WithChildInheritCommand<B, C> {
+    /// Creates a new [`WithChildInheritCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, C: Component + Clone> Command for WithChildInheritCommand<B, C> {
     fn apply(self, world: &mut World) {
         let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
-            #[cfg(debug_assertions)]
-            panic!("Parent entity not found");
-
-            #[cfg(not(debug_assertions))]
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
             return;
         };
 
-        let Some(with_children_component) = entity_mut.take::<WithChildren<B, I>>() else {
-            #[cfg(debug_assertions)]
-            panic!("WithChild component not found");
-
-            #[cfg(not(debug_assertions))]
+        let Some(with_child_component) = entity_mut.take::<WithChildInherit<B, C>>() else {
+            on_cancelled("WithChildInherit");
             return;
         };
 
-        for child_bundle in with_children_component.0 {
-            let child_entity = world.spawn(child_bundle).id();
-            world.entity_mut(self.parent_entity).add_child(child_entity);
+        let inherited_component = entity_mut.get::<C>().cloned();
+
+        let mut child_entity_mut = Link::spawn_child(world, with_child_component.0);
+        if let Some(inherited_component) = inherited_component {
+            child_entity_mut.insert(inherited_component);
         }
+        let child_entity = child_entity_mut.id();
+        Link::link_child(world, self.parent_entity, child_entity);
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use bevy_ecs::system::RunSystemOnce;
-    use bevy_hierarchy::Children;
-
-    use super::*;
-
-    #[derive(Component, PartialEq, Debug)]
-    struct A;
+/// A component that, when added to an entity, will add a child entity with the given bundle
+/// at the given index among the entity's existing children.
+///
+/// [`WithChild`] always appends the new child to the end of the parent's [`Children`](bevy_hierarchy::Children),
+/// which is a problem for insertion-order-sensitive contexts like UI layout.
+/// If the index is greater than the current number of children, it is clamped: the child is appended to the end.
+///
+/// The index is interpreted against the parent's [`Children`](bevy_hierarchy::Children) as they are when the command is applied,
+/// not when the component was added: any children added in between are taken into account.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{WithChild, WithChildAt};
+///
+/// #[derive(Component)]
+/// struct Item;
+///
+/// #[derive(Component)]
+/// struct Header;
+///
+/// let mut world = World::new();
+/// let list = world.spawn(WithChild(Item)).id();
+/// world.flush();
+///
+/// world.entity_mut(list).insert(WithChildAt(0, Header));
+/// world.flush();
+///
+/// let first_child = world.get::<Children>(list).unwrap()[0];
+/// assert!(world.entity(first_child).contains::<Header>());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct WithChildAt<B: Bundle>(pub usize, pub B);
 
-    #[derive(Component, PartialEq, Debug)]
-    struct B(u8);
+impl<B: Bundle> Component for WithChildAt<B> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
 
-    #[derive(Bundle)]
-    struct ABBundle {
-        a: A,
-        b: B,
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_child_at_hook::<B>);
     }
+}
 
-    #[derive(Bundle)]
-    struct HierarchicalBundle {
-        a: A,
-        child: WithChild<ABBundle>,
-    }
+/// A hook that runs whenever [`WithChildAt`] is added to an entity.
+///
+/// Generates a [`WithChildAtCommand`].
+fn with_child_at_hook<B: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildAtCommand::<B>::new,
+    );
+}
 
-    #[test]
-    fn with_child() {
-        let mut world = World::default();
+/// A [`Command`] that spawns the child stored in the [`WithChildAt<B>`] component of an entity,
+/// inserting it at the stored index among the entity's children.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildAtCommand<B> {
+    /// The entity holding the [`WithChildAt`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<B>,
+}
 
-        let parent = world.spawn(WithChild((A, B(3)))).id();
-        // FIXME: this should not be needed!
-        world.flush();
+impl<B> WithChildAtCommand<B> {
+    /// Creates a new [`WithChildAtCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
 
-        assert!(!world.entity(parent).contains::<WithChild<(A, B)>>());
-        assert!(!world.entity(parent).contains::<A>());
-        assert!(!world.entity(parent).contains::<B>());
+impl<B: Bundle> Command for WithChildAtCommand<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
 
-        let children = world.get::<Children>(parent).unwrap();
-        assert_eq!(children.len(), 1);
+        let Some(WithChildAt(index, bundle)) = entity_mut.take::<WithChildAt<B>>() else {
+            on_cancelled("WithChildAt");
+            return;
+        };
 
-        let child_entity = children[0];
-        assert_eq!(world.get::<A>(child_entity), Some(&A));
-        assert_eq!(world.get::<B>(child_entity), Some(&B(3)));
+        let child_entity = Link::spawn_child(world, bundle).id();
+        // Spawning flushes the world, which may have changed the children
+        let child_count = Link::children(world, self.parent_entity).len();
+        let index = index.min(child_count);
+        Link::link_child_at(world, self.parent_entity, index, child_entity);
     }
+}
 
-    #[test]
-    fn with_children_vec() {
-        let mut world = World::default();
-
-        let parent = world.spawn(WithChildren(vec![B(0), B(1), B(2)])).id();
-        // FIXME: this should not be needed!
-        world.flush();
+/// A component that records the slot a child was spawned into by [`WithChildSlot`].
+///
+/// Children with a [`ChildSlot`] are kept in ascending slot order among their siblings.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChildSlot(pub u8);
 
-        assert!(!world.entity(parent).contains::<WithChildren<B, Vec<B>>>());
-        assert!(!world.entity(parent).contains::<B>());
+/// A component that, when added to an entity, will add a child entity with the given bundle
+/// in the given slot.
+///
+/// When several [`WithChild`] components of distinct types are spawned together,
+/// their children appear in the order in which their hooks fired, which follows the order of the bundle.
+/// [`WithChildSlot`] makes this order explicit instead:
+/// the child is inserted after all siblings with a lower or equal [`ChildSlot`],
+/// and before the first sibling with a higher one, so that slotted children end up in ascending slot order
+/// regardless of the order in which they were resolved.
+///
+/// The slot is recorded on the child as a [`ChildSlot`] component.
+///
+/// As with [`WithChild`], only one [`WithChildSlot`] of each bundle type can be pending on an entity at once:
+/// inserting a second one before the first is resolved replaces it, whatever their slots.
+/// Debug builds log a warning when this happens.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::WithChildSlot;
+///
+/// #[derive(Component)]
+/// struct Footer;
+///
+/// #[derive(Component)]
+/// struct Header;
+///
+/// let mut world = World::new();
+/// let page = world
+///     .spawn((WithChildSlot(1, Footer), WithChildSlot(0, Header)))
+///     .id();
+/// world.flush();
+///
+/// let first_child = world.get::<Children>(page).unwrap()[0];
+/// assert!(world.entity(first_child).contains::<Header>());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct WithChildSlot<B: Bundle>(pub u8, pub B);
 
-        let children = world.get::<Children>(parent).unwrap();
-        assert_eq!(children.len(), 3);
+impl<B: Bundle> Component for WithChildSlot<B> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
 
-        for (i, child_entity) in children.iter().enumerate() {
-            assert_eq!(world.get::<B>(*child_entity), Some(&B(i as u8)));
-        }
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_child_slot_hook::<B>);
+        #[cfg(debug_assertions)]
+        hooks.on_insert(with_child_slot_insert_hook::<B>);
     }
+}
 
-    #[test]
-    fn with_child_closure() {
-        let mut world = World::default();
+/// A hook that runs whenever [`WithChildSlot`] is added to an entity.
+///
+/// Generates a [`WithChildSlotCommand`].
+fn with_child_slot_hook<B: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    #[cfg(debug_assertions)]
+    crate::collision_guard::mark_added(entity, component_id);
 
-        let parent = world.spawn(WithChildren((0..7).map(|i| B(i as u8)))).id();
-        // FIXME: this should not be needed!
-        world.flush();
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildSlotCommand::<B>::new,
+    );
+}
 
-        assert!(!world.entity(parent).contains::<WithChildren<B, Vec<B>>>());
-        assert!(!world.entity(parent).contains::<B>());
+/// A hook that runs whenever [`WithChildSlot`] is inserted on an entity, in debug builds.
+///
+/// Warns if this replaced a [`WithChildSlot`] of the same type that had not been resolved yet,
+/// naming the slot of the new component.
+#[cfg(debug_assertions)]
+fn with_child_slot_insert_hook<B: Bundle>(
+    world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    let slot = world.get::<WithChildSlot<B>>(entity).map(|slot| slot.0);
+    crate::collision_guard::warn_if_replaced(&world, entity, component_id, slot);
+}
 
-        let children = world.get::<Children>(parent).unwrap();
-        assert_eq!(children.len(), 7);
+/// A [`Command`] that spawns the child stored in the [`WithChildSlot<B>`] component of an entity,
+/// inserting it among the entity's children according to its slot.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildSlotCommand<B> {
+    /// The entity holding the [`WithChildSlot`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<B>,
+}
 
-        for (i, child_entity) in children.iter().enumerate() {
-            assert_eq!(world.get::<B>(*child_entity), Some(&B(i as u8)));
+impl<B> WithChildSlotCommand<B> {
+    /// Creates a new [`WithChildSlotCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
         }
     }
+}
 
-    #[test]
-    fn with_distinct_children() {
-        let mut world = World::default();
+impl<B: Bundle> Command for WithChildSlotCommand<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithChildSlot(slot, bundle)) = entity_mut.take::<WithChildSlot<B>>() else {
+            on_cancelled("WithChildSlot");
+            return;
+        };
+
+        let child_entity = Link::spawn_child(world, (bundle, ChildSlot(slot))).id();
+        insert_child_in_slot_order(world, self.parent_entity, child_entity, ChildSlot(slot));
+    }
+}
+
+/// Inserts `child_entity` into the children of `parent_entity`,
+/// before the first existing sibling whose [`ChildSlot`] is greater than `slot`.
+///
+/// Returns `false` if `parent_entity` no longer exists, in which case `child_entity` has been despawned.
+fn insert_child_in_slot_order(
+    world: &mut World,
+    parent_entity: Entity,
+    child_entity: Entity,
+    slot: ChildSlot,
+) -> bool {
+    let children = Link::children(world, parent_entity);
+    let index = children
+        .iter()
+        .position(|&sibling| world.get::<ChildSlot>(sibling).is_some_and(|s| *s > slot))
+        .unwrap_or(children.len());
+    Link::link_child_at(world, parent_entity, index, child_entity)
+}
+
+/// Spawns a child of `entity` with the given bundle in the lowest [`ChildSlot`] that isn't already taken,
+/// returning the chosen slot.
+///
+/// A slot is taken if one of the entity's children has that [`ChildSlot`],
+/// or if the entity has a pending [`WithChildSlot<B>`] for it.
+/// Returns [`None`], without spawning anything, if all 256 slots are taken.
+///
+/// As only one [`WithChildSlot`] of each bundle type can be pending on an entity at once,
+/// this spawns the child immediately rather than inserting a component,
+/// so it can be called repeatedly to allocate slots at runtime without choosing them up front.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::try_add_child_slot;
+///
+/// #[derive(Component)]
+/// struct Item;
+///
+/// let mut world = World::new();
+/// let mut inventory = world.spawn_empty();
+///
+/// assert_eq!(try_add_child_slot(&mut inventory, Item), Some(0));
+/// assert_eq!(try_add_child_slot(&mut inventory, Item), Some(1));
+/// ```
+pub fn try_add_child_slot<B: Bundle>(entity: &mut EntityWorldMut, bundle: B) -> Option<u8> {
+    let parent_entity = entity.id();
+    entity.world_scope(|world| {
+        let mut taken = [false; u8::MAX as usize + 1];
+        for &child in Link::children(world, parent_entity) {
+            if let Some(&ChildSlot(slot)) = world.get::<ChildSlot>(child) {
+                taken[slot as usize] = true;
+            }
+        }
+        if let Some(&WithChildSlot(slot, _)) = world.get::<WithChildSlot<B>>(parent_entity) {
+            taken[slot as usize] = true;
+        }
+
+        let slot = taken.iter().position(|&taken| !taken)? as u8;
+        let child_entity = Link::spawn_child(world, (bundle, ChildSlot(slot))).id();
+        insert_child_in_slot_order(world, parent_entity, child_entity, ChildSlot(slot))
+            .then_some(slot)
+    })
+}
+
+/// A component that records which [`ChildSlot`]s of an entity were filled by [`spawn_slotted_children`].
+///
+/// Each entry is indexed by slot, and holds the child spawned into that slot, if any.
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChildSlots(pub Vec<Option<Entity>>);
+
+/// Remaps each recorded child entity, so that [`ChildSlots`] remains valid when loaded into a different world.
+impl MapEntities for ChildSlots {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        for child_entity in self.0.iter_mut().flatten() {
+            *child_entity = entity_mapper.map_entity(*child_entity);
+        }
+    }
+}
+
+impl ChildSlots {
+    /// Returns the child in the given slot, if that slot was filled.
+    pub fn get(&self, slot: u8) -> Option<Entity> {
+        self.0.get(slot as usize).copied().flatten()
+    }
+}
+
+/// A component that, when added to an entity, will add a child entity for each item generated by the iterator,
+/// assigning them ascending [`ChildSlot`]s from `0` up to (but not including) `MAX`.
+///
+/// This bridges [`WithChildren`] and [`WithChildSlot`]:
+/// each child ends up in the same place among its siblings as if it had been spawned with a [`WithChildSlot`],
+/// and the filled slots are recorded in the parent's [`ChildSlots`].
+/// Items beyond `MAX` are dropped without being spawned, as with [`spawn_children_capped`],
+/// and a [`DroppedChildren`] component is added to the parent.
+///
+/// Usually constructed via [`spawn_slotted_children`].
+///
+/// Its [`Debug`](core::fmt::Debug) output includes `MAX`, which is otherwise only visible in the type:
+/// for example, `WithSlottedChildren<3>([A, B])`.
+#[derive(Clone, Default)]
+pub struct WithSlottedChildren<B: Bundle, I: IntoIterator<Item = B>, const MAX: u8>(pub I);
+
+impl<B: Bundle, I: IntoIterator<Item = B> + core::fmt::Debug, const MAX: u8> core::fmt::Debug
+    for WithSlottedChildren<B, I, MAX>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple(&format!("WithSlottedChildren<{MAX}>"))
+            .field(&self.0)
+            .finish()
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static, const MAX: u8> Component
+    for WithSlottedChildren<B, I, MAX>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_slotted_children_hook::<B, I, MAX>);
+    }
+}
+
+/// A hook that runs whenever [`WithSlottedChildren`] is added to an entity.
+///
+/// Generates a [`WithSlottedChildrenCommand`].
+fn with_slotted_children_hook<
+    B: Bundle,
+    I: IntoIterator<Item = B> + Send + Sync + 'static,
+    const MAX: u8,
+>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithSlottedChildrenCommand::<B, I, MAX>::new,
+    );
+}
+
+/// A [`Command`] that spawns the children generated by the [`WithSlottedChildren<B, I, MAX>`] component of an entity,
+/// assigning each of them a slot.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithSlottedChildrenCommand<B, I, const MAX: u8> {
+    /// The entity holding the [`WithSlottedChildren`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, I)>,
+}
+
+impl<B, I, const MAX: u8> WithSlottedChildrenCommand<B, I, MAX> {
+    /// Creates a new [`WithSlottedChildrenCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static, const MAX: u8> Command
+    for WithSlottedChildrenCommand<B, I, MAX>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithSlottedChildren(iter)) = entity_mut.take::<WithSlottedChildren<B, I, MAX>>()
+        else {
+            on_cancelled("WithSlottedChildren");
+            return;
+        };
+
+        let mut slots = entity_mut.take::<ChildSlots>().unwrap_or_default().0;
+        slots.resize(slots.len().max(MAX as usize), None);
+
+        let mut iter = iter.into_iter();
+        for (slot, child_bundle) in (0..MAX).zip(iter.by_ref()) {
+            let child_entity = Link::spawn_child(world, (child_bundle, ChildSlot(slot))).id();
+            if !insert_child_in_slot_order(world, self.parent_entity, child_entity, ChildSlot(slot))
+            {
+                return;
+            }
+            slots[slot as usize] = Some(child_entity);
+        }
+
+        let mut parent_mut = world.entity_mut(self.parent_entity);
+        parent_mut.insert(ChildSlots(slots));
+
+        // Check for a single overflowing item, rather than draining the iterator
+        if iter.next().is_some() {
+            let at_least = iter.size_hint().0.saturating_add(1);
+            parent_mut.insert(DroppedChildren { at_least });
+        }
+    }
+}
+
+/// Creates a [`WithSlottedChildren`] component, which spawns a child in each of the slots `0..MAX`
+/// for the items generated by `iter`, in order.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::{spawn_slotted_children, ChildSlots};
+///
+/// #[derive(Component)]
+/// struct Item(&'static str);
+///
+/// let mut world = World::new();
+/// let hotbar = world
+///     .spawn(spawn_slotted_children::<_, _, 4>([Item("sword"), Item("bow")]))
+///     .id();
+/// world.flush();
+///
+/// let slots = world.get::<ChildSlots>(hotbar).unwrap();
+/// let bow = slots.get(1).unwrap();
+/// assert_eq!(world.get::<Item>(bow).unwrap().0, "bow");
+/// assert_eq!(slots.get(2), None);
+/// ```
+pub fn spawn_slotted_children<B: Bundle, I: IntoIterator<Item = B>, const MAX: u8>(
+    iter: I,
+) -> WithSlottedChildren<B, I, MAX> {
+    WithSlottedChildren(iter)
+}
+
+/// A component that, when added to an entity, will call the stored closure with access to [`Commands`],
+/// and add a child entity with the bundle it returns.
+///
+/// This allows the child's construction to queue further work, such as spawning sibling side-effects.
+/// Commands queued by the closure are applied immediately after the child is spawned.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::WithChildCommands;
+///
+/// #[derive(Component)]
+/// struct Button;
+///
+/// #[derive(Component)]
+/// struct Tooltip;
+///
+/// let mut world = World::new();
+/// let parent = world
+///     .spawn(WithChildCommands(|commands: &mut Commands| {
+///         commands.spawn(Tooltip);
+///         Button
+///     }))
+///     .id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(parent).unwrap().len(), 1);
+/// assert_eq!(world.query::<&Tooltip>().iter(&world).count(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WithChildCommands<B: Bundle, F: FnOnce(&mut Commands) -> B>(pub F);
+
+impl<B: Bundle, F: FnOnce(&mut Commands) -> B + Send + Sync + 'static> Component
+    for WithChildCommands<B, F>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_child_commands_hook::<B, F>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildCommands`] is added to an entity.
+///
+/// Generates a [`WithChildCommandsCommand`].
+fn with_child_commands_hook<B: Bundle, F: FnOnce(&mut Commands) -> B + Send + Sync + 'static>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildCommandsCommand::<B, F>::new,
+    );
+}
+
+/// A [`Command`] that runs the closure stored in the [`WithChildCommands<B, F>`] component of an entity,
+/// spawns the resulting child, and applies any commands queued by the closure.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildCommandsCommand<B, F> {
+    /// The entity holding the [`WithChildCommands`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, F)>,
+}
+
+impl<B, F> WithChildCommandsCommand<B, F> {
+    /// Creates a new [`WithChildCommandsCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, F: FnOnce(&mut Commands) -> B + Send + Sync + 'static> Command
+    for WithChildCommandsCommand<B, F>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithChildCommands(f)) = entity_mut.take::<WithChildCommands<B, F>>() else {
+            on_cancelled("WithChildCommands");
+            return;
+        };
+
+        let mut queue = CommandQueue::default();
+        let bundle = f(&mut Commands::new(&mut queue, world));
+
+        let child_entity = Link::spawn_child(world, bundle).id();
+        Link::link_child(world, self.parent_entity, child_entity);
+        queue.apply(world);
+    }
+}
+
+/// A component that, when added to an entity, will add a child entity with the bundle returned by the closure,
+/// which can read the parent's components.
+///
+/// Unlike [`WithChild`], the command that spawns the child is queued from an `on_insert` hook rather than an `on_add` hook.
+/// As Bevy runs all `on_add` hooks (and `OnAdd` observers) for a bundle before any `on_insert` hooks,
+/// this command is ordered after any commands queued by the parent's other components as they were added,
+/// such as hooks that insert further components on the parent.
+/// This matters when the child's construction depends on the parent being fully set up.
+///
+/// Usually constructed via [`after_parent_ready`].
+#[derive(Debug, Clone, Default)]
+pub struct WithChildAfterParentReady<B: Bundle, F: FnOnce(EntityRef) -> B>(pub F);
+
+impl<B: Bundle, F: FnOnce(EntityRef) -> B + Send + Sync + 'static> Component
+    for WithChildAfterParentReady<B, F>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_insert(with_child_after_parent_ready_hook::<B, F>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildAfterParentReady`] is inserted on an entity.
+///
+/// Generates a [`WithChildAfterParentReadyCommand`].
+fn with_child_after_parent_ready_hook<
+    B: Bundle,
+    F: FnOnce(EntityRef) -> B + Send + Sync + 'static,
+>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildAfterParentReadyCommand::<B, F>::new,
+    );
+}
+
+/// A [`Command`] that calls the closure stored in the [`WithChildAfterParentReady<B, F>`] component of an entity
+/// with the parent, and spawns the resulting child.
+///
+/// This is queued automatically when the component is inserted, but can also be queued manually.
+pub struct WithChildAfterParentReadyCommand<B, F> {
+    /// The entity holding the [`WithChildAfterParentReady`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, F)>,
+}
+
+impl<B, F> WithChildAfterParentReadyCommand<B, F> {
+    /// Creates a new [`WithChildAfterParentReadyCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, F: FnOnce(EntityRef) -> B + Send + Sync + 'static> Command
+    for WithChildAfterParentReadyCommand<B, F>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithChildAfterParentReady(f)) =
+            entity_mut.take::<WithChildAfterParentReady<B, F>>()
+        else {
+            on_cancelled("WithChildAfterParentReady");
+            return;
+        };
+
+        let bundle = f(world.entity(self.parent_entity));
+        let child_entity = Link::spawn_child(world, bundle).id();
+        Link::link_child(world, self.parent_entity, child_entity);
+    }
+}
+
+/// Creates a [`WithChildAfterParentReady`] component, which spawns a child built from the parent
+/// once the parent's other components have finished being added.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::after_parent_ready;
+///
+/// #[derive(Component, Clone)]
+/// struct Theme(u8);
+///
+/// let mut world = World::new();
+/// let parent = world
+///     .spawn((
+///         after_parent_ready(|parent: EntityRef| parent.get::<Theme>().unwrap().clone()),
+///         Theme(3),
+///     ))
+///     .id();
+/// world.flush();
+///
+/// let child = world.get::<Children>(parent).unwrap()[0];
+/// assert_eq!(world.get::<Theme>(child).unwrap().0, 3);
+/// ```
+pub fn after_parent_ready<B, F>(f: F) -> WithChildAfterParentReady<B, F>
+where
+    B: Bundle,
+    F: FnOnce(EntityRef) -> B,
+{
+    WithChildAfterParentReady(f)
+}
+
+/// A component that, when added to an entity, will add a child entity with the given bundle.
+///
+/// This component will be removed from the entity immediately upon being spawned,
+/// and the supplied iterator will be iterated to completion to generate the data needed for each child.
+/// See [`WithChild`] for a more convenient API when adding only one child (or multiple children with distinct bundle types).
+///
+/// Under the hood, this is done using component lifecycle hooks.
+///
+/// # Examples
+///
+/// Just like when using [`Commands::spawn_batch`], any iterator that returns a bundle of the same type can be used.
+///
+/// Working with vectors, arrays and other collections is straightforward:
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::WithChildren;
+///
+/// #[derive(Component)]
+/// struct Name(&'static str);
+///
+/// fn spawn_hierarchy_with_vector(mut commands: Commands) {
+///   commands.spawn(
+///    (Name("Zeus"),
+///     WithChildren([Name("Athena"), Name("Apollo"), Name("Hermes")])
+///   ));
+/// }
+///```
+///
+/// However, generator-style iterators can also be used to dynamically vary the number and property of children:
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::WithChildren;
+///
+/// #[derive(Component)]
+/// struct A;
+///
+/// #[derive(Component)]
+/// struct ChildNumber(usize);
+///
+/// fn spawn_hierarchy_with_generator(mut commands: Commands) {
+///   commands.spawn(
+///    (A, // Component on parent
+///      WithChildren((0..3).map(|i| (ChildNumber(i)))) // Each child will have a ChildNumber component
+///    ));
+/// }
+///```
+///
+/// Unlike [`WithChild`], this does not implement [`PartialEq`] or [`Hash`]:
+/// most iterators (such as [`Map`] with a closure) can't be compared without consuming them.
+///
+/// The iterator is always consumed serially, on the thread applying the command.
+/// To build expensive bundles in parallel, use `spawn_children_parallel`, with the `parallel` feature, instead:
+/// how much faster that is depends on the cost of each bundle and the number of threads, and hasn't been benchmarked yet.
+#[derive(Debug, Clone, Default)]
+pub struct WithChildren<B: Bundle, I: IntoIterator<Item = B>>(pub I);
+
+impl<B: Bundle> WithChildren<B, Empty<B>> {
+    /// Creates a [`WithChildren`] component that is guaranteed to spawn no children.
+    ///
+    /// Unlike [`Default`], which defers to the default value of the iterator type,
+    /// this always uses an [`Empty`] iterator.
+    /// This is useful as a placeholder in conditional code,
+    /// where a [`WithChildren`] component of a fixed type is needed in every branch.
+    ///
+    /// ```rust
+    /// use bevy_ecs::prelude::*;
+    /// use bevy_hierarchy::Children;
+    /// use i_cant_believe_its_not_bsn::WithChildren;
+    ///
+    /// #[derive(Component)]
+    /// struct Leaf;
+    ///
+    /// let mut world = World::new();
+    /// let parent = world.spawn(WithChildren::<Leaf, _>::empty()).id();
+    /// world.flush();
+    ///
+    /// assert!(world.get::<Children>(parent).is_none());
+    /// ```
+    pub const fn empty() -> Self {
+        Self(core::iter::empty())
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = B>> WithChildren<B, I> {
+    /// Returns the iterator (or collection) that the children will be spawned from.
+    pub fn into_inner(self) -> I {
+        self.0
+    }
+
+    /// Returns a reference to the iterator (or collection) that the children will be spawned from.
+    pub fn get(&self) -> &I {
+        &self.0
+    }
+
+    /// Returns a mutable reference to the iterator (or collection) that the children will be spawned from.
+    ///
+    /// This can be used to adjust the children before the component is resolved,
+    /// for example by a system running while [`ManualResolution`](crate::ManualResolution) is enabled.
+    ///
+    /// ```rust
+    /// use bevy_ecs::prelude::*;
+    /// use bevy_hierarchy::Children;
+    /// use i_cant_believe_its_not_bsn::{resolve_deferred, ManualResolution, WithChildren};
+    ///
+    /// #[derive(Component)]
+    /// struct Row(u32);
+    ///
+    /// let mut world = World::new();
+    /// world.init_resource::<ManualResolution>();
+    /// let table = world.spawn(WithChildren(vec![Row(1), Row(2)])).id();
+    ///
+    /// // Append a row before the children are spawned
+    /// let mut with_children = world.get_mut::<WithChildren<Row, Vec<Row>>>(table).unwrap();
+    /// with_children.get_mut().push(Row(3));
+    ///
+    /// resolve_deferred(&mut world);
+    /// assert_eq!(world.get::<Children>(table).unwrap().len(), 3);
+    /// ```
+    pub fn get_mut(&mut self) -> &mut I {
+        &mut self.0
+    }
+}
+
+/// Collects the bundles of an iterator into a [`Vec`], to be spawned as children by a [`WithChildren`] component.
+///
+/// This allows iterator pipelines to end in `.collect()`.
+/// Collecting eagerly drops the iterator's own type, so that closures in the pipeline don't appear in the component's type.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::WithChildren;
+///
+/// #[derive(Component)]
+/// struct Score(u32);
+///
+/// let mut world = World::new();
+/// let leaderboard: WithChildren<_, _> = [120, 45, 300]
+///     .into_iter()
+///     .filter(|&score| score >= 100)
+///     .map(Score)
+///     .collect();
+/// let parent = world.spawn(leaderboard).id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(parent).unwrap().len(), 2);
+/// ```
+impl<B: Bundle> FromIterator<B> for WithChildren<B, Vec<B>> {
+    fn from_iter<T: IntoIterator<Item = B>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// A component that spawns children, such as [`WithChild`] or [`WithChildren`].
+///
+/// This allows generic code, such as template transformations or inspection tools,
+/// to treat components that spawn a single child and those that spawn many uniformly,
+/// as an iterator over the bundles of the children they will spawn.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::{SpawnsChildren, WithChild, WithChildren};
+///
+/// #[derive(Component)]
+/// struct Enemy {
+///     health: u32,
+/// }
+///
+/// /// Sums the health of every enemy that a component will spawn.
+/// fn total_health(spawner: impl SpawnsChildren<Bundle = Enemy>) -> u32 {
+///     spawner.into_child_bundles().map(|enemy| enemy.health).sum()
+/// }
+///
+/// assert_eq!(total_health(WithChild(Enemy { health: 10 })), 10);
+/// assert_eq!(
+///     total_health(WithChildren((1..=3).map(|health| Enemy { health }))),
+///     6
+/// );
+/// ```
+pub trait SpawnsChildren {
+    /// The bundle that each child is spawned with.
+    type Bundle: Bundle;
+
+    /// The iterator returned by [`SpawnsChildren::into_child_bundles`].
+    type IntoIter: Iterator<Item = Self::Bundle>;
+
+    /// Consumes the component, returning the bundles of the children it would have spawned, in order.
+    fn into_child_bundles(self) -> Self::IntoIter;
+}
+
+impl<B: Bundle> SpawnsChildren for WithChild<B> {
+    type Bundle = B;
+    type IntoIter = core::iter::Once<B>;
+
+    fn into_child_bundles(self) -> Self::IntoIter {
+        core::iter::once(self.0)
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = B>> SpawnsChildren for WithChildren<B, I> {
+    type Bundle = B;
+    type IntoIter = I::IntoIter;
+
+    fn into_child_bundles(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Component
+    for WithChildren<B, I>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_children_hook::<B, I>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildren`] is added to an entity.
+///
+/// Generates a [`WithChildrenCommand`].
+fn with_children_hook<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildrenCommand::<B, I>::new,
+    );
+}
+
+/// A [`Command`] that spawns the children generated by the [`WithChildren<B, I>`] component of an entity.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildrenCommand<B, I> {
+    /// The entity holding the [`WithChildren`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, I)>,
+}
+
+impl<B, I> WithChildrenCommand<B, I> {
+    /// Creates a new [`WithChildrenCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Command
+    for WithChildrenCommand<B, I>
+{
+    fn apply(self, world: &mut World) {
+        #[cfg(feature = "trace")]
+        let span = info_span!(
+            "with_children",
+            parent = ?self.parent_entity,
+            count = tracing::field::Empty
+        )
+        .entered();
+
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(with_children_component) = entity_mut.take::<WithChildren<B, I>>() else {
+            on_cancelled("WithChildren");
+            return;
+        };
+        entity_mut.insert(SpawnsChildrenMarker);
+
+        #[cfg(feature = "trace")]
+        let mut child_count: usize = 0;
+
+        #[cfg(feature = "auto_name")]
+        let mut namer = crate::auto_name::ChildNamer::new(&entity_mut);
+
+        Link::spawn_linked(
+            world,
+            self.parent_entity,
+            with_children_component.0,
+            |_child| {
+                #[cfg(feature = "auto_name")]
+                namer.name_next(_child);
+
+                #[cfg(feature = "trace")]
+                {
+                    child_count += 1;
+                }
+            },
+        );
+
+        #[cfg(feature = "trace")]
+        span.record("count", child_count);
+    }
+}
+
+/// A component that records the position of a child among the children spawned alongside it.
+///
+/// Indices are zero-based and contiguous.
+/// This is added to each child spawned via [`spawn_indexed_children`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChildIndex(pub usize);
+
+/// Creates a [`WithChildren`] component that spawns a child for each item in `iter`,
+/// adding a [`ChildIndex`] component to each child that records its position in the iterator.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{spawn_indexed_children, ChildIndex};
+///
+/// #[derive(Component)]
+/// struct Slot;
+///
+/// let mut world = World::new();
+/// let parent = world.spawn(spawn_indexed_children([Slot, Slot, Slot])).id();
+/// world.flush();
+///
+/// let children = world.get::<Children>(parent).unwrap();
+/// assert_eq!(world.get::<ChildIndex>(children[2]), Some(&ChildIndex(2)));
+/// ```
+pub fn spawn_indexed_children<B: Bundle, I: IntoIterator<Item = B>>(
+    iter: I,
+) -> WithChildren<(B, ChildIndex), impl Iterator<Item = (B, ChildIndex)> + Send + Sync + 'static>
+where
+    I::IntoIter: Send + Sync + 'static,
+{
+    WithChildren(
+        iter.into_iter()
+            .enumerate()
+            .map(|(index, bundle)| (bundle, ChildIndex(index))),
+    )
+}
+
+/// Creates a [`WithChildren`] component that spawns one child per pair of items from `iter_a` and `iter_b`,
+/// using `f` to build each child's bundle.
+///
+/// Just like [`Iterator::zip`], iteration stops as soon as either iterator is exhausted:
+/// when the inputs have different lengths, the number of children is equal to the length of the shorter one.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::spawn_children_zip;
+///
+/// #[derive(Component)]
+/// struct Position(f32);
+///
+/// #[derive(Component)]
+/// struct Color(u8);
+///
+/// let mut world = World::new();
+/// let positions = vec![0.0, 1.0, 2.0];
+/// let colors = vec![255, 0, 127];
+/// let parent = world
+///     .spawn(spawn_children_zip(positions, colors, |x, c| (Position(x), Color(c))))
+///     .id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(parent).unwrap().len(), 3);
+/// ```
+pub fn spawn_children_zip<B, IA, IB, F>(
+    iter_a: IA,
+    iter_b: IB,
+    mut f: F,
+) -> WithChildren<B, impl Iterator<Item = B> + Send + Sync + 'static>
+where
+    B: Bundle,
+    IA: IntoIterator,
+    IB: IntoIterator,
+    IA::IntoIter: Send + Sync + 'static,
+    IB::IntoIter: Send + Sync + 'static,
+    F: FnMut(IA::Item, IB::Item) -> B + Send + Sync + 'static,
+{
+    WithChildren(iter_a.into_iter().zip(iter_b).map(move |(a, b)| f(a, b)))
+}
+
+/// A [`WithChildren`] component that lazily builds each child's bundle from its index.
+///
+/// The closure and the count are stored in the component:
+/// no bundles are created until the command is applied.
+///
+/// Usually constructed via [`spawn_children_with_index`].
+pub type WithChildrenIndexed<B, F> = WithChildren<B, Map<Range<usize>, F>>;
+
+/// Creates a [`WithChildrenIndexed`] component that spawns `count` children,
+/// calling `f` with each child's index to build its bundle when the command is applied.
+///
+/// Unlike [`spawn_indexed_children`], which tags eagerly-constructed bundles with a [`ChildIndex`],
+/// this avoids materializing any bundles up front.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::spawn_children_with_index;
+///
+/// #[derive(Component)]
+/// struct Row(usize);
+///
+/// let mut world = World::new();
+/// let table = world.spawn(spawn_children_with_index(1000, Row)).id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(table).unwrap().len(), 1000);
+/// ```
+pub fn spawn_children_with_index<B, F>(count: usize, f: F) -> WithChildrenIndexed<B, F>
+where
+    B: Bundle,
+    F: Fn(usize) -> B,
+{
+    WithChildren((0..count).map(f))
+}
+
+/// Creates a [`WithChildren`] component that only spawns a child for the items of `iter` for which `f` returns `true`.
+///
+/// Items are filtered lazily, when the command is applied.
+/// If every item is filtered out, the component is still removed, and no children are spawned.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::spawn_children_filtered;
+///
+/// #[derive(Component)]
+/// struct Enemy {
+///     health: u32,
+/// }
+///
+/// let mut world = World::new();
+/// let enemies = vec![Enemy { health: 10 }, Enemy { health: 0 }, Enemy { health: 5 }];
+/// let wave = world
+///     .spawn(spawn_children_filtered(enemies, |enemy| enemy.health > 0))
+///     .id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(wave).unwrap().len(), 2);
+/// ```
+pub fn spawn_children_filtered<B, I, F>(iter: I, f: F) -> WithChildren<B, Filter<I::IntoIter, F>>
+where
+    B: Bundle,
+    I: IntoIterator<Item = B>,
+    F: Fn(&B) -> bool,
+{
+    WithChildren(iter.into_iter().filter(f))
+}
+
+/// Creates a [`WithChildren`] component that only spawns a child for the first item with each key,
+/// as returned by `key_fn`, skipping any later items with the same key.
+///
+/// Children are spawned in the order in which each key first occurs.
+/// Seen keys are tracked in a [`HashSet`] when the command is applied,
+/// which is useful when spawning rows from noisy data sources that may contain duplicates.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::spawn_children_dedup_by;
+///
+/// #[derive(Component)]
+/// struct Contact {
+///     email: &'static str,
+/// }
+///
+/// let contacts = vec![
+///     Contact { email: "ada@example.com" },
+///     Contact { email: "grace@example.com" },
+///     Contact { email: "ada@example.com" },
+/// ];
+///
+/// let mut world = World::new();
+/// let list = world
+///     .spawn(spawn_children_dedup_by(contacts, |contact| contact.email))
+///     .id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(list).unwrap().len(), 2);
+/// ```
+pub fn spawn_children_dedup_by<B, I, K, F>(
+    iter: I,
+    mut key_fn: F,
+) -> WithChildren<B, impl Iterator<Item = B> + Send + Sync + 'static>
+where
+    B: Bundle,
+    I: IntoIterator<Item = B>,
+    I::IntoIter: Send + Sync + 'static,
+    K: Eq + Hash + Send + Sync + 'static,
+    F: FnMut(&B) -> K + Send + Sync + 'static,
+{
+    let mut seen_keys = HashSet::new();
+    WithChildren(
+        iter.into_iter()
+            .filter(move |bundle| seen_keys.insert(key_fn(bundle))),
+    )
+}
+
+/// Creates a [`WithChildren`] component whose children all share the same `shared` data.
+///
+/// Each child bundle is built by calling `f` with the shared [`Arc`] and an item of `iter`.
+/// Cloning the [`Arc`] in `f` only increments its reference count,
+/// so heavy, immutable data such as meshes, lookup tables or configuration can be referenced by many children
+/// without being copied.
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::spawn_children_shared;
+///
+/// struct Palette {
+///     colors: Vec<[f32; 3]>,
+/// }
+///
+/// #[derive(Component)]
+/// struct Swatch {
+///     palette: Arc<Palette>,
+///     index: usize,
+/// }
+///
+/// let palette = Arc::new(Palette {
+///     colors: vec![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+/// });
+///
+/// let mut world = World::new();
+/// let picker = world
+///     .spawn(spawn_children_shared(
+///         palette.clone(),
+///         0..palette.colors.len(),
+///         |palette, index| Swatch {
+///             palette: Arc::clone(palette),
+///             index,
+///         },
+///     ))
+///     .id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(picker).unwrap().len(), 3);
+/// // One reference held here, and one by each swatch
+/// assert_eq!(Arc::strong_count(&palette), 4);
+/// ```
+pub fn spawn_children_shared<T, B, I, F>(
+    shared: Arc<T>,
+    iter: I,
+    mut f: F,
+) -> WithChildren<B, impl Iterator<Item = B> + Send + Sync + 'static>
+where
+    T: Send + Sync + 'static,
+    B: Bundle,
+    I: IntoIterator,
+    I::IntoIter: Send + Sync + 'static,
+    F: FnMut(&Arc<T>, I::Item) -> B + Send + Sync + 'static,
+{
+    WithChildren(iter.into_iter().map(move |item| f(&shared, item)))
+}
+
+/// Creates a [`WithChildren`] component that spawns one child for every `step`-th value in `start..end`,
+/// calling `f` with each value to build its bundle when the command is applied.
+///
+/// Children are spawned in ascending order of their values.
+/// This is convenient for laying out grids and tilemaps.
+///
+/// # Panics
+///
+/// Panics in debug builds if `step` is zero.
+/// In release builds, a step of zero is treated as a step of one.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::spawn_children_range;
+///
+/// #[derive(Component)]
+/// struct Column {
+///     x: usize,
+/// }
+///
+/// let mut world = World::new();
+/// // One column every 16 pixels, across a 128 pixel wide grid
+/// let grid = world
+///     .spawn(spawn_children_range(0, 128, 16, |x| Column { x }))
+///     .id();
+/// world.flush();
+///
+/// let children = world.get::<Children>(grid).unwrap();
+/// assert_eq!(children.len(), 8);
+/// assert_eq!(world.get::<Column>(children[7]).unwrap().x, 112);
+/// ```
+pub fn spawn_children_range<B, F>(
+    start: usize,
+    end: usize,
+    step: usize,
+    f: F,
+) -> WithChildren<B, Map<StepBy<Range<usize>>, F>>
+where
+    B: Bundle,
+    F: Fn(usize) -> B,
+{
+    debug_assert!(
+        step > 0,
+        "spawn_children_range requires a step greater than zero"
+    );
+    WithChildren((start..end).step_by(step.max(1)).map(f))
+}
+
+/// A component that identifies a group of entities spawned together by [`WithChildrenGrouped`].
+///
+/// Both the parent and each of the children spawned by a single [`WithChildrenGrouped`] are tagged with the same group,
+/// allowing them to be queried together, even across multiple parents.
+/// Each group id is unique for the lifetime of the program, and is generated from a global atomic counter.
+///
+/// As an entity can only hold a single [`SpawnGroup`],
+/// a parent that spawns multiple groups will be tagged with the most recent group.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SpawnGroup(pub u64);
+
+impl SpawnGroup {
+    /// Generates a new, unique [`SpawnGroup`].
+    pub fn new() -> Self {
+        static NEXT_SPAWN_GROUP: AtomicU64 = AtomicU64::new(0);
+
+        SpawnGroup(NEXT_SPAWN_GROUP.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for SpawnGroup {
+    /// Generates a new, unique [`SpawnGroup`].
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A component that, when added to an entity, will add a child entity for each item generated by the iterator,
+/// tagging both the parent and its new children with a fresh [`SpawnGroup`].
+///
+/// Otherwise, this behaves just like [`WithChildren`].
+/// Usually constructed via [`spawn_children_grouped`].
+#[derive(Debug, Clone, Default)]
+pub struct WithChildrenGrouped<B: Bundle, I: IntoIterator<Item = B>>(pub I);
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Component
+    for WithChildrenGrouped<B, I>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_children_grouped_hook::<B, I>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildrenGrouped`] is added to an entity.
+///
+/// Generates a [`WithChildrenGroupedCommand`].
+fn with_children_grouped_hook<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildrenGroupedCommand::<B, I>::new,
+    );
+}
+
+/// A [`Command`] that spawns the children generated by the [`WithChildrenGrouped<B, I>`] component of an entity,
+/// tagging them and their parent with a new [`SpawnGroup`].
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildrenGroupedCommand<B, I> {
+    /// The entity holding the [`WithChildrenGrouped`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, I)>,
+}
+
+impl<B, I> WithChildrenGroupedCommand<B, I> {
+    /// Creates a new [`WithChildrenGroupedCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Command
+    for WithChildrenGroupedCommand<B, I>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(with_children_component) = entity_mut.take::<WithChildrenGrouped<B, I>>() else {
+            on_cancelled("WithChildrenGrouped");
+            return;
+        };
+
+        let spawn_group = SpawnGroup::new();
+        entity_mut.insert(spawn_group);
+
+        for child_bundle in with_children_component.0 {
+            let child_entity = Link::spawn_child(world, (child_bundle, spawn_group)).id();
+            if !Link::link_child(world, self.parent_entity, child_entity) {
+                return;
+            }
+        }
+    }
+}
+
+/// Creates a [`WithChildrenGrouped`] component, which spawns a child for each item in `iter`
+/// and tags them and their parent with a shared [`SpawnGroup`].
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{spawn_children_grouped, SpawnGroup};
+///
+/// #[derive(Component)]
+/// struct Card;
+///
+/// let mut world = World::new();
+/// let parent = world.spawn(spawn_children_grouped([Card, Card])).id();
+/// world.flush();
+///
+/// let group = world.get::<SpawnGroup>(parent).unwrap();
+/// let children = world.get::<Children>(parent).unwrap();
+/// assert_eq!(world.get::<SpawnGroup>(children[0]), Some(group));
+/// ```
+pub fn spawn_children_grouped<B: Bundle, I: IntoIterator<Item = B>>(
+    iter: I,
+) -> WithChildrenGrouped<B, I> {
+    WithChildrenGrouped(iter)
+}
+
+/// A component that records how many children were dropped by [`spawn_children_capped`] or [`spawn_slotted_children`].
+///
+/// This is added to the parent entity only if its iterator produced more items than the cap allowed.
+/// The remaining items are never drained, as the iterator may be unbounded,
+/// so the exact number of dropped children is unknown.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DroppedChildren {
+    /// A lower bound on the number of dropped children: at least this many items were left in the iterator.
+    ///
+    /// This is one more than the lower bound of the iterator's [`Iterator::size_hint`] after the overflowing item,
+    /// so it is always at least `1`, and unbounded iterators report [`usize::MAX`].
+    pub at_least: usize,
+}
+
+/// A component that, when added to an entity, will add up to `max` children,
+/// one for each item generated by the iterator.
+///
+/// This is a safer alternative to [`WithChildren`] when working with potentially unbounded generators.
+/// Iteration stops as soon as the cap is reached:
+/// if any items remain, a [`DroppedChildren`] component is added to the parent.
+///
+/// Usually constructed via [`spawn_children_capped`].
+#[derive(Debug, Clone, Default)]
+pub struct WithChildrenCapped<B: Bundle, I: IntoIterator<Item = B>>(pub I, pub usize);
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Component
+    for WithChildrenCapped<B, I>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_children_capped_hook::<B, I>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildrenCapped`] is added to an entity.
+///
+/// Generates a [`WithChildrenCappedCommand`].
+fn with_children_capped_hook<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildrenCappedCommand::<B, I>::new,
+    );
+}
+
+/// A [`Command`] that spawns the children generated by the [`WithChildrenCapped<B, I>`] component of an entity,
+/// up to its cap.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildrenCappedCommand<B, I> {
+    /// The entity holding the [`WithChildrenCapped`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, I)>,
+}
+
+impl<B, I> WithChildrenCappedCommand<B, I> {
+    /// Creates a new [`WithChildrenCappedCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Command
+    for WithChildrenCappedCommand<B, I>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithChildrenCapped(iter, max)) = entity_mut.take::<WithChildrenCapped<B, I>>()
+        else {
+            on_cancelled("WithChildrenCapped");
+            return;
+        };
+
+        let mut iter = iter.into_iter();
+        for child_bundle in iter.by_ref().take(max) {
+            let child_entity = Link::spawn_child(world, child_bundle).id();
+            if !Link::link_child(world, self.parent_entity, child_entity) {
+                return;
+            }
+        }
+
+        // Check for a single overflowing item, rather than draining the iterator
+        if iter.next().is_some() {
+            let at_least = iter.size_hint().0.saturating_add(1);
+            world
+                .entity_mut(self.parent_entity)
+                .insert(DroppedChildren { at_least });
+        }
+    }
+}
+
+/// Creates a [`WithChildrenCapped`] component, which spawns at most `max` children from `iter`.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{spawn_children_capped, DroppedChildren};
+///
+/// #[derive(Component)]
+/// struct A;
+///
+/// let mut world = World::new();
+/// let parent = world.spawn(spawn_children_capped(vec![A, A, A, A, A], 3)).id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(parent).unwrap().len(), 3);
+/// assert_eq!(world.get::<DroppedChildren>(parent), Some(&DroppedChildren { at_least: 2 }));
+/// ```
+pub fn spawn_children_capped<B: Bundle, I: IntoIterator<Item = B>>(
+    iter: I,
+    max: usize,
+) -> WithChildrenCapped<B, I> {
+    WithChildrenCapped(iter, max)
+}
+
+/// A component that, when added to an entity, will add a child entity for each item generated by the iterator,
+/// distributing them between this entity and another parent.
+///
+/// Items for which the predicate returns `true` become children of this entity,
+/// while all other items become children of the other parent, which must already exist.
+///
+/// Usually constructed via [`spawn_children_partitioned`].
+#[derive(Debug, Clone)]
+pub struct WithChildrenPartitioned<B: Bundle, I: IntoIterator<Item = B>, F: FnMut(&B) -> bool>(
+    pub I,
+    pub F,
+    pub Entity,
+);
+
+/// Remaps the other parent entity, so that a [`WithChildrenPartitioned`] loaded into a different world
+/// sends its rejected children to the right place.
+impl<B: Bundle, I: IntoIterator<Item = B>, F: FnMut(&B) -> bool> MapEntities
+    for WithChildrenPartitioned<B, I, F>
+{
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.2 = entity_mapper.map_entity(self.2);
+    }
+}
+
+impl<
+        B: Bundle,
+        I: IntoIterator<Item = B> + Send + Sync + 'static,
+        F: FnMut(&B) -> bool + Send + Sync + 'static,
+    > Component for WithChildrenPartitioned<B, I, F>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_children_partitioned_hook::<B, I, F>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildrenPartitioned`] is added to an entity.
+///
+/// Generates a [`WithChildrenPartitionedCommand`].
+fn with_children_partitioned_hook<
+    B: Bundle,
+    I: IntoIterator<Item = B> + Send + Sync + 'static,
+    F: FnMut(&B) -> bool + Send + Sync + 'static,
+>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildrenPartitionedCommand::<B, I, F>::new,
+    );
+}
+
+/// A [`Command`] that spawns the children generated by the [`WithChildrenPartitioned<B, I, F>`] component of an entity,
+/// splitting them between the entity and the other parent.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildrenPartitionedCommand<B, I, F> {
+    /// The entity holding the [`WithChildrenPartitioned`] component, which will become the parent of matching items.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, I, F)>,
+}
+
+impl<B, I, F> WithChildrenPartitionedCommand<B, I, F> {
+    /// Creates a new [`WithChildrenPartitionedCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+        B: Bundle,
+        I: IntoIterator<Item = B> + Send + Sync + 'static,
+        F: FnMut(&B) -> bool + Send + Sync + 'static,
+    > Command for WithChildrenPartitionedCommand<B, I, F>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithChildrenPartitioned(iter, mut predicate, other_parent)) =
+            entity_mut.take::<WithChildrenPartitioned<B, I, F>>()
+        else {
+            on_cancelled("WithChildrenPartitioned");
+            return;
+        };
+
+        if world.get_entity(other_parent).is_none() {
+            on_missing(world, WithChildError::ParentMissing(other_parent));
+            return;
+        }
+
+        for child_bundle in iter {
+            let parent = if predicate(&child_bundle) {
+                self.parent_entity
+            } else {
+                other_parent
+            };
+            let child_entity = Link::spawn_child(world, child_bundle).id();
+            if !Link::link_child(world, parent, child_entity) {
+                return;
+            }
+        }
+    }
+}
+
+/// Creates a [`WithChildrenPartitioned`] component, which spawns children from `iter`,
+/// sending items that fail the `predicate` to `other_parent` instead.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::spawn_children_partitioned;
+///
+/// #[derive(Component)]
+/// struct Task { done: bool }
+///
+/// let mut world = World::new();
+/// let done_column = world.spawn_empty().id();
+/// let tasks = vec![Task { done: false }, Task { done: true }, Task { done: false }];
+/// let todo_column = world
+///     .spawn(spawn_children_partitioned(tasks, |task: &Task| !task.done, done_column))
+///     .id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(todo_column).unwrap().len(), 2);
+/// assert_eq!(world.get::<Children>(done_column).unwrap().len(), 1);
+/// ```
+pub fn spawn_children_partitioned<B, I, F>(
+    iter: I,
+    predicate: F,
+    other_parent: Entity,
+) -> WithChildrenPartitioned<B, I, F>
+where
+    B: Bundle,
+    I: IntoIterator<Item = B>,
+    F: FnMut(&B) -> bool,
+{
+    WithChildrenPartitioned(iter, predicate, other_parent)
+}
+
+/// A marker component added to each separator child spawned by [`WithChildrenInterspersed`].
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Separator;
+
+/// A component that, when added to an entity, will add a child entity for each item generated by the iterator,
+/// with a separator child between each pair of consecutive items.
+///
+/// Each separator's bundle is built by calling the closure, and is spawned alongside a [`Separator`] marker.
+/// No separator is spawned before the first item or after the last one.
+///
+/// Usually constructed via [`spawn_children_interspersed`].
+#[derive(Debug, Clone)]
+pub struct WithChildrenInterspersed<
+    B: Bundle,
+    S: Bundle,
+    I: IntoIterator<Item = B>,
+    F: FnMut() -> S,
+>(pub I, pub F);
+
+impl<
+        B: Bundle,
+        S: Bundle,
+        I: IntoIterator<Item = B> + Send + Sync + 'static,
+        F: FnMut() -> S + Send + Sync + 'static,
+    > Component for WithChildrenInterspersed<B, S, I, F>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_children_interspersed_hook::<B, S, I, F>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildrenInterspersed`] is added to an entity.
+///
+/// Generates a [`WithChildrenInterspersedCommand`].
+fn with_children_interspersed_hook<
+    B: Bundle,
+    S: Bundle,
+    I: IntoIterator<Item = B> + Send + Sync + 'static,
+    F: FnMut() -> S + Send + Sync + 'static,
+>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildrenInterspersedCommand::<B, S, I, F>::new,
+    );
+}
+
+/// A [`Command`] that spawns the children generated by the [`WithChildrenInterspersed<B, S, I, F>`] component of an entity,
+/// along with the separators between them.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildrenInterspersedCommand<B, S, I, F> {
+    /// The entity holding the [`WithChildrenInterspersed`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, S, I, F)>,
+}
+
+impl<B, S, I, F> WithChildrenInterspersedCommand<B, S, I, F> {
+    /// Creates a new [`WithChildrenInterspersedCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+        B: Bundle,
+        S: Bundle,
+        I: IntoIterator<Item = B> + Send + Sync + 'static,
+        F: FnMut() -> S + Send + Sync + 'static,
+    > Command for WithChildrenInterspersedCommand<B, S, I, F>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithChildrenInterspersed(iter, mut separator)) =
+            entity_mut.take::<WithChildrenInterspersed<B, S, I, F>>()
+        else {
+            on_cancelled("WithChildrenInterspersed");
+            return;
+        };
+
+        for (index, child_bundle) in iter.into_iter().enumerate() {
+            if index > 0 {
+                let separator_entity = Link::spawn_child(world, (separator(), Separator)).id();
+                if !Link::link_child(world, self.parent_entity, separator_entity) {
+                    return;
+                }
+            }
+
+            let child_entity = Link::spawn_child(world, child_bundle).id();
+            if !Link::link_child(world, self.parent_entity, child_entity) {
+                return;
+            }
+        }
+    }
+}
+
+/// Creates a [`WithChildrenInterspersed`] component, which spawns a child for each item in `iter`,
+/// with a separator child built by `separator` between each pair of items.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{spawn_children_interspersed, Separator};
+///
+/// #[derive(Component)]
+/// struct MenuItem(&'static str);
+///
+/// #[derive(Component)]
+/// struct Divider;
+///
+/// let mut world = World::new();
+/// let items = [MenuItem("Open"), MenuItem("Save"), MenuItem("Quit")];
+/// let menu = world
+///     .spawn(spawn_children_interspersed(items, || Divider))
+///     .id();
+/// world.flush();
+///
+/// let children = world.get::<Children>(menu).unwrap();
+/// assert_eq!(children.len(), 5);
+/// assert!(world.entity(children[1]).contains::<Separator>());
+/// ```
+pub fn spawn_children_interspersed<B, S, I, F>(
+    iter: I,
+    separator: F,
+) -> WithChildrenInterspersed<B, S, I, F>
+where
+    B: Bundle,
+    S: Bundle,
+    I: IntoIterator<Item = B>,
+    F: FnMut() -> S,
+{
+    WithChildrenInterspersed(iter, separator)
+}
+
+/// A component that records the child entities spawned by the most recent [`WithChildrenRecorded<B, I>`]
+/// added to this entity, in spawn order.
+///
+/// Unlike the parent's [`Children`](bevy_hierarchy::Children), this only contains the output of that specific component:
+/// children added by any other means are not included.
+/// It is generic over the bundle type, so that recording children of different types doesn't overwrite earlier records.
+///
+/// Entities are not removed from this list when they are despawned.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct SpawnedChildren<B: Bundle>(pub Vec<Entity>, pub PhantomData<B>);
+
+impl<B: Bundle> SpawnedChildren<B> {
+    /// Returns the recorded child entities, in spawn order.
+    pub fn entities(&self) -> &[Entity] {
+        &self.0
+    }
+}
+
+/// Remaps the recorded child entities, so that they remain correct when the parent is loaded into a different world.
+impl<B: Bundle> MapEntities for SpawnedChildren<B> {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        for entity in &mut self.0 {
+            *entity = entity_mapper.map_entity(*entity);
+        }
+    }
+}
+
+/// A component that, when added to an entity, will add a child entity for each item generated by the iterator,
+/// then record exactly which entities were spawned in a [`SpawnedChildren<B>`] component on the parent.
+///
+/// Any previous [`SpawnedChildren<B>`] record on the parent is replaced.
+///
+/// Usually constructed via [`spawn_children_recorded`].
+#[derive(Debug, Clone, Default)]
+pub struct WithChildrenRecorded<B: Bundle, I: IntoIterator<Item = B>>(pub I);
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Component
+    for WithChildrenRecorded<B, I>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_children_recorded_hook::<B, I>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildrenRecorded`] is added to an entity.
+///
+/// Generates a [`WithChildrenRecordedCommand`].
+fn with_children_recorded_hook<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildrenRecordedCommand::<B, I>::new,
+    );
+}
+
+/// A [`Command`] that spawns the children generated by the [`WithChildrenRecorded<B, I>`] component of an entity,
+/// and records them in a [`SpawnedChildren<B>`] component.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildrenRecordedCommand<B, I> {
+    /// The entity holding the [`WithChildrenRecorded`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, I)>,
+}
+
+impl<B, I> WithChildrenRecordedCommand<B, I> {
+    /// Creates a new [`WithChildrenRecordedCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Command
+    for WithChildrenRecordedCommand<B, I>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithChildrenRecorded(iter)) = entity_mut.take::<WithChildrenRecorded<B, I>>()
+        else {
+            on_cancelled("WithChildrenRecorded");
+            return;
+        };
+
+        let mut spawned = Vec::new();
+        let parent_exists = Link::spawn_linked(world, self.parent_entity, iter, |child| {
+            spawned.push(child.id());
+        });
+        if !parent_exists {
+            return;
+        }
+
+        world
+            .entity_mut(self.parent_entity)
+            .insert(SpawnedChildren::<B>(spawned, PhantomData));
+    }
+}
+
+/// Creates a [`WithChildrenRecorded`] component, which spawns a child for each item in `iter`
+/// and records their entities in a [`SpawnedChildren<B>`] component on the parent.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{spawn_children_recorded, SpawnedChildren, WithChild};
+///
+/// #[derive(Component)]
+/// struct Title;
+///
+/// #[derive(Component)]
+/// struct Entry(u32);
+///
+/// let mut world = World::new();
+/// let list = world
+///     .spawn((WithChild(Title), spawn_children_recorded(vec![Entry(1), Entry(2)])))
+///     .id();
+/// world.flush();
+///
+/// // The title is a child too, but isn't part of the record
+/// assert_eq!(world.get::<Children>(list).unwrap().len(), 3);
+/// let entries = world.get::<SpawnedChildren<Entry>>(list).unwrap();
+/// assert_eq!(entries.entities().len(), 2);
+/// ```
+pub fn spawn_children_recorded<B: Bundle, I: IntoIterator<Item = B>>(
+    iter: I,
+) -> WithChildrenRecorded<B, I> {
+    WithChildrenRecorded(iter)
+}
+
+/// A component that, when added to an entity, will spawn a child for each item of the iterator,
+/// building each child's bundle from the entity's `P` component and the item.
+///
+/// This extends [`WithChildInherit`] to many children:
+/// rather than cloning the parent's component, each child can derive whatever it needs from it,
+/// such as a color from a parent's theme.
+/// The parent's `P` component is read once, as it is when the command is applied, and shared by every child.
+///
+/// The parent must have a `P` component: if it doesn't, no children are spawned,
+/// and the [`FailurePolicy`](crate::FailurePolicy) is followed.
+///
+/// Usually constructed via [`spawn_children_projected`].
+#[derive(Debug, Clone, Default)]
+pub struct WithChildrenProjected<P, B, I, F>(pub I, pub F, pub PhantomData<(P, B)>);
+
+impl<P, B, I, F> Component for WithChildrenProjected<P, B, I, F>
+where
+    P: Component,
+    B: Bundle,
+    I: IntoIterator + Send + Sync + 'static,
+    F: FnMut(&P, I::Item) -> B + Send + Sync + 'static,
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_children_projected_hook::<P, B, I, F>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildrenProjected`] is added to an entity.
+///
+/// Generates a [`WithChildrenProjectedCommand`].
+fn with_children_projected_hook<P, B, I, F>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) where
+    P: Component,
+    B: Bundle,
+    I: IntoIterator + Send + Sync + 'static,
+    F: FnMut(&P, I::Item) -> B + Send + Sync + 'static,
+{
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildrenProjectedCommand::<P, B, I, F>::new,
+    );
+}
+
+/// A [`Command`] that spawns the children generated by the [`WithChildrenProjected<P, B, I, F>`] component of an entity,
+/// projecting the entity's `P` component into each child's bundle.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildrenProjectedCommand<P, B, I, F> {
+    /// The entity holding the [`WithChildrenProjected`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(P, B, I, F)>,
+}
+
+impl<P, B, I, F> WithChildrenProjectedCommand<P, B, I, F> {
+    /// Creates a new [`WithChildrenProjectedCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<P, B, I, F> Command for WithChildrenProjectedCommand<P, B, I, F>
+where
+    P: Component,
+    B: Bundle,
+    I: IntoIterator + Send + Sync + 'static,
+    F: FnMut(&P, I::Item) -> B + Send + Sync + 'static,
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithChildrenProjected(iter, mut f, _)) =
+            entity_mut.take::<WithChildrenProjected<P, B, I, F>>()
+        else {
+            on_cancelled("WithChildrenProjected");
+            return;
+        };
+
+        let Some(projected) = entity_mut.get::<P>() else {
+            on_missing(world, WithChildError::ComponentMissing(self.parent_entity));
+            return;
+        };
+
+        // The parent's component is borrowed while the bundles are built, so they must be collected before spawning
+        let bundles: Vec<B> = iter.into_iter().map(|item| f(projected, item)).collect();
+        Link::spawn_linked(world, self.parent_entity, bundles, |_| {});
+    }
+}
+
+/// Creates a [`WithChildrenProjected`] component, which spawns a child for each item of `iter`,
+/// with a bundle built by `f` from the parent's `P` component and the item.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::spawn_children_projected;
+///
+/// #[derive(Component)]
+/// struct Theme {
+///     font_size: f32,
+/// }
+///
+/// #[derive(Component)]
+/// struct Label {
+///     text: &'static str,
+///     font_size: f32,
+/// }
+///
+/// let mut world = World::new();
+/// let menu = world
+///     .spawn((
+///         Theme { font_size: 24.0 },
+///         spawn_children_projected(["Play", "Quit"], |theme: &Theme, text| Label {
+///             text,
+///             font_size: theme.font_size,
+///         }),
+///     ))
+///     .id();
+/// world.flush();
+///
+/// let children = world.get::<Children>(menu).unwrap();
+/// assert_eq!(world.get::<Label>(children[1]).unwrap().text, "Quit");
+/// assert_eq!(world.get::<Label>(children[1]).unwrap().font_size, 24.0);
+/// ```
+pub fn spawn_children_projected<P, B, I, F>(iter: I, f: F) -> WithChildrenProjected<P, B, I, F>
+where
+    P: Component,
+    B: Bundle,
+    I: IntoIterator,
+    F: FnMut(&P, I::Item) -> B,
+{
+    WithChildrenProjected(iter, f, PhantomData)
+}
+
+/// A component that, when added to an entity, will spawn a new parent entity with the given bundle,
+/// and make this entity its child.
+///
+/// This is the inverse of [`WithChild`], and is useful when an entity wants to wrap itself in a container
+/// without the caller needing to restructure the hierarchy.
+///
+/// If the entity already has a parent, the new parent is inserted between them:
+/// it takes the entity's place among the original parent's children,
+/// and the entity is re-parented under the new parent.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Parent;
+/// use i_cant_believe_its_not_bsn::spawn_parent;
+///
+/// #[derive(Component)]
+/// struct Container;
+///
+/// #[derive(Component)]
+/// struct Leaf;
+///
+/// let mut world = World::new();
+/// let leaf = world.spawn((Leaf, spawn_parent(Container))).id();
+/// world.flush();
+///
+/// let container = world.get::<Parent>(leaf).unwrap().get();
+/// assert!(world.entity(container).contains::<Container>());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WithParent<B: Bundle>(pub B);
+
+impl<B: Bundle> Component for WithParent<B> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_parent_hook::<B>);
+    }
+}
+
+/// A hook that runs whenever [`WithParent`] is added to an entity.
+///
+/// Generates a [`WithParentCommand`].
+fn with_parent_hook<B: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithParentCommand::<B>::new,
+    );
+}
+
+/// A [`Command`] that spawns the parent stored in the [`WithParent<B>`] component of an entity,
+/// and makes the entity its child.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithParentCommand<B> {
+    /// The entity holding the [`WithParent`] component, which will become the child.
+    pub child_entity: Entity,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> WithParentCommand<B> {
+    /// Creates a new [`WithParentCommand`] targeting the given entity.
+    pub const fn new(child_entity: Entity) -> Self {
+        Self {
+            child_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle> Command for WithParentCommand<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.child_entity) else {
+            on_missing(world, WithChildError::ChildMissing(self.child_entity));
+            return;
+        };
+
+        let Some(with_parent_component) = entity_mut.take::<WithParent<B>>() else {
+            on_cancelled("WithParent");
+            return;
+        };
+
+        let original_parent = Link::parent(world, self.child_entity);
+        let new_parent = world.spawn(with_parent_component.0).id();
+
+        if let Some(original_parent) = original_parent {
+            let index = Link::children(world, original_parent)
+                .iter()
+                .position(|&e| e == self.child_entity)
+                .unwrap_or_default();
+            Link::link_child_at(world, original_parent, index, new_parent);
+        }
+
+        Link::link_child(world, new_parent, self.child_entity);
+    }
+}
+
+/// Creates a [`WithParent`] component, which spawns a new parent with the given bundle above this entity.
+pub fn spawn_parent<B: Bundle>(bundle: B) -> WithParent<B> {
+    WithParent(bundle)
+}
+
+/// A component that, when added to an entity, will make each existing entity generated by the iterator
+/// a child of that entity, inserting the accompanying bundle onto it.
+///
+/// This unifies adoption and augmentation: rather than spawning new children,
+/// existing entities are reparented (removing them from any previous parent) and given extra components.
+/// Entities that no longer exist when the command is applied are skipped, following the [`FailurePolicy`](crate::FailurePolicy).
+///
+/// Usually constructed via [`adopt_children`].
+#[derive(Debug, Clone, Default)]
+pub struct AdoptChildren<B: Bundle, I: IntoIterator<Item = (Entity, B)>>(pub I);
+
+impl<B: Bundle, I: IntoIterator<Item = (Entity, B)> + Send + Sync + 'static> Component
+    for AdoptChildren<B, I>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(adopt_children_hook::<B, I>);
+    }
+}
+
+/// A hook that runs whenever [`AdoptChildren`] is added to an entity.
+///
+/// Generates an [`AdoptChildrenCommand`].
+fn adopt_children_hook<B: Bundle, I: IntoIterator<Item = (Entity, B)> + Send + Sync + 'static>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        AdoptChildrenCommand::<B, I>::new,
+    );
+}
+
+/// A [`Command`] that adopts the entities listed in the [`AdoptChildren<B, I>`] component of an entity.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct AdoptChildrenCommand<B, I> {
+    /// The entity holding the [`AdoptChildren`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, I)>,
+}
+
+impl<B, I> AdoptChildrenCommand<B, I> {
+    /// Creates a new [`AdoptChildrenCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = (Entity, B)> + Send + Sync + 'static> Command
+    for AdoptChildrenCommand<B, I>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(AdoptChildren(entries)) = entity_mut.take::<AdoptChildren<B, I>>() else {
+            on_cancelled("AdoptChildren");
+            return;
+        };
+
+        for (child_entity, bundle) in entries {
+            let Some(mut child_mut) = world.get_entity_mut(child_entity) else {
+                on_missing(world, WithChildError::ChildMissing(child_entity));
+                continue;
+            };
+
+            child_mut.insert(bundle);
+            Link::link_child(world, self.parent_entity, child_entity);
+        }
+    }
+}
+
+/// Creates an [`AdoptChildren`] component, which makes each listed entity a child of this one,
+/// inserting the accompanying bundle onto it.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Parent;
+/// use i_cant_believe_its_not_bsn::adopt_children;
+///
+/// #[derive(Component)]
+/// struct Squad;
+///
+/// #[derive(Component)]
+/// struct Recruited;
+///
+/// let mut world = World::new();
+/// let soldier = world.spawn_empty().id();
+/// let squad = world.spawn((Squad, adopt_children([(soldier, Recruited)]))).id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Parent>(soldier).unwrap().get(), squad);
+/// assert!(world.entity(soldier).contains::<Recruited>());
+/// ```
+pub fn adopt_children<B: Bundle, I: IntoIterator<Item = (Entity, B)>>(
+    entries: I,
+) -> AdoptChildren<B, I> {
+    AdoptChildren(entries)
+}
+
+/// A component that, when added to an entity, will insert the given bundle onto the given entity,
+/// spawning it if it doesn't exist yet, and add it as a child.
+///
+/// This gives control over the child's identity, which is useful when entity ids must be deterministic,
+/// such as when they are agreed upon over the network.
+/// The entity is spawned with [`World::get_or_spawn`], so it should have been reserved or received from elsewhere:
+/// if its index is already in use by an entity of a different generation, the [`FailurePolicy`](crate::FailurePolicy) is followed.
+///
+/// If the entity already exists, the bundle is inserted onto it,
+/// overwriting any components of the same types, while its other components are kept.
+/// If it already has a parent, it is moved to this one.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::WithChildEntity;
+///
+/// #[derive(Component)]
+/// struct Player;
+///
+/// let mut world = World::new();
+/// // An id that was allocated by the server
+/// let player = world.entities().reserve_entity();
+/// let team = world.spawn(WithChildEntity(player, Player)).id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(team).unwrap()[0], player);
+/// assert!(world.entity(player).contains::<Player>());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WithChildEntity<B: Bundle>(pub Entity, pub B);
+
+/// Remaps the designated child entity, so that it is correct when the parent is loaded into a different world.
+impl<B: Bundle> MapEntities for WithChildEntity<B> {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.0 = entity_mapper.map_entity(self.0);
+    }
+}
+
+impl<B: Bundle> Component for WithChildEntity<B> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_child_entity_hook::<B>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildEntity`] is added to an entity.
+///
+/// Generates a [`WithChildEntityCommand`].
+fn with_child_entity_hook<B: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildEntityCommand::<B>::new,
+    );
+}
+
+/// A [`Command`] that inserts the bundle stored in the [`WithChildEntity<B>`] component of an entity
+/// onto the designated child entity, spawning it if needed, and adds it as a child.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildEntityCommand<B> {
+    /// The entity holding the [`WithChildEntity`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> WithChildEntityCommand<B> {
+    /// Creates a new [`WithChildEntityCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle> Command for WithChildEntityCommand<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithChildEntity(child_entity, bundle)) = entity_mut.take::<WithChildEntity<B>>()
+        else {
+            on_cancelled("WithChildEntity");
+            return;
+        };
+
+        if child_entity == self.parent_entity {
+            on_missing(world, WithChildError::SelfParenting(child_entity));
+            return;
+        }
+
+        let spawned = world.get_entity(child_entity).is_none();
+        let Some(mut child_mut) = world.get_or_spawn(child_entity) else {
+            on_missing(world, WithChildError::ChildGenerationMismatch(child_entity));
+            return;
+        };
+
+        child_mut.insert(bundle);
+        Link::link_child(world, self.parent_entity, child_entity);
+        if spawned {
+            crate::record_children_spawned(world, 1);
+        }
+    }
+}
+
+/// A buffer of child entities, used when collecting children before linking them to their parent.
+///
+/// This is used by [`EntityWorldMutExt::with_children_now`], [`SpawnChildrenRoundRobin`](crate::SpawnChildrenRoundRobin),
+/// the reconciling components and [`despawn_children`](crate::despawn_children).
+/// [`WithChildren`] doesn't need it, as it links each child as soon as it's spawned.
+/// With the `smallvec` feature enabled, small sets of children are stored inline, avoiding a heap allocation.
+#[cfg(feature = "smallvec")]
+pub(crate) type ChildBuffer = smallvec::SmallVec<[Entity; 4]>;
+
+/// A buffer of child entities, used when collecting children before linking them to their parent.
+///
+/// This is used by [`EntityWorldMutExt::with_children_now`], [`SpawnChildrenRoundRobin`](crate::SpawnChildrenRoundRobin),
+/// the reconciling components and [`despawn_children`](crate::despawn_children).
+/// [`WithChildren`] doesn't need it, as it links each child as soon as it's spawned.
+/// With the `smallvec` feature enabled, small sets of children are stored inline, avoiding a heap allocation.
+#[cfg(not(feature = "smallvec"))]
+pub(crate) type ChildBuffer = Vec<Entity>;
+
+/// An extension trait for [`EntityWorldMut`], which spawns children immediately.
+///
+/// Unlike [`WithChild`] and [`WithChildren`], which rely on deferred commands,
+/// these methods synchronously spawn and link the children.
+/// This is useful when working with `&mut World` directly, such as in editor tooling or tests,
+/// where immediate structural changes are fine and no [`World::flush`] is needed.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::EntityWorldMutExt;
+///
+/// #[derive(Component)]
+/// struct A;
+///
+/// let mut world = World::new();
+/// let parent = world.spawn_empty().with_child_now(A).id();
+///
+/// assert_eq!(world.get::<Children>(parent).unwrap().len(), 1);
+/// ```
+pub trait EntityWorldMutExt {
+    /// Spawns a child entity with the given bundle, and immediately adds it as a child of this entity.
+    fn with_child_now<B: Bundle>(&mut self, bundle: B) -> &mut Self;
+
+    /// Spawns a child entity for each bundle in `bundles`, and immediately adds them as children of this entity.
+    fn with_children_now<B: Bundle>(&mut self, bundles: impl IntoIterator<Item = B>) -> &mut Self;
+}
+
+impl EntityWorldMutExt for EntityWorldMut<'_> {
+    fn with_child_now<B: Bundle>(&mut self, bundle: B) -> &mut Self {
+        let parent_entity = self.id();
+        self.world_scope(|world| {
+            let child_entity = Link::spawn_child(world, bundle).id();
+            Link::link_child(world, parent_entity, child_entity);
+        });
+        self
+    }
+
+    fn with_children_now<B: Bundle>(&mut self, bundles: impl IntoIterator<Item = B>) -> &mut Self {
+        let parent_entity = self.id();
+        self.world_scope(|world| {
+            let child_entities: ChildBuffer = bundles
+                .into_iter()
+                .map(|bundle| Link::spawn_child(world, bundle).id())
+                .collect();
+            Link::link_children(world, parent_entity, &child_entities);
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::bevy_ecs::event::Events;
+    use crate::deps::bevy_ecs::system::RunSystemOnce;
+    use crate::deps::bevy_hierarchy::{
+        BuildWorldChildren, Children, DespawnRecursiveExt, HierarchyEvent, Parent,
+    };
+
+    use super::*;
+    use crate::{
+        assert_hierarchy, resolve_deferred, FailurePolicy, ManualResolution, BATCH_THRESHOLD,
+    };
+
+    #[derive(Component, PartialEq, Debug, Clone)]
+    struct A;
+
+    #[derive(Component, PartialEq, Eq, Hash, Debug, Clone)]
+    struct B(u8);
+
+    #[derive(Bundle)]
+    struct ABBundle {
+        a: A,
+        b: B,
+    }
+
+    #[derive(Bundle)]
+    struct HierarchicalBundle {
+        a: A,
+        child: WithChild<ABBundle>,
+    }
+
+    #[test]
+    fn with_child() {
+        let mut world = World::default();
+
+        let parent = world.spawn(WithChild((A, B(3)))).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world.entity(parent).contains::<WithChild<(A, B)>>());
+        assert!(!world.entity(parent).contains::<A>());
+        assert!(!world.entity(parent).contains::<B>());
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 1);
+
+        let child_entity = children[0];
+        assert_eq!(world.get::<A>(child_entity), Some(&A));
+        assert_eq!(world.get::<B>(child_entity), Some(&B(3)));
+    }
+
+    #[test]
+    fn with_child_stray_parent() {
+        use crate::deps::bevy_ecs::{
+            entity::{EntityMapper, MapEntities},
+            event::Events,
+            world::FromWorld,
+        };
+        use crate::deps::bevy_hierarchy::HierarchyEvent;
+
+        use crate::test_utils::capture_logs;
+
+        /// Maps every entity to the given one.
+        struct MapTo(Entity);
+
+        impl EntityMapper for MapTo {
+            fn map_entity(&mut self, _entity: Entity) -> Entity {
+                self.0
+            }
+        }
+
+        let logs = capture_logs(|| {
+            let mut world = World::default();
+            world.init_resource::<Events<HierarchyEvent>>();
+            let other = world.spawn(A).id();
+            let parent = world.spawn_empty().id();
+
+            // A `Parent` pointing to the parent itself, which doesn't list the child yet
+            let mut stray_to_parent = Parent::from_world(&mut world);
+            stray_to_parent.map_entities(&mut MapTo(parent));
+            // A `Parent` pointing to an unrelated entity
+            let mut stray_to_other = Parent::from_world(&mut world);
+            stray_to_other.map_entities(&mut MapTo(other));
+
+            world.entity_mut(parent).insert((
+                WithChild((A, stray_to_parent)),
+                WithChildSlot(0, (B(1), stray_to_other)),
+            ));
+            // FIXME: this should not be needed!
+            world.flush();
+
+            let children = world.get::<Children>(parent).unwrap().to_vec();
+            assert_eq!(children.len(), 2);
+            for &child in &children {
+                assert_eq!(world.get::<Parent>(child).map(Parent::get), Some(parent));
+            }
+            assert!(world.get::<Children>(other).is_none());
+
+            // Both children are reported as newly added, rather than moved
+            let events: Vec<_> = world
+                .resource_mut::<Events<HierarchyEvent>>()
+                .drain()
+                .collect();
+            assert_eq!(events.len(), 2);
+            assert!(events
+                .iter()
+                .all(|event| matches!(event, HierarchyEvent::ChildAdded { .. })));
+        });
+        assert_eq!(logs.matches("The stray `Parent` was removed").count(), 2);
+    }
+
+    #[test]
+    fn with_child_in_secondary_world() {
+        let main_world = World::default();
+        let mut scratch_world = World::default();
+
+        let parent = scratch_world.spawn(WithChild(B(3))).id();
+        scratch_world.commands().spawn(WithChild(B(4)));
+        // FIXME: this should not be needed!
+        scratch_world.flush();
+
+        let children = scratch_world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(scratch_world.get::<B>(children[0]), Some(&B(3)));
+        assert_eq!(scratch_world.query::<&B>().iter(&scratch_world).count(), 2);
+        assert_eq!(
+            scratch_world
+                .query::<&Parent>()
+                .iter(&scratch_world)
+                .count(),
+            2
+        );
+
+        // Nothing leaks into the main world
+        assert_eq!(main_world.entities().len(), 0);
+    }
+
+    #[test]
+    fn removal_cancels_pending_spawn() {
+        let mut world = World::default();
+        // Cancellation is not a failure, even under the strictest policy
+        world.insert_resource(FailurePolicy::Panic);
+
+        let parent = world
+            .spawn((WithChild(A), WithChildren(vec![B(1), B(2)])))
+            .id();
+        world
+            .entity_mut(parent)
+            .remove::<(WithChild<A>, WithChildren<B, Vec<B>>)>();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(world.get::<Children>(parent).is_none());
+        assert_eq!(world.entities().len(), 1);
+    }
+
+    #[test]
+    fn with_child_observing() {
+        #[derive(Event)]
+        struct Ping;
+
+        #[derive(Resource, Default)]
+        struct Pinged(Vec<Entity>);
+
+        let mut world = World::default();
+        world.init_resource::<Pinged>();
+
+        let parent = world
+            .spawn((
+                A,
+                WithChildObserving::new(
+                    B(1),
+                    |trigger: Trigger<Ping>, mut pinged: ResMut<Pinged>| {
+                        pinged.0.push(trigger.entity());
+                    },
+                ),
+            ))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let child = world.get::<Children>(parent).unwrap()[0];
+        assert_eq!(world.get::<B>(child), Some(&B(1)));
+
+        // Only the spawned child is observed
+        world.trigger_targets(Ping, parent);
+        world.trigger_targets(Ping, child);
+        world.flush();
+        assert_eq!(world.resource::<Pinged>().0, vec![child]);
+    }
+
+    #[test]
+    fn spawns_children_marker() {
+        let mut world = World::default();
+
+        let single = world.spawn(WithChild(A)).id();
+        let many = world.spawn(WithChildren(vec![A, A])).id();
+        let unrelated = world.spawn(A).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(world.entity(single).contains::<SpawnsChildrenMarker>());
+        assert!(world.entity(many).contains::<SpawnsChildrenMarker>());
+        assert!(!world.entity(unrelated).contains::<SpawnsChildrenMarker>());
+        // Children are not marked unless they spawn children of their own
+        let child = world.get::<Children>(single).unwrap()[0];
+        assert!(!world.entity(child).contains::<SpawnsChildrenMarker>());
+    }
+
+    #[test]
+    fn with_child_equality() {
+        use core::hash::BuildHasher;
+
+        let a = WithChild(B(1));
+        let b = WithChild(B(1));
+        assert_eq!(a, b);
+        assert_ne!(a, WithChild(B(2)));
+
+        let hasher = std::collections::hash_map::RandomState::new();
+        assert_eq!(hasher.hash_one(&a), hasher.hash_one(&b));
+    }
+
+    #[test]
+    fn with_child_at() {
+        let mut world = World::default();
+
+        let parent = world.spawn(WithChildren(vec![B(1), B(2)])).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        world.entity_mut(parent).insert(WithChildAt(0, B(0)));
+        world.flush();
+        // Out of bounds indexes are clamped to the end
+        world.entity_mut(parent).insert(WithChildAt(10, B(3)));
+        world.flush();
+
+        let values: Vec<u8> = world
+            .get::<Children>(parent)
+            .unwrap()
+            .iter()
+            .map(|&child| world.get::<B>(child).unwrap().0)
+            .collect();
+        assert_eq!(values, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn with_child_at_reads_current_children() {
+        let mut world = World::default();
+
+        let parent = world.spawn(WithChildren(vec![B(1), B(2)])).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        world.init_resource::<ManualResolution>();
+        world.entity_mut(parent).insert(WithChildAt(1, B(9)));
+        world.flush();
+
+        // A child is added at the front after the hook ran, but before the command is applied
+        let late_child = world.spawn(B(0)).id();
+        world.entity_mut(parent).insert_children(0, &[late_child]);
+        resolve_deferred(&mut world);
+
+        let values: Vec<u8> = world
+            .get::<Children>(parent)
+            .unwrap()
+            .iter()
+            .map(|&child| world.get::<B>(child).unwrap().0)
+            .collect();
+        assert_eq!(values, vec![0, 9, 1, 2]);
+    }
+
+    #[test]
+    fn with_child_commands() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(WithChildCommands(|commands: &mut Commands| {
+                commands.spawn(B(9));
+                (A, B(1))
+            }))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(world.get::<B>(children[0]), Some(&B(1)));
+
+        // The side-effect entity is not a child
+        let mut query = world.query_filtered::<&B, Without<Parent>>();
+        assert_eq!(query.iter(&world).collect::<Vec<_>>(), vec![&B(9)]);
+    }
+
+    #[test]
+    fn with_child_after_parent_ready() {
+        /// A component whose hook inserts `A` on its entity via commands.
+        struct InsertsA;
+
+        impl Component for InsertsA {
+            const STORAGE_TYPE: StorageType = StorageType::Table;
+
+            fn register_component_hooks(hooks: &mut ComponentHooks) {
+                hooks.on_add(|mut world, entity, _| {
+                    world.commands().entity(entity).insert(A);
+                });
+            }
+        }
+
+        let mut world = World::default();
+
+        // The child's component is listed first, so a plain `on_add` hook would run first
+        let parent = world
+            .spawn((
+                after_parent_ready(|parent: EntityRef| B(parent.contains::<A>() as u8)),
+                InsertsA,
+            ))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(world.get::<B>(children[0]), Some(&B(1)));
+    }
+
+    #[test]
+    fn with_children_vec() {
+        let mut world = World::default();
+
+        let parent = world.spawn(WithChildren(vec![B(0), B(1), B(2)])).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world.entity(parent).contains::<WithChildren<B, Vec<B>>>());
+        assert!(!world.entity(parent).contains::<B>());
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 3);
+
+        for (i, child_entity) in children.iter().enumerate() {
+            assert_eq!(world.get::<B>(*child_entity), Some(&B(i as u8)));
+        }
+    }
+
+    #[test]
+    fn with_child_closure() {
+        let mut world = World::default();
+
+        let parent = world.spawn(WithChildren((0..7).map(|i| B(i as u8)))).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world.entity(parent).contains::<WithChildren<B, Vec<B>>>());
+        assert!(!world.entity(parent).contains::<B>());
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 7);
+
+        for (i, child_entity) in children.iter().enumerate() {
+            assert_eq!(world.get::<B>(*child_entity), Some(&B(i as u8)));
+        }
+    }
+
+    #[test]
+    fn indexed_children() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(spawn_indexed_children((0..5).map(|i| B(i * 2))))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 5);
+
+        for (i, child_entity) in children.iter().enumerate() {
+            assert_eq!(world.get::<ChildIndex>(*child_entity), Some(&ChildIndex(i)));
+            assert_eq!(world.get::<B>(*child_entity), Some(&B(i as u8 * 2)));
+        }
+    }
+
+    #[test]
+    fn with_children_inaccurate_size_hint() {
+        /// An iterator over `0..len`, which claims to have `hint` items.
+        #[derive(Clone)]
+        struct Misleading {
+            next: u8,
+            len: u8,
+            hint: usize,
+        }
+
+        impl Iterator for Misleading {
+            type Item = B;
+
+            fn next(&mut self) -> Option<B> {
+                (self.next < self.len).then(|| {
+                    self.next += 1;
+                    B(self.next - 1)
+                })
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.hint, Some(self.hint))
+            }
+        }
+
+        let mut world = World::default();
+        world.init_resource::<Events<HierarchyEvent>>();
+
+        // Both hints are large enough for the children to be spawned as a batch, reserving storage for `hint` children
+        let overestimated = world
+            .spawn(WithChildren(Misleading {
+                next: 0,
+                len: 2,
+                hint: 1000,
+            }))
+            .id();
+        let underestimated = world
+            .spawn(WithChildren(Misleading {
+                next: 0,
+                len: BATCH_THRESHOLD as u8 + 3,
+                hint: BATCH_THRESHOLD,
+            }))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_hierarchy!(world, overestimated, [] { [B = B(0)], [B = B(1)] });
+        let children = world.get::<Children>(underestimated).unwrap();
+        assert_eq!(children.len(), BATCH_THRESHOLD + 3);
+        for (i, &child) in children.iter().enumerate() {
+            assert_eq!(world.get::<B>(child), Some(&B(i as u8)));
+            assert_eq!(world.get::<Parent>(child).unwrap().get(), underestimated);
+        }
+        assert_eq!(world.entities().len() as usize, 2 + 2 + BATCH_THRESHOLD + 3);
+
+        // Every child is reported as added, as it would be when spawned one at a time
+        let events = world.resource::<Events<HierarchyEvent>>();
+        let added = events
+            .iter_current_update_events()
+            .filter(|event| matches!(event, HierarchyEvent::ChildAdded { .. }))
+            .count();
+        assert_eq!(added, 2 + BATCH_THRESHOLD + 3);
+    }
+
+    #[test]
+    fn with_children_collect() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn((0..3).map(B).collect::<WithChildren<_, Vec<_>>>())
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let values: Vec<u8> = world
+            .get::<Children>(parent)
+            .unwrap()
+            .iter()
+            .map(|&child| world.get::<B>(child).unwrap().0)
+            .collect();
+        assert_eq!(values, [0, 1, 2]);
+    }
+
+    #[test]
+    fn spawns_children_is_uniform() {
+        /// Respawns the children of any spawner as a single `WithChildren`, doubling each value.
+        fn doubled(spawner: impl SpawnsChildren<Bundle = B>) -> WithChildren<B, Vec<B>> {
+            spawner.into_child_bundles().map(|b| B(b.0 * 2)).collect()
+        }
+
+        let mut world = World::default();
+
+        let single = world.spawn(doubled(WithChild(B(1)))).id();
+        let many = world.spawn(doubled(WithChildren((2..4).map(B)))).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_hierarchy!(world, single, [] { [B = B(2)] });
+        assert_hierarchy!(world, many, [] { [B = B(4)], [B = B(6)] });
+    }
+
+    #[test]
+    fn with_children_adjusted_before_resolution() {
+        let mut world = World::default();
+        world.init_resource::<ManualResolution>();
+
+        let parent = world.spawn(WithChildren(vec![B(1), B(2)])).id();
+        world.run_system_once(|mut query: Query<&mut WithChildren<B, Vec<B>>>| {
+            for mut with_children in &mut query {
+                with_children.get_mut().retain(|b| b.0 != 1);
+                with_children.get_mut().push(B(3));
+            }
+        });
+        assert_eq!(
+            world
+                .get::<WithChildren<B, Vec<B>>>(parent)
+                .unwrap()
+                .get()
+                .len(),
+            2
+        );
+
+        resolve_deferred(&mut world);
+        assert_hierarchy!(world, parent, [] { [B = B(2)], [B = B(3)] });
+    }
+
+    #[test]
+    fn with_children_empty() {
+        let mut world = World::default();
+
+        let parent = world.spawn(WithChildren::<A, _>::empty()).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world.entity(parent).contains::<WithChildren<A, Empty<A>>>());
+        assert!(world.get::<Children>(parent).is_none());
+    }
+
+    #[test]
+    fn with_children_indexed() {
+        let mut world = World::default();
+
+        let f = |i: usize| B(i as u8 * 3);
+        let parent = world.spawn(spawn_children_with_index(5, f)).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 5);
+        for (i, &child) in children.iter().enumerate() {
+            assert_eq!(world.get::<B>(child), Some(&f(i)));
+        }
+    }
+
+    #[test]
+    fn with_children_filtered() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(spawn_children_filtered((0..6).map(B), |b| b.0 % 2 == 0))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let children = world.get::<Children>(parent).unwrap();
+        let values: Vec<u8> = children
+            .iter()
+            .map(|&child| world.get::<B>(child).unwrap().0)
+            .collect();
+        assert_eq!(values, [0, 2, 4]);
+
+        let reject_all: fn(&B) -> bool = |_| false;
+        let empty_parent = world
+            .spawn(spawn_children_filtered(
+                (0..6).map(B).collect::<Vec<_>>(),
+                reject_all,
+            ))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world
+            .entity(empty_parent)
+            .contains::<WithChildren<B, Filter<std::vec::IntoIter<B>, fn(&B) -> bool>>>());
+        assert!(world.get::<Children>(empty_parent).is_none());
+    }
+
+    #[test]
+    fn with_children_dedup_by() {
+        let mut world = World::default();
+
+        let items = vec![B(1), B(2), B(11), B(3), B(12), B(4)];
+        let parent = world
+            .spawn(spawn_children_dedup_by(items, |b| b.0 % 10))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let values: Vec<u8> = world
+            .get::<Children>(parent)
+            .unwrap()
+            .iter()
+            .map(|&child| world.get::<B>(child).unwrap().0)
+            .collect();
+        // The first occurrence of each key is kept, in order
+        assert_eq!(values, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn with_children_shared() {
+        #[derive(Component)]
+        struct Shared(Arc<Vec<u8>>);
+
+        let mut world = World::default();
+        let data = Arc::new(vec![0; 1024]);
+
+        let parent = world
+            .spawn(spawn_children_shared(data.clone(), 0..3, |data, _| {
+                Shared(Arc::clone(data))
+            }))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 3);
+        for &child in children {
+            assert!(Arc::ptr_eq(&world.get::<Shared>(child).unwrap().0, &data));
+        }
+    }
+
+    #[test]
+    fn with_children_range() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(spawn_children_range(3, 20, 5, |i| B(i as u8)))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let children = world.get::<Children>(parent).unwrap();
+        let values: Vec<u8> = children
+            .iter()
+            .map(|&child| world.get::<B>(child).unwrap().0)
+            .collect();
+        assert_eq!(values, [3, 8, 13, 18]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "step greater than zero")]
+    fn with_children_range_zero_step() {
+        spawn_children_range(0, 10, 0, |i| B(i as u8));
+    }
+
+    #[test]
+    fn with_children_zip() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(spawn_children_zip(
+                vec![1, 2, 3, 4, 5],
+                vec![10, 20, 30],
+                |a, b| B(a + b),
+            ))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 3);
+        for (i, child_entity) in children.iter().enumerate() {
+            let i = i as u8 + 1;
+            assert_eq!(world.get::<B>(*child_entity), Some(&B(i + i * 10)));
+        }
+    }
+
+    #[test]
+    fn with_children_grouped() {
+        let mut world = World::default();
+
+        let parent_1 = world.spawn(spawn_children_grouped(vec![B(0), B(1)])).id();
+        let parent_2 = world.spawn(spawn_children_grouped(vec![B(2), B(3)])).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let group_1 = *world.get::<SpawnGroup>(parent_1).unwrap();
+        let group_2 = *world.get::<SpawnGroup>(parent_2).unwrap();
+        assert_ne!(group_1, group_2);
+
+        for (parent, group) in [(parent_1, group_1), (parent_2, group_2)] {
+            let children = world.get::<Children>(parent).unwrap();
+            assert_eq!(children.len(), 2);
+            for child_entity in children.iter() {
+                assert_eq!(world.get::<SpawnGroup>(*child_entity), Some(&group));
+            }
+        }
+
+        let mut query = world.query::<(&SpawnGroup, &B)>();
+        let group_1_children = query
+            .iter(&world)
+            .filter(|(group, _)| **group == group_1)
+            .count();
+        assert_eq!(group_1_children, 2);
+    }
+
+    #[test]
+    fn with_children_capped() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(spawn_children_capped(std::iter::repeat_with(|| B(7)), 10))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(world.get::<Children>(parent).unwrap().len(), 10);
+        assert_eq!(
+            world.get::<DroppedChildren>(parent),
+            Some(&DroppedChildren {
+                at_least: usize::MAX
+            })
+        );
+
+        // Iterators that fit under the cap don't report anything
+        let parent = world
+            .spawn(spawn_children_capped(vec![B(0), B(1)], 10))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(world.get::<Children>(parent).unwrap().len(), 2);
+        assert_eq!(world.get::<DroppedChildren>(parent), None);
+    }
+
+    #[test]
+    fn with_children_capped_lower_bound() {
+        let mut world = World::default();
+
+        // Filtering discards the iterator's lower bound, so only the overflowing item is known to be dropped
+        let parent = world
+            .spawn(spawn_children_capped((0..10).map(B).filter(|_| true), 3))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(world.get::<Children>(parent).unwrap().len(), 3);
+        assert_eq!(
+            world.get::<DroppedChildren>(parent),
+            Some(&DroppedChildren { at_least: 1 })
+        );
+    }
+
+    #[test]
+    fn with_children_partitioned() {
+        let mut world = World::default();
+
+        let odd_parent = world.spawn_empty().id();
+        let even_parent = world
+            .spawn(spawn_children_partitioned(
+                (0..6).map(B),
+                |b: &B| b.0.is_multiple_of(2),
+                odd_parent,
+            ))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let children_values = |parent: Entity| -> Vec<u8> {
+            world
+                .get::<Children>(parent)
+                .unwrap()
+                .iter()
+                .map(|&child| world.get::<B>(child).unwrap().0)
+                .collect()
+        };
+        assert_eq!(children_values(even_parent), vec![0, 2, 4]);
+        assert_eq!(children_values(odd_parent), vec![1, 3, 5]);
+    }
+
+    #[test]
+    fn with_children_interspersed() {
+        #[derive(Component, PartialEq, Debug)]
+        struct Divider;
+
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(spawn_children_interspersed(vec![B(0), B(1), B(2)], || {
+                Divider
+            }))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 5);
+        for (i, &child) in children.iter().enumerate() {
+            let is_separator = i % 2 == 1;
+            assert_eq!(world.entity(child).contains::<Separator>(), is_separator);
+            assert_eq!(world.entity(child).contains::<Divider>(), is_separator);
+            if !is_separator {
+                assert_eq!(world.get::<B>(child), Some(&B(i as u8 / 2)));
+            }
+        }
+
+        let single = world
+            .spawn(spawn_children_interspersed(vec![B(0)], || Divider))
+            .id();
+        world.flush();
+        assert_eq!(world.get::<Children>(single).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn with_children_recorded() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn((WithChild(A), spawn_children_recorded(vec![B(0), B(1)])))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+        world.entity_mut(parent).insert(WithChildren(vec![B(2)]));
+        world.flush();
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 4);
+        let recorded = world.get::<SpawnedChildren<B>>(parent).unwrap();
+        assert_eq!(recorded.entities(), &children[1..3]);
+        let values: Vec<u8> = recorded
+            .entities()
+            .iter()
+            .map(|&child| world.get::<B>(child).unwrap().0)
+            .collect();
+        assert_eq!(values, [0, 1]);
+
+        // A new record replaces the previous one
+        world
+            .entity_mut(parent)
+            .insert(spawn_children_recorded(vec![B(3)]));
+        world.flush();
+        let children = world.get::<Children>(parent).unwrap();
+        let recorded = world.get::<SpawnedChildren<B>>(parent).unwrap();
+        assert_eq!(recorded.entities(), &children[4..]);
+    }
+
+    #[test]
+    fn with_children_projected() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn((
+                B(10),
+                spawn_children_projected(0..3, |parent: &B, i| B(parent.0 + i)),
+            ))
+            .id();
+        // The parent's value is read when the command is applied
+        world.entity_mut(parent).insert(B(20));
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_hierarchy!(world, parent, [B = B(20)] {
+            [B = B(20)],
+            [B = B(21)],
+            [B = B(22)],
+        });
+    }
+
+    #[test]
+    fn with_children_projected_missing_component() {
+        let mut world = World::default();
+        world.insert_resource(FailurePolicy::Ignore);
+
+        let parent = world
+            .spawn(spawn_children_projected(0..3, |parent: &B, i| {
+                B(parent.0 + i)
+            }))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(world.get::<Children>(parent).is_none());
+        let mut query = world.query::<&B>();
+        assert_eq!(query.iter(&world).count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Required component not found")]
+    fn with_children_projected_missing_component_panics() {
+        let mut world = World::default();
+        world.insert_resource(FailurePolicy::Panic);
+
+        world.spawn(spawn_children_projected(0..3, |parent: &B, i| {
+            B(parent.0 + i)
+        }));
+        // FIXME: this should not be needed!
+        world.flush();
+    }
+
+    #[test]
+    fn with_parent() {
+        let mut world = World::default();
+
+        let child = world.spawn((B(1), spawn_parent(A))).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world.entity(child).contains::<WithParent<A>>());
+        let parent = world.get::<Parent>(child).unwrap().get();
+        assert_eq!(world.get::<A>(parent), Some(&A));
+        assert_eq!(world.get::<Parent>(parent), None);
+        assert_eq!(&**world.get::<Children>(parent).unwrap(), &[child]);
+    }
+
+    #[test]
+    fn with_parent_wraps_existing_parent() {
+        let mut world = World::default();
+
+        let root = world
+            .spawn_empty()
+            .with_children_now([B(0), B(1), B(2)])
+            .id();
+        let middle_child = world.get::<Children>(root).unwrap()[1];
+
+        world.entity_mut(middle_child).insert(spawn_parent(A));
+        // FIXME: this should not be needed!
+        world.flush();
+
+        // The new parent takes the original child's place
+        let children = world.get::<Children>(root).unwrap();
+        assert_eq!(children.len(), 3);
+        let wrapper = children[1];
+        assert_eq!(world.get::<A>(wrapper), Some(&A));
+        assert_eq!(world.get::<Parent>(wrapper).unwrap().get(), root);
+
+        // And the original child is re-parented under it
+        assert_eq!(world.get::<Parent>(middle_child).unwrap().get(), wrapper);
+        assert_eq!(&**world.get::<Children>(wrapper).unwrap(), &[middle_child]);
+    }
+
+    #[test]
+    fn with_child_now() {
+        let mut world = World::default();
+
+        let parent = world.spawn(A).with_child_now(B(1)).id();
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(world.get::<B>(children[0]), Some(&B(1)));
+    }
+
+    #[test]
+    fn with_children_now() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(A)
+            .with_children_now((0..3).map(B))
+            .with_child_now(B(3))
+            .id();
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 4);
+        for (i, child_entity) in children.iter().enumerate() {
+            assert_eq!(world.get::<B>(*child_entity), Some(&B(i as u8)));
+        }
+    }
+
+    #[test]
+    fn with_distinct_children() {
+        let mut world = World::default();
 
         let parent = world.spawn((WithChild(A), WithChild(B(1)))).id();
         // FIXME: this should not be needed!
@@ -306,6 +3946,219 @@ mod tests {
         assert_eq!(world.get::<A>(children[1]), Some(&A));
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    fn overwritten_slot_warns() {
+        use crate::test_utils::capture_logs;
+
+        let logs = capture_logs(|| {
+            let mut world = World::default();
+            let mut parent = world.spawn(WithChildSlot(0, B(0)));
+            parent.insert(WithChildSlot(1, B(1)));
+            let parent = parent.id();
+            // FIXME: this should not be needed!
+            world.flush();
+
+            let children = world.get::<Children>(parent).unwrap();
+            assert_eq!(children.len(), 1);
+            assert_eq!(world.get::<B>(children[0]), Some(&B(1)));
+        });
+        assert!(logs.contains("in slot 1"));
+        assert!(logs.contains("the replaced child will never be spawned"));
+
+        let logs = capture_logs(|| {
+            let mut world = World::default();
+            // Distinct component types, and components that were already resolved, don't collide
+            let parent = world.spawn((WithChildSlot(0, B(0)), WithChild(B(1)))).id();
+            world.flush();
+            world.entity_mut(parent).insert(WithChildSlot(1, B(2)));
+            world.flush();
+        });
+        assert!(logs.is_empty());
+    }
+
+    #[test]
+    fn try_add_child_slot_fills_lowest_free_slot() {
+        let mut world = World::default();
+
+        let mut parent = world.spawn_empty();
+        assert_eq!(try_add_child_slot(&mut parent, B(0)), Some(0));
+        assert_eq!(try_add_child_slot(&mut parent, B(1)), Some(1));
+        assert_eq!(try_add_child_slot(&mut parent, B(2)), Some(2));
+        let parent = parent.id();
+
+        assert_hierarchy!(world, parent, [] {
+            [B = B(0), ChildSlot = ChildSlot(0)],
+            [B = B(1), ChildSlot = ChildSlot(1)],
+            [B = B(2), ChildSlot = ChildSlot(2)],
+        });
+    }
+
+    #[test]
+    fn try_add_child_slot_skips_taken_slots() {
+        let mut world = World::default();
+
+        let parent = world.spawn(WithChildSlot(0, A)).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let mut parent_mut = world.entity_mut(parent);
+        // Pending slots are taken too
+        parent_mut.insert(WithChildSlot(1, B(1)));
+        assert_eq!(try_add_child_slot(&mut parent_mut, B(2)), Some(2));
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_hierarchy!(world, parent, [] {
+            [A, ChildSlot = ChildSlot(0)],
+            [B = B(1), ChildSlot = ChildSlot(1)],
+            [B = B(2), ChildSlot = ChildSlot(2)],
+        });
+    }
+
+    #[test]
+    fn try_add_child_slot_when_full() {
+        let mut world = World::default();
+
+        let mut parent = world.spawn_empty();
+        for slot in 0..=u8::MAX {
+            assert_eq!(try_add_child_slot(&mut parent, A), Some(slot));
+        }
+        assert_eq!(try_add_child_slot(&mut parent, A), None);
+        assert_eq!(parent.get::<Children>().unwrap().len(), 256);
+    }
+
+    #[test]
+    fn with_child_slots() {
+        let mut world = World::default();
+
+        #[derive(Component, PartialEq, Debug)]
+        struct C;
+
+        // Slots are resolved in the order 2, 0, 1
+        let parent = world
+            .spawn((
+                WithChildSlot(2, C),
+                WithChildSlot(0, A),
+                WithChildSlot(1, B(1)),
+            ))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 3);
+        assert_eq!(world.get::<A>(children[0]), Some(&A));
+        assert_eq!(world.get::<B>(children[1]), Some(&B(1)));
+        assert_eq!(world.get::<C>(children[2]), Some(&C));
+        for (i, &child) in children.iter().enumerate() {
+            assert_eq!(world.get::<ChildSlot>(child), Some(&ChildSlot(i as u8)));
+        }
+    }
+
+    #[test]
+    fn slotted_children() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(spawn_slotted_children::<_, _, 4>(vec![B(0), B(1), B(2)]))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world
+            .entity(parent)
+            .contains::<WithSlottedChildren<B, Vec<B>, 4>>());
+        assert!(world.get::<DroppedChildren>(parent).is_none());
+
+        let slots = world.get::<ChildSlots>(parent).unwrap();
+        assert_eq!(slots.0.len(), 4);
+        assert_eq!(slots.get(3), None);
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 3);
+        for (i, &child) in children.iter().enumerate() {
+            assert_eq!(slots.get(i as u8), Some(child));
+            assert_eq!(world.get::<ChildSlot>(child), Some(&ChildSlot(i as u8)));
+            assert_eq!(world.get::<B>(child), Some(&B(i as u8)));
+        }
+
+        let overflowing = world
+            .spawn(spawn_slotted_children::<_, _, 2>([A, A, A]))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(world.get::<Children>(overflowing).unwrap().len(), 2);
+        assert_eq!(
+            world.get::<DroppedChildren>(overflowing),
+            Some(&DroppedChildren { at_least: 1 })
+        );
+    }
+
+    #[test]
+    fn slotted_children_debug() {
+        let two_slots = spawn_slotted_children::<B, _, 2>(vec![B(0), B(1)]);
+        assert_eq!(
+            format!("{two_slots:?}"),
+            "WithSlottedChildren<2>([B(0), B(1)])"
+        );
+
+        let eight_slots = spawn_slotted_children::<B, _, 8>(vec![B(3)]);
+        assert_eq!(format!("{eight_slots:?}"), "WithSlottedChildren<8>([B(3)])");
+    }
+
+    #[test]
+    fn missing_parent_policy() {
+        let mut world = World::default();
+        let parent = world.spawn_empty().id();
+        world.despawn(parent);
+
+        world.insert_resource(FailurePolicy::Ignore);
+        WithChildCommand::<A>::new(parent).apply(&mut world);
+        assert_eq!(world.entities().len(), 0);
+
+        world.insert_resource(FailurePolicy::Panic);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            WithChildCommand::<A>::new(parent).apply(&mut world);
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn with_child_inherit() {
+        #[derive(Component, Clone, PartialEq, Debug)]
+        struct Scale(u8);
+
+        let mut world = World::default();
+
+        let parent = world
+            .spawn((Scale(2), WithChildInherit::<_, Scale>::new(A)))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world
+            .entity(parent)
+            .contains::<WithChildInherit<A, Scale>>());
+        assert_eq!(world.get::<Scale>(parent), Some(&Scale(2)));
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(world.get::<A>(children[0]), Some(&A));
+        assert_eq!(world.get::<Scale>(children[0]), Some(&Scale(2)));
+
+        // Parent without the inherited component
+        let parent = world.spawn(WithChildInherit::<_, Scale>::new(A)).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(world.get::<A>(children[0]), Some(&A));
+        assert_eq!(world.get::<Scale>(children[0]), None);
+    }
+
     #[test]
     fn grandchildren() {
         let mut world = World::default();
@@ -314,18 +4167,91 @@ mod tests {
         // FIXME: this should not be needed!
         world.flush();
 
+        assert_hierarchy!(world, parent, [] {
+            [A = A] { [A = A, B = B(3)] },
+        });
+    }
+
+    #[test]
+    fn nested_repeated_children() {
+        let mut world = World::default();
+
+        // Each child of the same type carries its own set of same-typed grandchildren
+        let parent = world
+            .spawn(WithChildren((0..2).map(|i| {
+                (B(i), WithChildren(vec![(A, B(10 * i)), (A, B(10 * i + 1))]))
+            })))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
         let children = world.get::<Children>(parent).unwrap();
-        assert_eq!(children.len(), 1);
+        assert_eq!(children.len(), 2);
 
-        let child_entity = children[0];
-        assert_eq!(world.get::<A>(child_entity), Some(&A));
+        let mut grandchild_values = Vec::new();
+        for (i, &child) in children.iter().enumerate() {
+            assert_eq!(world.get::<B>(child), Some(&B(i as u8)));
+
+            let grandchildren = world.get::<Children>(child).unwrap();
+            assert_eq!(grandchildren.len(), 2);
+            for &grandchild in grandchildren {
+                assert_eq!(world.get::<A>(grandchild), Some(&A));
+                grandchild_values.push(world.get::<B>(grandchild).unwrap().0);
+            }
+        }
+        assert_eq!(grandchild_values, vec![0, 1, 10, 11]);
+    }
+
+    #[test]
+    fn with_children_grandchildren() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(WithChildren((0..3).map(|i| (B(i), WithChild(A)))))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
 
-        let grandchildren = world.get::<Children>(child_entity).unwrap();
-        assert_eq!(grandchildren.len(), 1);
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 3);
+        for (i, &child) in children.iter().enumerate() {
+            assert_eq!(world.get::<B>(child), Some(&B(i as u8)));
+            assert_eq!(world.get::<Parent>(child).unwrap().get(), parent);
+
+            let grandchildren = world.get::<Children>(child).unwrap();
+            assert_eq!(grandchildren.len(), 1);
+            assert_eq!(world.get::<A>(grandchildren[0]), Some(&A));
+        }
+    }
+
+    #[test]
+    fn cloned_bundle() {
+        #[derive(Bundle, Clone)]
+        struct ClonableBundle {
+            a: A,
+            child: WithChild<(A, ChildIndex)>,
+        }
 
-        let grandchild_entity = grandchildren[0];
-        assert_eq!(world.get::<A>(grandchild_entity), Some(&A));
-        assert_eq!(world.get::<B>(grandchild_entity), Some(&B(3)));
+        let mut world = World::default();
+
+        let bundle = ClonableBundle {
+            a: A,
+            child: WithChild((A, ChildIndex(3))),
+        };
+        let parent_1 = world.spawn(bundle.clone()).id();
+        let parent_2 = world.spawn(bundle).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let children_1 = world.get::<Children>(parent_1).unwrap();
+        let children_2 = world.get::<Children>(parent_2).unwrap();
+        assert_eq!(children_1.len(), 1);
+        assert_eq!(children_2.len(), 1);
+        assert_ne!(children_1[0], children_2[0]);
+
+        for child_entity in [children_1[0], children_2[0]] {
+            assert_eq!(world.get::<ChildIndex>(child_entity), Some(&ChildIndex(3)));
+        }
     }
 
     #[test]
@@ -354,6 +4280,151 @@ mod tests {
         assert_eq!(world.get::<B>(child_entity), Some(&B(17)));
     }
 
+    #[test]
+    fn despawned_parent_no_orphan() {
+        /// A component whose hook despawns the given parent via commands.
+        struct DespawnsParent(Entity);
+
+        impl Component for DespawnsParent {
+            const STORAGE_TYPE: StorageType = StorageType::Table;
+
+            fn register_component_hooks(hooks: &mut ComponentHooks) {
+                hooks.on_add(|mut world, entity, _| {
+                    let parent = world.get::<DespawnsParent>(entity).unwrap().0;
+                    if let Some(parent_commands) = world.commands().get_entity(parent) {
+                        parent_commands.despawn_recursive();
+                    }
+                });
+            }
+        }
+
+        let mut world = World::new();
+        world.insert_resource(FailurePolicy::Ignore);
+
+        // The despawn is applied when spawning the next child flushes the world
+        let parent = world.spawn_empty().id();
+        world.entity_mut(parent).insert(WithChildren(vec![
+            DespawnsParent(parent),
+            DespawnsParent(parent),
+            DespawnsParent(parent),
+        ]));
+        world.flush();
+
+        let grouped_parent = world.spawn_empty().id();
+        world
+            .entity_mut(grouped_parent)
+            .insert(spawn_children_grouped(vec![
+                DespawnsParent(grouped_parent),
+                DespawnsParent(grouped_parent),
+            ]));
+        world.flush();
+
+        // Large batches apply the despawn once every child has been spawned
+        let batch_parent = world.spawn_empty().id();
+        world.entity_mut(batch_parent).insert(WithChildren(
+            (0..BATCH_THRESHOLD)
+                .map(|_| DespawnsParent(batch_parent))
+                .collect::<Vec<_>>(),
+        ));
+        world.flush();
+
+        let recorded_parent = world.spawn_empty().id();
+        world
+            .entity_mut(recorded_parent)
+            .insert(spawn_children_recorded(vec![
+                DespawnsParent(recorded_parent),
+                DespawnsParent(recorded_parent),
+            ]));
+        world.flush();
+
+        assert!(world.get_entity(parent).is_none());
+        assert!(world.get_entity(grouped_parent).is_none());
+        assert!(world.get_entity(recorded_parent).is_none());
+        assert!(world.get_entity(batch_parent).is_none());
+        let mut query = world.query::<&DespawnsParent>();
+        assert_eq!(query.iter(&world).count(), 0);
+    }
+
+    #[test]
+    fn adopt_existing_children() {
+        let mut world = World::new();
+        world.insert_resource(FailurePolicy::Ignore);
+
+        let old_parent = world.spawn_empty().id();
+        let first = world.spawn(A).id();
+        let second = world.spawn(A).id();
+        world.entity_mut(old_parent).add_child(second);
+        let despawned = world.spawn_empty().id();
+        world.despawn(despawned);
+
+        let parent = world
+            .spawn(adopt_children([
+                (first, B(1)),
+                (despawned, B(2)),
+                (second, B(3)),
+            ]))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(world.get_entity(despawned).is_none());
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.to_vec(), [first, second]);
+        assert_eq!(world.get::<B>(first), Some(&B(1)));
+        assert_eq!(world.get::<B>(second), Some(&B(3)));
+        assert!(world.get::<Children>(old_parent).is_none());
+    }
+
+    #[test]
+    fn with_child_entity() {
+        let mut world = World::new();
+
+        // A reserved id is spawned in place
+        let reserved = world.entities().reserve_entity();
+        let parent = world.spawn(WithChildEntity(reserved, B(1))).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world.entity(parent).contains::<WithChildEntity<B>>());
+        assert_eq!(world.get::<Children>(parent).unwrap().to_vec(), [reserved]);
+        assert_eq!(world.get::<B>(reserved), Some(&B(1)));
+
+        // An existing entity keeps its other components, but conflicting ones are overwritten
+        let existing = world.spawn((A, B(0))).id();
+        world
+            .entity_mut(parent)
+            .insert(WithChildEntity(existing, B(2)));
+        world.flush();
+
+        assert_eq!(
+            world.get::<Children>(parent).unwrap().to_vec(),
+            [reserved, existing]
+        );
+        assert!(world.entity(existing).contains::<A>());
+        assert_eq!(world.get::<B>(existing), Some(&B(2)));
+    }
+
+    #[test]
+    fn parent_change_detection() {
+        let mut world = World::new();
+        let parent = world.spawn((A, B(0))).id();
+        world.clear_trackers();
+
+        world.entity_mut(parent).insert(WithChild(B(1)));
+        // FIXME: this should not be needed!
+        world.flush();
+        assert_eq!(world.get::<Children>(parent).unwrap().len(), 1);
+
+        let mut changed_a = world.query_filtered::<Entity, Changed<A>>();
+        let mut changed_b = world.query_filtered::<Entity, Changed<B>>();
+        let mut changed_children = world.query_filtered::<Entity, Changed<Children>>();
+        assert_eq!(changed_a.iter(&world).count(), 0);
+        // Only the newly spawned child's B counts as changed, not the parent's
+        assert_eq!(changed_b.iter(&world).count(), 1);
+        assert!(!changed_b.iter(&world).any(|entity| entity == parent));
+        assert_eq!(changed_children.iter(&world).collect::<Vec<_>>(), [parent]);
+    }
+
     #[test]
     fn command_form() {
         fn spawn_with_child(mut commands: Commands) -> Entity {
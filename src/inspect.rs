@@ -0,0 +1,138 @@
+use core::fmt::Write;
+use std::collections::HashSet;
+
+use crate::deps::bevy_ecs::prelude::*;
+use crate::deps::bevy_hierarchy::{Children, Parent};
+use crate::deps::bevy_utils::get_short_name;
+
+use crate::SpawnsChildrenMarker;
+
+/// Produces a deterministic, indented text representation of the hierarchy rooted at `root`.
+///
+/// Each entity is written on its own line, listing the short type names of its components in alphabetical order.
+/// Children are indented beneath their parent, in the order they appear in [`Children`].
+/// [`Parent`], [`Children`] and [`SpawnsChildrenMarker`] themselves are omitted, as they are represented by the indentation.
+///
+/// This is intended for snapshot testing of templates, where walking the hierarchy by hand is tedious.
+/// Entity ids are not included, so the output is stable regardless of spawn order elsewhere in the world.
+///
+/// Although hierarchies should never contain cycles, they are guarded against:
+/// an entity that has already been visited is written as `<cycle>` and not descended into.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::{dump_hierarchy, WithChild};
+///
+/// #[derive(Component)]
+/// struct Menu;
+///
+/// #[derive(Component)]
+/// struct Button;
+///
+/// #[derive(Component)]
+/// struct Label;
+///
+/// let mut world = World::new();
+/// let root = world.spawn((Menu, WithChild((Button, WithChild(Label))))).id();
+/// world.flush();
+///
+/// assert_eq!(
+///     dump_hierarchy(&world, root),
+///     "- Menu\n  - Button\n    - Label\n"
+/// );
+/// ```
+pub fn dump_hierarchy(world: &World, root: Entity) -> String {
+    let mut output = String::new();
+    let mut visited = HashSet::new();
+    dump_entity(world, root, 0, &mut visited, &mut output);
+    output
+}
+
+fn dump_entity(
+    world: &World,
+    entity: Entity,
+    depth: usize,
+    visited: &mut HashSet<Entity>,
+    output: &mut String,
+) {
+    let indent = "  ".repeat(depth);
+
+    if !visited.insert(entity) {
+        let _ = writeln!(output, "{indent}- <cycle>");
+        return;
+    }
+
+    if world.get_entity(entity).is_none() {
+        let _ = writeln!(output, "{indent}- <missing>");
+        return;
+    }
+
+    let omitted = [
+        world.component_id::<Parent>(),
+        world.component_id::<Children>(),
+        world.component_id::<SpawnsChildrenMarker>(),
+        // Internal bookkeeping should not show up in user-facing snapshots
+        #[cfg(all(feature = "recursion_guard", debug_assertions))]
+        world.component_id::<crate::recursion_guard::SpawnedByWithChild>(),
+    ];
+
+    let mut names: Vec<String> = world
+        .inspect_entity(entity)
+        .into_iter()
+        .filter(|info| !omitted.contains(&Some(info.id())))
+        .map(|info| get_short_name(info.name()))
+        .collect();
+    names.sort();
+
+    let description = if names.is_empty() {
+        "<empty>".to_string()
+    } else {
+        names.join(", ")
+    };
+    let _ = writeln!(output, "{indent}- {description}");
+
+    if let Some(children) = world.get::<Children>(entity) {
+        for &child in children {
+            dump_entity(world, child, depth + 1, visited, output);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{WithChild, WithChildren};
+
+    #[derive(Component)]
+    struct A;
+
+    #[derive(Component)]
+    struct B(#[allow(dead_code)] u8);
+
+    #[test]
+    fn grandchildren_snapshot() {
+        let mut world = World::default();
+
+        let parent = world.spawn(WithChild((A, WithChild((A, B(3)))))).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(
+            dump_hierarchy(&world, parent),
+            "- <empty>\n  - A\n    - A, B\n"
+        );
+    }
+
+    #[test]
+    fn sibling_order() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn((B(0), WithChildren(vec![(A, B(1)), (A, B(2))])))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(dump_hierarchy(&world, parent), "- B\n  - A, B\n  - A, B\n");
+    }
+}
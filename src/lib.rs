@@ -1,7 +1,101 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "async")]
+mod async_stream;
+#[cfg(feature = "async")]
+pub use async_stream::*;
+
+#[cfg(feature = "auto_name")]
+mod auto_name;
+
+mod boxed;
+pub use boxed::*;
+
+mod builder;
+pub use builder::*;
+
+mod channel;
+pub use channel::*;
+
+mod deps;
+
+mod despawn;
+pub use despawn::*;
+
+mod diagnostics;
+pub(crate) use diagnostics::{
+    record_children_spawned, record_command_applied, record_missing_skip,
+};
+#[cfg(feature = "diagnostics")]
+pub use diagnostics::{WithChildDiagnostics, WithChildDiagnosticsPlugin};
+
+mod failure;
+pub use failure::*;
+
 mod hierarchy;
 pub use hierarchy::*;
 
+mod inspect;
+pub use inspect::*;
+
+mod link;
+pub(crate) use link::*;
+
+#[cfg(feature = "single_threaded")]
+mod local;
+#[cfg(feature = "single_threaded")]
+pub use local::*;
+
 mod maybe;
 pub use maybe::*;
+
+mod named;
+pub use named::*;
+
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "parallel")]
+pub use parallel::*;
+
+mod pending;
+pub use pending::*;
+
+mod reconcile;
+pub use reconcile::*;
+
+mod round_robin;
+pub use round_robin::*;
+
+#[cfg(feature = "bevy_scene")]
+mod scene;
+#[cfg(feature = "bevy_scene")]
+pub use scene::*;
+
+mod spawner;
+pub use spawner::*;
+
+mod streamed;
+pub use streamed::*;
+
+mod strict;
+pub use strict::*;
+
+#[cfg(any(test, feature = "testing"))]
+mod testing;
+#[cfg(any(test, feature = "testing"))]
+pub use testing::*;
+
+#[cfg(feature = "bevy_transform")]
+mod transform;
+
+mod tree;
+pub use tree::*;
+
+#[cfg(debug_assertions)]
+mod collision_guard;
+
+#[cfg(all(feature = "recursion_guard", debug_assertions))]
+mod recursion_guard;
+
+#[cfg(test)]
+mod test_utils;
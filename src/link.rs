@@ -0,0 +1,331 @@
+//! The single point through which this crate's commands link children to their parents.
+//!
+//! Bevy's parent-child relationship is currently provided by `bevy_hierarchy`,
+//! but is expected to move to a more general relationships model.
+//! Keeping every structural hierarchy operation behind [`LinkChild`] means that
+//! supporting a different relationship only requires a new implementation of that trait,
+//! and pointing [`Link`] at it.
+
+use crate::deps::bevy_ecs::{
+    entity::{EntityMapper, MapEntities},
+    event::Events,
+    prelude::*,
+    world::Command,
+};
+use crate::deps::bevy_hierarchy::{
+    despawn_with_children_recursive, BuildWorldChildren, Children, HierarchyEvent, Parent,
+};
+use crate::deps::bevy_utils::tracing::warn;
+
+#[cfg(feature = "bevy_transform")]
+use crate::transform::ChildTransforms;
+use crate::{on_missing, record_children_spawned, WithChildError};
+
+/// A parent-child relationship between entities, as used by this crate's commands.
+///
+/// Spawning an entity flushes the world, which may apply a despawn of the parent queued by a hook or an earlier command.
+/// The linking methods therefore check that the parent still exists:
+/// if it doesn't, the children are despawned along with their descendants rather than left orphaned,
+/// the failure is reported according to the [`FailurePolicy`](crate::FailurePolicy), and `false` is returned.
+pub(crate) trait LinkChild {
+    /// Adds `child` to the end of the children of `parent`.
+    ///
+    /// Returns `false` if `parent` no longer exists, in which case `child` has been despawned.
+    fn link_child(world: &mut World, parent: Entity, child: Entity) -> bool;
+
+    /// Inserts `child` into the children of `parent`, at the given index.
+    ///
+    /// Returns `false` if `parent` no longer exists, in which case `child` has been despawned.
+    fn link_child_at(world: &mut World, parent: Entity, index: usize, child: Entity) -> bool;
+
+    /// Adds each of `children` to the end of the children of `parent`, in order.
+    ///
+    /// Returns `false` if `parent` no longer exists, in which case `children` have been despawned.
+    fn link_children(world: &mut World, parent: Entity, children: &[Entity]) -> bool;
+
+    /// Spawns an entity with the given bundle, which the caller will then link as a child.
+    ///
+    /// Every child spawned by this crate goes through either this or [`LinkChild::spawn_linked`],
+    /// so that it's counted by `WithChildDiagnostics`.
+    fn spawn_child<B: Bundle>(world: &mut World, bundle: B) -> EntityWorldMut<'_>;
+
+    /// Spawns a child of `parent` for each bundle, in order, passing each new child to `on_spawn`.
+    ///
+    /// If the bundles' iterator reports at least [`BATCH_THRESHOLD`] items in its lower size bound,
+    /// the children are spawned with [`World::spawn_batch`], which reserves storage for all of them up front.
+    /// Commands queued by the children's hooks are then applied once every child has been spawned,
+    /// rather than before spawning the next child.
+    ///
+    /// Returns `false` if `parent` no longer exists, in which case the remaining bundles are dropped without being spawned.
+    fn spawn_linked<B: Bundle>(
+        world: &mut World,
+        parent: Entity,
+        bundles: impl IntoIterator<Item = B>,
+        on_spawn: impl FnMut(EntityWorldMut),
+    ) -> bool;
+
+    /// Removes every child of `parent`, leaving them without a parent.
+    fn unlink_children(world: &mut World, parent: Entity);
+
+    /// Returns the current children of `parent`, in order.
+    fn children(world: &World, parent: Entity) -> &[Entity];
+
+    /// Returns the parent of `child`, if any.
+    fn parent(world: &World, child: Entity) -> Option<Entity>;
+
+    /// Despawns `entity` along with all of its descendants.
+    fn despawn_recursive(world: &mut World, entity: Entity);
+}
+
+/// The [`LinkChild`] implementation used throughout this crate.
+pub(crate) type Link = HierarchyLink;
+
+/// Links children using the [`Parent`] and [`Children`] components of `bevy_hierarchy`.
+pub(crate) struct HierarchyLink;
+
+impl LinkChild for HierarchyLink {
+    fn link_child(world: &mut World, parent: Entity, child: Entity) -> bool {
+        if !parent_exists(world, parent, &[child]) {
+            return false;
+        }
+        remove_stray_parent(world, child);
+        world.entity_mut(parent).add_child(child);
+        #[cfg(feature = "bevy_transform")]
+        ChildTransforms::new(world, parent).propagate_to(world.entity_mut(child));
+        true
+    }
+
+    fn link_child_at(world: &mut World, parent: Entity, index: usize, child: Entity) -> bool {
+        if !parent_exists(world, parent, &[child]) {
+            return false;
+        }
+        remove_stray_parent(world, child);
+        world.entity_mut(parent).insert_children(index, &[child]);
+        #[cfg(feature = "bevy_transform")]
+        ChildTransforms::new(world, parent).propagate_to(world.entity_mut(child));
+        true
+    }
+
+    fn link_children(world: &mut World, parent: Entity, children: &[Entity]) -> bool {
+        if !parent_exists(world, parent, children) {
+            return false;
+        }
+        for &child in children {
+            remove_stray_parent(world, child);
+        }
+        world.entity_mut(parent).push_children(children);
+        #[cfg(feature = "bevy_transform")]
+        {
+            let transforms = ChildTransforms::new(world, parent);
+            for &child in children {
+                transforms.propagate_to(world.entity_mut(child));
+            }
+        }
+        true
+    }
+
+    fn spawn_child<B: Bundle>(world: &mut World, bundle: B) -> EntityWorldMut<'_> {
+        record_children_spawned(world, 1);
+        world.spawn(bundle)
+    }
+
+    fn spawn_linked<B: Bundle>(
+        world: &mut World,
+        parent: Entity,
+        bundles: impl IntoIterator<Item = B>,
+        mut on_spawn: impl FnMut(EntityWorldMut),
+    ) -> bool {
+        let bundles = bundles.into_iter();
+        if bundles.size_hint().0 >= BATCH_THRESHOLD {
+            return spawn_batch_linked(world, parent, bundles, on_spawn);
+        }
+
+        #[cfg(feature = "bevy_transform")]
+        let transforms = ChildTransforms::new(world, parent);
+        let mut child_count = 0;
+        let mut parent_exists = true;
+        for bundle in bundles {
+            // Spawning flushes the world, which would apply any despawn of the parent queued by the previous child's hooks
+            // between spawning this child and linking it: flushing first means that can't happen.
+            world.flush();
+            let Some(mut parent_mut) = world.get_entity_mut(parent) else {
+                on_missing(world, WithChildError::ParentMissing(parent));
+                parent_exists = false;
+                break;
+            };
+
+            // Spawning through the builder inserts each child's `Parent` alongside its bundle,
+            // avoiding an extra archetype move per child compared to spawning and then linking it.
+            parent_mut.with_children(|builder| {
+                let child = builder.spawn(bundle);
+                #[cfg(feature = "bevy_transform")]
+                let child = transforms.propagate_to(child);
+                on_spawn(child);
+            });
+            child_count += 1;
+        }
+        record_children_spawned(world, child_count);
+        parent_exists
+    }
+
+    fn unlink_children(world: &mut World, parent: Entity) {
+        world.entity_mut(parent).clear_children();
+    }
+
+    fn children(world: &World, parent: Entity) -> &[Entity] {
+        world
+            .get::<Children>(parent)
+            .map_or(&[], |children| children)
+    }
+
+    fn parent(world: &World, child: Entity) -> Option<Entity> {
+        world.get::<Parent>(child).map(Parent::get)
+    }
+
+    fn despawn_recursive(world: &mut World, entity: Entity) {
+        despawn_with_children_recursive(world, entity);
+    }
+}
+
+/// The lower size bound from which [`LinkChild::spawn_linked`] spawns children with [`World::spawn_batch`].
+///
+/// Below this, the cost of setting up the batch outweighs the savings from reserving storage.
+pub(crate) const BATCH_THRESHOLD: usize = 32;
+
+/// Spawns a child of `parent` for each bundle with [`World::spawn_batch`], for [`LinkChild::spawn_linked`].
+///
+/// Each child is spawned with its [`Parent`] already in place, so linking it doesn't move it to another archetype.
+fn spawn_batch_linked<B: Bundle>(
+    world: &mut World,
+    parent: Entity,
+    bundles: impl Iterator<Item = B>,
+    mut on_spawn: impl FnMut(EntityWorldMut),
+) -> bool {
+    // Spawning the batch flushes the world, so check the parent afterwards
+    world.flush();
+    if world.get_entity(parent).is_none() {
+        on_missing(world, WithChildError::ParentMissing(parent));
+        return false;
+    }
+
+    // `Parent` can only be built outside of `bevy_hierarchy` as a placeholder, which is then mapped to the real parent.
+    // Building the placeholder requires a world, but doesn't use it.
+    let mut scratch_world = World::new();
+    let mut mapper = ParentMapper(parent);
+    let children: Vec<Entity> = world
+        // The batch polls the iterator again when it's dropped, so it must stop at the first `None`
+        .spawn_batch(LowerBound(bundles.fuse()).map(|bundle| {
+            let mut parent_component = Parent::from_world(&mut scratch_world);
+            parent_component.map_entities(&mut mapper);
+            (bundle, parent_component)
+        }))
+        .collect();
+    record_children_spawned(world, children.len());
+
+    // The batch applies the commands queued by the children's hooks once they have all been spawned
+    if !parent_exists(world, parent, &children) {
+        return false;
+    }
+    world.entity_mut(parent).push_children(&children);
+    // As the children already point to their parent, linking them doesn't report them as added
+    if let Some(mut events) = world.get_resource_mut::<Events<HierarchyEvent>>() {
+        events.extend(
+            children
+                .iter()
+                .map(|&child| HierarchyEvent::ChildAdded { child, parent }),
+        );
+    }
+
+    #[cfg(feature = "bevy_transform")]
+    let transforms = ChildTransforms::new(world, parent);
+    for child in children {
+        let child = world.entity_mut(child);
+        #[cfg(feature = "bevy_transform")]
+        let child = transforms.propagate_to(child);
+        on_spawn(child);
+    }
+    true
+}
+
+/// Maps every entity to the given parent, to point a placeholder [`Parent`] at it.
+struct ParentMapper(Entity);
+
+impl EntityMapper for ParentMapper {
+    fn map_entity(&mut self, _entity: Entity) -> Entity {
+        self.0
+    }
+}
+
+/// Hides the upper size bound of an iterator, so that [`World::spawn_batch`] reserves storage for its lower bound.
+///
+/// The upper bound can be far larger than the number of items, such as for a filtered range.
+struct LowerBound<I>(I);
+
+impl<I: Iterator> Iterator for LowerBound<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.size_hint().0, None)
+    }
+}
+
+/// Returns `true` if `parent` exists, so that `children` can be linked to it.
+///
+/// Otherwise, `children` are despawned along with their descendants, rather than being left orphaned,
+/// and the failure is reported.
+fn parent_exists(world: &mut World, parent: Entity, children: &[Entity]) -> bool {
+    if world.get_entity(parent).is_some() {
+        return true;
+    }
+
+    for &child in children {
+        HierarchyLink::despawn_recursive(world, child);
+    }
+    on_missing(world, WithChildError::ParentMissing(parent));
+    false
+}
+
+/// Removes the [`Parent`] of `child` if its parent doesn't list it among its [`Children`], logging a warning.
+///
+/// Such a stray [`Parent`] can only come from the child's bundle, for example if it was cloned from another entity
+/// or created with [`FromWorld`].
+/// Linking a child with a stray [`Parent`] would report it as moved from that entity,
+/// or not report it at all if the stray [`Parent`] already points to the new parent.
+/// Removing it first means the child is linked as if it had been spawned without one.
+fn remove_stray_parent(world: &mut World, child: Entity) {
+    let Some(stray_parent) = world.get::<Parent>(child).map(Parent::get) else {
+        return;
+    };
+    if world
+        .get::<Children>(stray_parent)
+        .is_some_and(|children| children.contains(&child))
+    {
+        return;
+    }
+
+    warn!(
+        "Child {child} had a `Parent` component pointing to {stray_parent}, which doesn't list it as a child. \
+        The stray `Parent` was removed before linking the child: don't include `Parent` in child bundles."
+    );
+    world.entity_mut(child).remove::<Parent>();
+}
+
+/// A [`Command`] that spawns a child of `parent_entity` for each of `bundles`, in order.
+///
+/// This is used by systems that produce children over several frames, so that they go through [`Link`] like any other command.
+pub(crate) struct SpawnLinkedCommand<B> {
+    /// The entity that will become the parent.
+    pub(crate) parent_entity: Entity,
+    /// The bundles to spawn as children.
+    pub(crate) bundles: Vec<B>,
+}
+
+impl<B: Bundle> Command for SpawnLinkedCommand<B> {
+    fn apply(self, world: &mut World) {
+        Link::spawn_linked(world, self.parent_entity, self.bundles, |_| {});
+    }
+}
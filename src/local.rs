@@ -0,0 +1,225 @@
+use core::any::Any;
+use core::cell::RefCell;
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::collections::HashMap;
+
+use crate::deps::bevy_ecs::{
+    component::{ComponentHooks, ComponentId, StorageType},
+    prelude::*,
+    world::{Command, DeferredWorld},
+};
+
+use crate::{
+    mark_deferred, on_cancelled, on_missing, queue_deferred, Link, LinkChild, WithChildError,
+};
+
+thread_local! {
+    /// The iterators of every unresolved [`WithChildrenLocal`] created on this thread, keyed by id.
+    ///
+    /// Each value is a `Box<dyn Iterator<Item = B>>` for the component's bundle type `B`.
+    static LOCAL_ITERATORS: RefCell<HashMap<u64, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// The id of the next [`WithChildrenLocal`] to be created.
+static NEXT_LOCAL_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A component that, when added to an entity, will add a child entity for each item generated by the iterator,
+/// which does not need to be [`Send`] or [`Sync`].
+///
+/// This allows generators that capture [`Rc`](std::rc::Rc) or other thread-bound data,
+/// which [`WithChildren`](crate::WithChildren) rules out.
+///
+/// Components must be [`Send`] and [`Sync`], so the iterator is kept in thread-local storage,
+/// and the component only stores a handle to it.
+/// As a result, this is only suitable for single-threaded apps, such as Wasm builds without threads:
+/// the component must be resolved on the same thread that created it.
+/// If it isn't, the iterator is not found, and the [`FailurePolicy`](crate::FailurePolicy) is followed.
+///
+/// If the component is dropped without being resolved on the thread that created it, its iterator is dropped too.
+///
+/// This requires the `single_threaded` feature.
+///
+/// ```rust
+/// use std::rc::Rc;
+///
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::WithChildrenLocal;
+///
+/// #[derive(Component)]
+/// struct Glyph(char);
+///
+/// let text: Rc<str> = Rc::from("hi!");
+///
+/// let mut world = World::new();
+/// let label = world
+///     .spawn(WithChildrenLocal::new(
+///         (0..text.len()).map(move |i| Glyph(text[i..].chars().next().unwrap())),
+///     ))
+///     .id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(label).unwrap().len(), 3);
+/// ```
+#[derive(Debug)]
+pub struct WithChildrenLocal<B: Bundle> {
+    id: u64,
+    _phantom: PhantomData<B>,
+}
+
+impl<B: Bundle> WithChildrenLocal<B> {
+    /// Creates a new [`WithChildrenLocal`] component, storing `iter` on the current thread.
+    pub fn new<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = B>,
+        I::IntoIter: 'static,
+    {
+        let id = NEXT_LOCAL_ID.fetch_add(1, Ordering::Relaxed);
+        let iter: Box<dyn Iterator<Item = B>> = Box::new(iter.into_iter());
+        LOCAL_ITERATORS.with_borrow_mut(|iterators| iterators.insert(id, Box::new(iter)));
+
+        Self {
+            id,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Removes this component's iterator from the current thread's storage, if it is there.
+    fn take_iter(&self) -> Option<Box<dyn Iterator<Item = B>>> {
+        LOCAL_ITERATORS
+            .try_with(|iterators| iterators.borrow_mut().remove(&self.id))
+            .ok()
+            .flatten()
+            .and_then(|iter| iter.downcast::<Box<dyn Iterator<Item = B>>>().ok())
+            .map(|iter| *iter)
+    }
+}
+
+impl<B: Bundle> Drop for WithChildrenLocal<B> {
+    fn drop(&mut self) {
+        // Frees the iterator of a component that was never resolved
+        drop(self.take_iter());
+    }
+}
+
+impl<B: Bundle> Component for WithChildrenLocal<B> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_children_local_hook::<B>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildrenLocal`] is added to an entity.
+///
+/// Generates a [`WithChildrenLocalCommand`].
+fn with_children_local_hook<B: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildrenLocalCommand::<B>::new,
+    );
+}
+
+/// A [`Command`] that spawns the children generated by the iterator of the [`WithChildrenLocal<B>`] component of an entity.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildrenLocalCommand<B> {
+    /// The entity holding the [`WithChildrenLocal`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> WithChildrenLocalCommand<B> {
+    /// Creates a new [`WithChildrenLocalCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle> Command for WithChildrenLocalCommand<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(with_children_local) = entity_mut.take::<WithChildrenLocal<B>>() else {
+            on_cancelled("WithChildrenLocal");
+            return;
+        };
+
+        let Some(iter) = with_children_local.take_iter() else {
+            on_missing(world, WithChildError::WrongThread(self.parent_entity));
+            return;
+        };
+
+        Link::spawn_linked(world, self.parent_entity, iter, |_| {});
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::deps::bevy_hierarchy::Children;
+
+    use super::*;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct B(u8);
+
+    #[test]
+    fn rc_capturing_iterator() {
+        let mut world = World::default();
+
+        let generated = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&generated);
+        let parent = world
+            .spawn(WithChildrenLocal::new((0..3).map(move |i| {
+                counter.set(counter.get() + 1);
+                B(i)
+            })))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world.entity(parent).contains::<WithChildrenLocal<B>>());
+        let values: Vec<u8> = world
+            .get::<Children>(parent)
+            .unwrap()
+            .iter()
+            .map(|&child| world.get::<B>(child).unwrap().0)
+            .collect();
+        assert_eq!(values, [0, 1, 2]);
+        assert_eq!(generated.get(), 3);
+        // The iterator, and the closure holding a clone of the `Rc`, has been dropped
+        assert_eq!(Rc::strong_count(&generated), 1);
+    }
+
+    #[test]
+    fn unresolved_iterator_is_dropped() {
+        let generated = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&generated);
+        let component = WithChildrenLocal::new(core::iter::once(B(0)).inspect(move |_| {
+            counter.set(counter.get() + 1);
+        }));
+        assert_eq!(Rc::strong_count(&generated), 2);
+
+        drop(component);
+        assert_eq!(Rc::strong_count(&generated), 1);
+    }
+}
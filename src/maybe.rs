@@ -1,10 +1,20 @@
 use core::marker::PhantomData;
 
-use bevy_ecs::{
+use crate::deps::bevy_ecs::{
     component::{ComponentHooks, ComponentId, StorageType},
+    entity::{EntityHashMap, EntityMapper, MapEntities},
     prelude::*,
     world::{Command, DeferredWorld},
 };
+#[cfg(feature = "trace")]
+use crate::deps::bevy_utils::tracing::info_span;
+#[cfg(debug_assertions)]
+use crate::deps::bevy_utils::tracing::warn;
+
+use crate::{
+    mark_deferred, on_cancelled, on_missing, queue_deferred, Link, LinkChild, ManualResolution,
+    WithChildError,
+};
 
 /// A component that when added to an entity, will be removed from the entity and replaced with its contents if [`Some`].
 ///
@@ -18,7 +28,7 @@ use bevy_ecs::{
 /// use bevy_ecs::prelude::*;
 /// use bevy_ecs::system::RunSystemOnce;
 /// use i_cant_believe_its_not_bsn::Maybe;
-
+///
 /// #[derive(Component)]
 /// struct A;
 ///
@@ -51,6 +61,58 @@ use bevy_ecs::{
 /// assert!(!entity_ref.contains::<A>());
 /// assert!(!entity_ref.contains::<Maybe<A>>());
 /// ```
+///
+/// # Components and bundles
+///
+/// Any bundle can be stored in a `Maybe`, including a single component.
+/// Note that `Maybe::new(A)` and `Maybe::new((A,))` insert the same component,
+/// but are different types: `Maybe<A>` and `Maybe<(A,)>`.
+/// Code that inspects or modifies the `Maybe` before it is resolved must use the matching type.
+/// Prefer [`Maybe::component`] for single components to make that intent explicit.
+///
+/// # Ordering
+///
+/// The contents of a [`Maybe`] are inserted by a command, after the insertion that added the [`Maybe`] has completed.
+/// This gives the following guarantees:
+///
+/// - Hooks and observers that react to the contents themselves (such as [`OnAdd`] or [`OnInsert`] for a component of `B`)
+///   run when the contents are inserted, so they always see the resolved bundle,
+///   along with the contents of every [`Maybe`] added to the same entity before it.
+/// - Hooks and observers that react to the insertion of the [`Maybe`] itself, or to other components inserted alongside it,
+///   run before it is resolved, so they never see its contents.
+/// - Commands queued by those hooks and observers may run before or after the [`Maybe`] is resolved,
+///   depending on the order in which the hooks ran.
+///
+/// Logic that depends on the contents of a [`Maybe`] should therefore observe the contents directly,
+/// rather than a component spawned alongside it.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::Maybe;
+///
+/// #[derive(Component)]
+/// struct Flying;
+///
+/// #[derive(Component)]
+/// struct Wings;
+///
+/// let mut world = World::new();
+/// world.observe(|trigger: Trigger<OnAdd, Wings>, query: Query<Has<Flying>>| {
+///     // The earlier Maybe has already been resolved
+///     assert!(query.get(trigger.entity()).unwrap());
+/// });
+///
+/// world.spawn((Maybe::new(Flying), Maybe::new(Wings)));
+/// world.flush();
+/// ```
+///
+/// # Performance
+///
+/// [`Maybe`] components added to the same entity before the world is next flushed are resolved by a single command,
+/// queued by the first of them, rather than by one command each.
+/// They are resolved in the order they were added, at the position of that first command.
+/// Coalescing is enabled for a world once its first [`Maybe`] has been resolved,
+/// and is skipped while the [`ManualResolution`] resource exists.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Maybe<B: Bundle>(pub Option<B>);
 
@@ -59,6 +121,7 @@ impl<B: Bundle> Component for Maybe<B> {
     const STORAGE_TYPE: StorageType = StorageType::SparseSet;
 
     fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
         hooks.on_add(maybe_hook::<B>);
     }
 }
@@ -72,10 +135,198 @@ impl<B: Bundle> Maybe<B> {
         Self(Some(bundle))
     }
 
+    /// Creates a new `Maybe` component that contains `bundle` if and only if `condition` is `true`.
+    ///
+    /// If `condition` is `false`, the bundle is dropped immediately.
+    /// See [`maybe_if`] for a free-function version of this constructor.
+    pub fn from_bool(condition: bool, bundle: B) -> Self {
+        if condition {
+            Self::new(bundle)
+        } else {
+            Self::NONE
+        }
+    }
+
     /// Returns the contents of the `Maybe` component, if any.
     pub fn into_inner(self) -> Option<B> {
         self.0
     }
+
+    /// Takes the bundle out of the `Maybe` component, leaving [`Maybe::NONE`] in its place.
+    ///
+    /// This mirrors [`Option::take`], and can be used to modify a `Maybe` component before it is resolved.
+    pub fn take(&mut self) -> Option<B> {
+        self.0.take()
+    }
+
+    /// Replaces the bundle in the `Maybe` component with the given bundle, returning the old bundle, if any.
+    ///
+    /// This mirrors [`Option::replace`], and can be used to modify a `Maybe` component before it is resolved.
+    pub fn replace(&mut self, bundle: B) -> Option<B> {
+        self.0.replace(bundle)
+    }
+
+    /// Returns a reference to the bundle in the `Maybe` component, if any.
+    ///
+    /// This mirrors [`Option::as_ref`], and can be used to inspect a `Maybe` component before it is resolved.
+    ///
+    /// ```rust
+    /// use bevy_ecs::prelude::*;
+    /// use i_cant_believe_its_not_bsn::{resolve_deferred, ManualResolution, Maybe};
+    ///
+    /// #[derive(Component, Debug, PartialEq)]
+    /// struct Shield(u32);
+    ///
+    /// let mut world = World::new();
+    /// // Hold off resolution, so that the pending components can be inspected
+    /// world.init_resource::<ManualResolution>();
+    /// world.spawn(Maybe::new(Shield(50)));
+    /// world.spawn(Maybe::<Shield>::NONE);
+    /// world.flush();
+    ///
+    /// let mut query = world.query::<&Maybe<Shield>>();
+    /// let mut pending_shields: Vec<_> = query.iter(&world).filter_map(Maybe::as_ref).collect();
+    /// assert_eq!(pending_shields, [&Shield(50)]);
+    ///
+    /// resolve_deferred(&mut world);
+    /// assert_eq!(query.iter(&world).count(), 0);
+    /// ```
+    pub fn as_ref(&self) -> Option<&B> {
+        self.0.as_ref()
+    }
+
+    /// Returns a mutable reference to the bundle in the `Maybe` component, if any.
+    ///
+    /// This mirrors [`Option::as_mut`], and can be used to modify a `Maybe` component in place before it is resolved.
+    ///
+    /// ```rust
+    /// use bevy_ecs::prelude::*;
+    /// use i_cant_believe_its_not_bsn::{resolve_deferred, ManualResolution, Maybe};
+    ///
+    /// #[derive(Component, Debug, PartialEq)]
+    /// struct Shield(u32);
+    ///
+    /// let mut world = World::new();
+    /// world.init_resource::<ManualResolution>();
+    /// let entity = world.spawn(Maybe::new(Shield(50))).id();
+    /// world.flush();
+    ///
+    /// // Double every pending shield before it is inserted
+    /// let mut query = world.query::<&mut Maybe<Shield>>();
+    /// for mut maybe_shield in query.iter_mut(&mut world) {
+    ///     // `Mut` implements `AsMut` itself, so the method must be called explicitly
+    ///     if let Some(shield) = Maybe::as_mut(&mut maybe_shield) {
+    ///         shield.0 *= 2;
+    ///     }
+    /// }
+    ///
+    /// resolve_deferred(&mut world);
+    /// assert_eq!(world.get::<Shield>(entity), Some(&Shield(100)));
+    /// ```
+    pub fn as_mut(&mut self) -> Option<&mut B> {
+        self.0.as_mut()
+    }
+}
+
+impl<C: Component> Maybe<C> {
+    /// Creates a new `Maybe` component holding a single component.
+    ///
+    /// This is equivalent to [`Maybe::new`], but rules out accidentally wrapping a tuple bundle.
+    ///
+    /// ```rust
+    /// use bevy_ecs::prelude::*;
+    /// use i_cant_believe_its_not_bsn::Maybe;
+    ///
+    /// #[derive(Component)]
+    /// struct A;
+    ///
+    /// #[derive(Component)]
+    /// struct B;
+    ///
+    /// let mut world = World::new();
+    /// // A single component: stored as a `Maybe<A>`
+    /// let single = world.spawn(Maybe::component(A)).id();
+    /// // A bundle of components: stored as a `Maybe<(A, B)>`
+    /// let bundle = world.spawn(Maybe::new((A, B))).id();
+    /// world.flush();
+    ///
+    /// assert!(world.entity(single).contains::<A>());
+    /// assert!(world.entity(bundle).contains::<A>());
+    /// assert!(world.entity(bundle).contains::<B>());
+    /// ```
+    pub const fn component(component: C) -> Self {
+        Self(Some(component))
+    }
+}
+
+impl<B: Bundle> Maybe<Maybe<B>> {
+    /// Collapses a nested `Maybe<Maybe<B>>` into a single `Maybe<B>`.
+    ///
+    /// Nested `Maybe`s can arise when conditional templates are composed.
+    /// They resolve correctly without flattening: the outer layer's command inserts the inner `Maybe`,
+    /// whose own command then runs during the same flush.
+    /// However, this queues two commands and moves the entity between archetypes an extra time,
+    /// so prefer flattening when both layers are known up front.
+    ///
+    /// ```rust
+    /// use bevy_ecs::prelude::*;
+    /// use i_cant_believe_its_not_bsn::Maybe;
+    ///
+    /// #[derive(Component, Debug, PartialEq)]
+    /// struct A;
+    ///
+    /// assert_eq!(Maybe::new(Maybe::new(A)).flatten(), Maybe::new(A));
+    /// assert_eq!(Maybe::new(Maybe::<A>::NONE).flatten(), Maybe::NONE);
+    /// assert_eq!(Maybe::<Maybe<A>>::NONE.flatten(), Maybe::NONE);
+    /// ```
+    pub fn flatten(self) -> Maybe<B> {
+        Maybe(self.0.and_then(Maybe::into_inner))
+    }
+}
+
+impl<B: Bundle + Copy> Maybe<B> {
+    /// A `const` version of [`Maybe::from_bool`].
+    ///
+    /// This requires `B: Copy`, as bundles with drop glue cannot be discarded in a `const` context.
+    pub const fn const_from_bool(condition: bool, bundle: B) -> Self {
+        if condition {
+            Self::new(bundle)
+        } else {
+            Self::NONE
+        }
+    }
+}
+
+/// Creates a new [`Maybe`] component that contains `bundle` if and only if `condition` is `true`.
+///
+/// This is shorthand for [`Maybe::from_bool`], and is particularly readable inside of larger bundles.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::{maybe_if, Maybe};
+///
+/// #[derive(Component)]
+/// struct Enemy;
+///
+/// #[derive(Component)]
+/// struct Boss;
+///
+/// #[derive(Component)]
+/// struct Health(u32);
+///
+/// let mut world = World::new();
+///
+/// let is_boss = true;
+/// let entity = world
+///     .spawn((Enemy, Health(100), maybe_if(is_boss, Boss)))
+///     .id();
+/// world.flush();
+///
+/// assert!(world.entity(entity).contains::<Boss>());
+/// assert!(!world.entity(entity).contains::<Maybe<Boss>>());
+/// ```
+pub fn maybe_if<B: Bundle>(condition: bool, bundle: B) -> Maybe<B> {
+    Maybe::from_bool(condition, bundle)
 }
 
 impl<B: Bundle> Default for Maybe<B> {
@@ -85,37 +336,177 @@ impl<B: Bundle> Default for Maybe<B> {
     }
 }
 
+/// Converts an [`Option`] into the equivalent [`Maybe`]: [`None`] becomes [`Maybe::NONE`].
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::Maybe;
+///
+/// #[derive(Component, Debug, PartialEq)]
+/// struct A;
+///
+/// assert_eq!(Maybe::from(Some(A)), Maybe::new(A));
+/// assert_eq!(Maybe::<A>::from(None), Maybe::NONE);
+///
+/// // Round trip
+/// assert_eq!(Option::from(Maybe::from(Some(A))), Some(A));
+/// assert_eq!(Option::<A>::from(Maybe::NONE), None);
+/// ```
+impl<B: Bundle> From<Option<B>> for Maybe<B> {
+    fn from(option: Option<B>) -> Self {
+        Self(option)
+    }
+}
+
+/// Converts a [`Maybe`] back into an [`Option`]: [`Maybe::NONE`] becomes [`None`].
+///
+/// This is equivalent to [`Maybe::into_inner`].
+impl<B: Bundle> From<Maybe<B>> for Option<B> {
+    fn from(maybe: Maybe<B>) -> Self {
+        maybe.0
+    }
+}
+
 /// A hook that runs whenever [`Maybe`] is added to an entity.
 ///
-/// Generates a [`MaybeCommand`].
-fn maybe_hook<B: Bundle>(mut world: DeferredWorld<'_>, entity: Entity, _component_id: ComponentId) {
+/// Adds the component to the entity's pending [`MaybeBatches`] entry if it has one,
+/// and generates a [`MaybeBatchCommand`] otherwise.
+fn maybe_hook<B: Bundle>(mut world: DeferredWorld<'_>, entity: Entity, component_id: ComponentId) {
+    if !world.contains_resource::<ManualResolution>() {
+        if let Some(mut batches) = world.get_resource_mut::<MaybeBatches>() {
+            if let Some(resolvers) = batches.0.get_mut(&entity) {
+                resolvers.push(resolve_maybe::<B>);
+                return;
+            }
+            batches.0.insert(entity, Vec::new());
+        }
+    }
+
     // Component hooks can't perform structural changes, so we need to rely on commands.
-    world.commands().add(MaybeCommand {
+    queue_deferred(
+        &mut world,
+        component_id,
         entity,
-        _phantom: PhantomData::<B>,
-    });
+        MaybeBatchCommand::<B>::new,
+    );
+}
+
+/// Resolves a single [`Maybe`] component, as part of a [`MaybeBatchCommand`].
+type MaybeResolver = fn(&mut World, Entity);
+
+fn resolve_maybe<B: Bundle>(world: &mut World, entity: Entity) {
+    MaybeCommand::<B>::new(entity).apply(world);
 }
 
-struct MaybeCommand<B> {
+/// The [`Maybe`] components added to each entity since its [`MaybeBatchCommand`] was queued.
+///
+/// This is initialized by the first [`MaybeBatchCommand`] to be applied,
+/// as hooks can't insert resources: until then, each [`Maybe`] is resolved by its own command.
+#[derive(Resource, Default)]
+pub(crate) struct MaybeBatches(EntityHashMap<Vec<MaybeResolver>>);
+
+/// A [`Command`] that resolves the [`Maybe<B>`] component of an entity,
+/// followed by every other [`Maybe`] component added to the entity since it was queued.
+struct MaybeBatchCommand<B> {
     entity: Entity,
     _phantom: PhantomData<B>,
 }
 
+impl<B> MaybeBatchCommand<B> {
+    const fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle> Command for MaybeBatchCommand<B> {
+    fn apply(self, world: &mut World) {
+        let resolvers = world
+            .get_resource_or_insert_with(MaybeBatches::default)
+            .0
+            .remove(&self.entity)
+            .unwrap_or_default();
+
+        if world.get_entity(self.entity).is_none() {
+            on_missing(world, WithChildError::EntityMissing(self.entity));
+            return;
+        }
+
+        MaybeCommand::<B>::new(self.entity).apply(world);
+        for resolve in resolvers {
+            resolve(world, self.entity);
+        }
+    }
+}
+
+/// A [`Command`] that resolves the [`Maybe<B>`] component of an entity,
+/// inserting its bundle if it is [`Some`].
+///
+/// This is applied automatically when the component is added,
+/// as part of a single command that resolves every [`Maybe`] component added to the entity alongside it,
+/// but can also be queued manually, or applied as part of a larger command.
+///
+/// This command is idempotent: if the [`Maybe<B>`] component has already been resolved
+/// (for example, because two commands were queued for the same entity), it does nothing,
+/// regardless of the [`FailurePolicy`](crate::FailurePolicy).
+///
+/// ```rust
+/// use bevy_ecs::{prelude::*, world::Command};
+/// use i_cant_believe_its_not_bsn::{Maybe, MaybeCommand};
+///
+/// #[derive(Component)]
+/// struct A;
+///
+/// /// Resolves a `Maybe<A>` immediately, then reports whether `A` was inserted.
+/// struct ResolveAndReport(Entity);
+///
+/// impl Command for ResolveAndReport {
+///     fn apply(self, world: &mut World) {
+///         MaybeCommand::<A>::new(self.0).apply(world);
+///         let inserted = world.entity(self.0).contains::<A>();
+///         println!("A was inserted: {inserted}");
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// // Both the hook and `ResolveAndReport` queue a command: the second to run finds nothing left to do
+/// let entity = world.spawn(Maybe::new(A)).id();
+/// world.commands().add(ResolveAndReport(entity));
+/// world.flush();
+///
+/// assert!(world.entity(entity).contains::<A>());
+/// ```
+pub struct MaybeCommand<B> {
+    /// The entity holding the [`Maybe`] component.
+    pub entity: Entity,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> MaybeCommand<B> {
+    /// Creates a new [`MaybeCommand`] targeting the given entity.
+    pub const fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
 impl<B: Bundle> Command for MaybeCommand<B> {
     fn apply(self, world: &mut World) {
-        let Some(mut entity_mut) = world.get_entity_mut(self.entity) else {
-            #[cfg(debug_assertions)]
-            panic!("Entity with Maybe component not found");
+        #[cfg(feature = "trace")]
+        let _span = info_span!("maybe", entity = ?self.entity).entered();
 
-            #[cfg(not(debug_assertions))]
+        let Some(mut entity_mut) = world.get_entity_mut(self.entity) else {
+            on_missing(world, WithChildError::EntityMissing(self.entity));
             return;
         };
 
+        // The component may have already been resolved by an earlier command for the same entity,
+        // which can legitimately happen during rapid adds and removes: this is not an error.
         let Some(maybe_component) = entity_mut.take::<Maybe<B>>() else {
-            #[cfg(debug_assertions)]
-            panic!("Maybe component not found");
-
-            #[cfg(not(debug_assertions))]
             return;
         };
 
@@ -125,53 +516,1423 @@ impl<B: Bundle> Command for MaybeCommand<B> {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// A component that, when added to an entity, will be removed from the entity and replaced with
+/// the contents of each [`Some`] value in the tuple of options it holds.
+///
+/// This is a more efficient alternative to adding several [`Maybe`] components to the same entity:
+/// only a single sparse set component and a single command are needed,
+/// and the bundles are guaranteed to be inserted in the order they appear in the tuple.
+///
+/// Tuples of up to 8 options are supported.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::MaybeChain;
+///
+/// #[derive(Component)]
+/// struct A;
+///
+/// #[derive(Component)]
+/// struct B;
+///
+/// let mut world = World::new();
+/// let entity = world.spawn(MaybeChain((Some(A), None::<B>))).id();
+/// world.flush();
+///
+/// assert!(world.entity(entity).contains::<A>());
+/// assert!(!world.entity(entity).contains::<B>());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct MaybeChain<T: OptionalBundles>(pub T);
 
-    #[derive(Component)]
-    struct A;
+/// Derives conversion of a struct whose fields are all [`Option`]s of bundles into a [`MaybeChain`] component.
+///
+/// This makes it easy to define a "partial bundle", whose fields are each inserted only if they are [`Some`].
+/// The derive generates a `From` implementation and an `into_maybe_chain` method,
+/// which produce a [`MaybeChain`] of the fields in declaration order.
+/// As with [`MaybeChain`], structs with up to 8 fields are supported, and both named and tuple structs can be used.
+///
+/// This requires the `derive` feature.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::MaybeBundle;
+///
+/// #[derive(Component)]
+/// struct Health(u32);
+///
+/// #[derive(Component)]
+/// struct Shield(u32);
+///
+/// #[derive(MaybeBundle)]
+/// struct Defenses {
+///     health: Option<Health>,
+///     shield: Option<Shield>,
+/// }
+///
+/// let mut world = World::new();
+/// let defenses = Defenses {
+///     health: Some(Health(100)),
+///     shield: None,
+/// };
+/// let entity = world.spawn(defenses.into_maybe_chain()).id();
+/// world.flush();
+///
+/// assert!(world.entity(entity).contains::<Health>());
+/// assert!(!world.entity(entity).contains::<Shield>());
+/// ```
+#[cfg(feature = "derive")]
+pub use i_cant_believe_its_not_bsn_macros::MaybeBundle;
 
-    #[derive(Bundle)]
-    struct TestBundle {
-        maybe_a: Maybe<A>,
+/// A tuple of optional bundles, which can be stored in a [`MaybeChain`].
+///
+/// This trait is implemented for tuples of up to 8 `Option<B: Bundle>` values.
+pub trait OptionalBundles: Send + Sync + 'static {
+    /// Inserts each [`Some`] bundle into the entity, in order.
+    fn insert_into(self, entity_mut: &mut EntityWorldMut);
+}
+
+macro_rules! impl_optional_bundles {
+    ($($name:ident),*) => {
+        impl<$($name: Bundle),*> OptionalBundles for ($(Option<$name>,)*) {
+            #[allow(non_snake_case)]
+            fn insert_into(self, entity_mut: &mut EntityWorldMut) {
+                let ($($name,)*) = self;
+                $(
+                    if let Some(bundle) = $name {
+                        entity_mut.insert(bundle);
+                    }
+                )*
+            }
+        }
+    };
+}
+
+impl_optional_bundles!(B0);
+impl_optional_bundles!(B0, B1);
+impl_optional_bundles!(B0, B1, B2);
+impl_optional_bundles!(B0, B1, B2, B3);
+impl_optional_bundles!(B0, B1, B2, B3, B4);
+impl_optional_bundles!(B0, B1, B2, B3, B4, B5);
+impl_optional_bundles!(B0, B1, B2, B3, B4, B5, B6);
+impl_optional_bundles!(B0, B1, B2, B3, B4, B5, B6, B7);
+
+impl<T: OptionalBundles> Component for MaybeChain<T> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(maybe_chain_hook::<T>);
     }
+}
 
-    #[test]
-    fn maybe_some() {
-        let mut world = World::new();
-        let entity = world
-            .spawn(TestBundle {
-                maybe_a: Maybe::new(A),
-            })
-            .id();
+/// A hook that runs whenever [`MaybeChain`] is added to an entity.
+///
+/// Generates a [`MaybeChainCommand`].
+fn maybe_chain_hook<T: OptionalBundles>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        MaybeChainCommand::<T>::new,
+    );
+}
 
-        // FIXME: this should not be needed!
-        world.flush();
+/// A [`Command`] that resolves the [`MaybeChain<T>`] component of an entity,
+/// inserting each of its [`Some`] bundles in order.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct MaybeChainCommand<T> {
+    /// The entity holding the [`MaybeChain`] component.
+    pub entity: Entity,
+    _phantom: PhantomData<T>,
+}
 
-        assert!(world.get::<A>(entity).is_some());
-        assert!(world.get::<Maybe<A>>(entity).is_none());
+impl<T> MaybeChainCommand<T> {
+    /// Creates a new [`MaybeChainCommand`] targeting the given entity.
+    pub const fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _phantom: PhantomData,
+        }
     }
+}
 
-    #[test]
-    fn maybe_none() {
-        let mut world = World::new();
-        let entity = world
-            .spawn(TestBundle {
-                maybe_a: Maybe::NONE,
-            })
-            .id();
+impl<T: OptionalBundles> Command for MaybeChainCommand<T> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.entity) else {
+            on_missing(world, WithChildError::EntityMissing(self.entity));
+            return;
+        };
 
-        // FIXME: this should not be needed!
-        world.flush();
+        let Some(maybe_chain_component) = entity_mut.take::<MaybeChain<T>>() else {
+            on_cancelled("MaybeChain");
+            return;
+        };
 
-        assert!(world.get::<A>(entity).is_none());
-        assert!(world.get::<Maybe<A>>(entity).is_none());
+        maybe_chain_component.0.insert_into(&mut entity_mut);
+    }
+}
+
+/// A component that, when added to an entity, will be removed from the entity and replaced with
+/// whichever of its two bundles is present.
+///
+/// This is useful for mutually exclusive variants, such as enabled and disabled states,
+/// as unlike a pair of [`Maybe`] components, both bundles can never be inserted at once.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::Either;
+///
+/// #[derive(Component)]
+/// struct Enabled;
+///
+/// #[derive(Component)]
+/// struct Disabled;
+///
+/// let mut world = World::new();
+/// let is_enabled = false;
+/// let button = if is_enabled {
+///     Either::left(Enabled)
+/// } else {
+///     Either::right(Disabled)
+/// };
+/// let entity = world.spawn(button).id();
+/// world.flush();
+///
+/// assert!(world.entity(entity).contains::<Disabled>());
+/// assert!(!world.entity(entity).contains::<Enabled>());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Either<L: Bundle, R: Bundle>(pub Result<L, R>);
+
+impl<L: Bundle, R: Bundle> Either<L, R> {
+    /// Creates a new `Either` component that will insert the left bundle.
+    pub const fn left(bundle: L) -> Self {
+        Self(Ok(bundle))
+    }
+
+    /// Creates a new `Either` component that will insert the right bundle.
+    pub const fn right(bundle: R) -> Self {
+        Self(Err(bundle))
+    }
+
+    /// Returns the contents of the `Either` component.
+    pub fn into_inner(self) -> Result<L, R> {
+        self.0
+    }
+}
+
+impl<L: Bundle, R: Bundle> Component for Either<L, R> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(either_hook::<L, R>);
+    }
+}
+
+/// A hook that runs whenever [`Either`] is added to an entity.
+///
+/// Generates an [`EitherCommand`].
+fn either_hook<L: Bundle, R: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(&mut world, component_id, entity, EitherCommand::<L, R>::new);
+}
+
+/// A [`Command`] that resolves the [`Either<L, R>`] component of an entity,
+/// inserting whichever bundle it holds.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct EitherCommand<L, R> {
+    /// The entity holding the [`Either`] component.
+    pub entity: Entity,
+    _phantom: PhantomData<(L, R)>,
+}
+
+impl<L, R> EitherCommand<L, R> {
+    /// Creates a new [`EitherCommand`] targeting the given entity.
+    pub const fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<L: Bundle, R: Bundle> Command for EitherCommand<L, R> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.entity) else {
+            on_missing(world, WithChildError::EntityMissing(self.entity));
+            return;
+        };
+
+        let Some(either_component) = entity_mut.take::<Either<L, R>>() else {
+            on_cancelled("Either");
+            return;
+        };
+
+        match either_component.into_inner() {
+            Ok(left) => entity_mut.insert(left),
+            Err(right) => entity_mut.insert(right),
+        };
+    }
+}
+
+/// A component that, when added to an entity, will be removed from the entity and replaced with its bundle,
+/// but only if the entity has a `T` component when the command is applied.
+///
+/// This allows shared templates to include add-ons that only apply to entities marked elsewhere.
+/// If the entity does not have a `T` component, the bundle is dropped.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::MaybeIfPresent;
+///
+/// #[derive(Component)]
+/// struct Boss;
+///
+/// #[derive(Component)]
+/// struct HealthBar;
+///
+/// fn enemy_template() -> impl Bundle {
+///     MaybeIfPresent::<Boss, HealthBar>::new(HealthBar)
+/// }
+///
+/// let mut world = World::new();
+/// let boss = world.spawn((Boss, enemy_template())).id();
+/// let minion = world.spawn(enemy_template()).id();
+/// world.flush();
+///
+/// assert!(world.entity(boss).contains::<HealthBar>());
+/// assert!(!world.entity(minion).contains::<HealthBar>());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaybeIfPresent<T: Component, B: Bundle>(pub B, pub PhantomData<T>);
+
+impl<T: Component, B: Bundle> MaybeIfPresent<T, B> {
+    /// Creates a new [`MaybeIfPresent`] component, which inserts `bundle` if a `T` component is present.
+    pub const fn new(bundle: B) -> Self {
+        Self(bundle, PhantomData)
+    }
+}
+
+impl<T: Component, B: Bundle> Component for MaybeIfPresent<T, B> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(maybe_if_present_hook::<T, B>);
+    }
+}
+
+/// A hook that runs whenever [`MaybeIfPresent`] is added to an entity.
+///
+/// Generates a [`MaybeIfPresentCommand`].
+fn maybe_if_present_hook<T: Component, B: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        MaybeIfPresentCommand::<T, B>::new,
+    );
+}
+
+/// A [`Command`] that resolves the [`MaybeIfPresent<T, B>`] component of an entity,
+/// inserting its bundle only if the entity has a `T` component.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct MaybeIfPresentCommand<T, B> {
+    /// The entity holding the [`MaybeIfPresent`] component.
+    pub entity: Entity,
+    _phantom: PhantomData<(T, B)>,
+}
+
+impl<T, B> MaybeIfPresentCommand<T, B> {
+    /// Creates a new [`MaybeIfPresentCommand`] targeting the given entity.
+    pub const fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: Component, B: Bundle> Command for MaybeIfPresentCommand<T, B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.entity) else {
+            on_missing(world, WithChildError::EntityMissing(self.entity));
+            return;
+        };
+
+        let Some(MaybeIfPresent(bundle, _)) = entity_mut.take::<MaybeIfPresent<T, B>>() else {
+            on_cancelled("MaybeIfPresent");
+            return;
+        };
+
+        if entity_mut.contains::<T>() {
+            entity_mut.insert(bundle);
+        }
+    }
+}
+
+/// A component that, when added to an entity, will be removed from the entity,
+/// and will also remove the component `C` if its flag is `true`.
+///
+/// This is the counterpart to [`Maybe`], allowing templates to conditionally strip a component
+/// that was added by default elsewhere.
+/// If the entity does not have a `C` component, nothing happens.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::MaybeRemove;
+///
+/// #[derive(Component)]
+/// struct Visible;
+///
+/// let mut world = World::new();
+/// let hidden = world.spawn((Visible, MaybeRemove::<Visible>::new(true))).id();
+/// let shown = world.spawn((Visible, MaybeRemove::<Visible>::new(false))).id();
+/// world.flush();
+///
+/// assert!(!world.entity(hidden).contains::<Visible>());
+/// assert!(world.entity(shown).contains::<Visible>());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaybeRemove<C: Component>(pub bool, pub PhantomData<C>);
+
+impl<C: Component> MaybeRemove<C> {
+    /// Creates a new [`MaybeRemove`] component, which removes `C` if and only if `condition` is `true`.
+    pub const fn new(condition: bool) -> Self {
+        Self(condition, PhantomData)
+    }
+}
+
+impl<C: Component> Component for MaybeRemove<C> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(maybe_remove_hook::<C>);
+    }
+}
+
+/// A hook that runs whenever [`MaybeRemove`] is added to an entity.
+///
+/// Generates a [`MaybeRemoveCommand`].
+fn maybe_remove_hook<C: Component>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        MaybeRemoveCommand::<C>::new,
+    );
+}
+
+/// A [`Command`] that resolves the [`MaybeRemove<C>`] component of an entity,
+/// removing `C` if requested.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct MaybeRemoveCommand<C> {
+    /// The entity holding the [`MaybeRemove`] component.
+    pub entity: Entity,
+    _phantom: PhantomData<C>,
+}
+
+impl<C> MaybeRemoveCommand<C> {
+    /// Creates a new [`MaybeRemoveCommand`] targeting the given entity.
+    pub const fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<C: Component> Command for MaybeRemoveCommand<C> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.entity) else {
+            on_missing(world, WithChildError::EntityMissing(self.entity));
+            return;
+        };
+
+        let Some(MaybeRemove(condition, _)) = entity_mut.take::<MaybeRemove<C>>() else {
+            on_cancelled("MaybeRemove");
+            return;
+        };
+
+        if condition {
+            entity_mut.remove::<C>();
+        }
+    }
+}
+
+/// A component that, when added to an entity, will be removed from the entity,
+/// and if [`Some`], will insert its bundle and make the entity a child of the given parent.
+///
+/// If [`None`], the entity stays a root with no extra components.
+/// This supports templates where an entity may or may not be parented depending on runtime state.
+///
+/// The parent entity must exist when the command is applied:
+/// if it does not, the [`FailurePolicy`](crate::FailurePolicy) is followed, and the bundle is not inserted.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Parent;
+/// use i_cant_believe_its_not_bsn::MaybeChild;
+///
+/// #[derive(Component)]
+/// struct Docked;
+///
+/// let mut world = World::new();
+/// let dock = world.spawn_empty().id();
+/// let window = world.spawn(MaybeChild(Some((dock, Docked)))).id();
+/// let floating = world.spawn(MaybeChild::<Docked>(None)).id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Parent>(window).unwrap().get(), dock);
+/// assert!(world.get::<Parent>(floating).is_none());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MaybeChild<B: Bundle>(pub Option<(Entity, B)>);
+
+/// Remaps the stored parent entity, if any,
+/// so that a [`MaybeChild`] loaded from a scene or network snapshot refers to the parent in the new world.
+impl<B: Bundle> MapEntities for MaybeChild<B> {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        if let Some((parent_entity, _)) = &mut self.0 {
+            *parent_entity = entity_mapper.map_entity(*parent_entity);
+        }
+    }
+}
+
+impl<B: Bundle> Component for MaybeChild<B> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(maybe_child_hook::<B>);
+    }
+}
+
+/// A hook that runs whenever [`MaybeChild`] is added to an entity.
+///
+/// Generates a [`MaybeChildCommand`].
+fn maybe_child_hook<B: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        MaybeChildCommand::<B>::new,
+    );
+}
+
+/// A [`Command`] that resolves the [`MaybeChild<B>`] component of an entity,
+/// inserting its bundle and attaching the entity to its parent if it is [`Some`].
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct MaybeChildCommand<B> {
+    /// The entity holding the [`MaybeChild`] component, which may become a child.
+    pub entity: Entity,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> MaybeChildCommand<B> {
+    /// Creates a new [`MaybeChildCommand`] targeting the given entity.
+    pub const fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle> Command for MaybeChildCommand<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.entity) else {
+            on_missing(world, WithChildError::EntityMissing(self.entity));
+            return;
+        };
+
+        let Some(MaybeChild(maybe_parent)) = entity_mut.take::<MaybeChild<B>>() else {
+            on_cancelled("MaybeChild");
+            return;
+        };
+
+        let Some((parent_entity, bundle)) = maybe_parent else {
+            return;
+        };
+
+        if world.get_entity(parent_entity).is_none() {
+            on_missing(world, WithChildError::ParentMissing(parent_entity));
+            return;
+        }
+
+        world.entity_mut(self.entity).insert(bundle);
+        Link::link_child(world, parent_entity, self.entity);
+    }
+}
+
+/// A component that, when added to an entity, will be removed from the entity,
+/// and if [`Some`], will insert its bundle onto the entity's first child.
+///
+/// This supports templates that conditionally decorate a known child, without needing its [`Entity`] up front.
+/// The child is looked up when the command is applied, so it can be spawned by a [`WithChild`](crate::WithChild)
+/// in the same bundle, as long as that component comes first.
+///
+/// If the entity has no children, nothing happens.
+/// If it has more than one, the first child is targeted, and a warning is logged in debug builds,
+/// as this is usually a sign that the wrong entity is being decorated.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{MaybeOnChild, WithChild};
+///
+/// #[derive(Component)]
+/// struct Label;
+///
+/// #[derive(Component)]
+/// struct Highlighted;
+///
+/// let mut world = World::new();
+/// let is_selected = true;
+/// let button = world
+///     .spawn((WithChild(Label), MaybeOnChild(is_selected.then_some(Highlighted))))
+///     .id();
+/// world.flush();
+///
+/// let label = world.get::<Children>(button).unwrap()[0];
+/// assert!(world.entity(label).contains::<Highlighted>());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MaybeOnChild<B: Bundle>(pub Option<B>);
+
+impl<B: Bundle> Component for MaybeOnChild<B> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(maybe_on_child_hook::<B>);
+    }
+}
+
+/// A hook that runs whenever [`MaybeOnChild`] is added to an entity.
+///
+/// Generates a [`MaybeOnChildCommand`].
+fn maybe_on_child_hook<B: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        MaybeOnChildCommand::<B>::new,
+    );
+}
+
+/// A [`Command`] that resolves the [`MaybeOnChild<B>`] component of an entity,
+/// inserting its bundle onto the entity's first child if it is [`Some`].
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct MaybeOnChildCommand<B> {
+    /// The entity holding the [`MaybeOnChild`] component, whose first child may be modified.
+    pub entity: Entity,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> MaybeOnChildCommand<B> {
+    /// Creates a new [`MaybeOnChildCommand`] targeting the given entity.
+    pub const fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle> Command for MaybeOnChildCommand<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.entity) else {
+            on_missing(world, WithChildError::EntityMissing(self.entity));
+            return;
+        };
+
+        let Some(MaybeOnChild(maybe_bundle)) = entity_mut.take::<MaybeOnChild<B>>() else {
+            on_cancelled("MaybeOnChild");
+            return;
+        };
+
+        let Some(bundle) = maybe_bundle else {
+            return;
+        };
+
+        let children = Link::children(world, self.entity);
+        let Some(&first_child) = children.first() else {
+            return;
+        };
+
+        #[cfg(debug_assertions)]
+        if children.len() > 1 {
+            warn!(
+                "MaybeOnChild<{}> on {:?} has {} children: only the first, {first_child:?}, is modified",
+                core::any::type_name::<B>(),
+                self.entity,
+                children.len()
+            );
+        }
+
+        world.entity_mut(first_child).insert(bundle);
+    }
+}
+
+/// A component that, when added to an entity, will be removed from the entity and replaced with its bundle if [`Some`],
+/// or will call its fallback closure on the entity if [`None`].
+///
+/// This expresses an if/else declaratively in a single component:
+/// the fallback has mutable access to the entity, and can insert an alternative bundle or perform any other modification.
+/// The fallback is dropped without being called if the bundle is [`Some`].
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::MaybeOrElse;
+///
+/// #[derive(Component)]
+/// struct Interactable;
+///
+/// #[derive(Component)]
+/// struct Disabled;
+///
+/// let mut world = World::new();
+/// let is_enabled = false;
+/// let button = world
+///     .spawn(MaybeOrElse(is_enabled.then_some(Interactable), |entity_mut: &mut EntityWorldMut| {
+///         entity_mut.insert(Disabled);
+///     }))
+///     .id();
+/// world.flush();
+///
+/// assert!(world.entity(button).contains::<Disabled>());
+/// assert!(!world.entity(button).contains::<Interactable>());
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct MaybeOrElse<B: Bundle, F: FnOnce(&mut EntityWorldMut)>(pub Option<B>, pub F);
+
+impl<B: Bundle, F: FnOnce(&mut EntityWorldMut) + Send + Sync + 'static> Component
+    for MaybeOrElse<B, F>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(maybe_or_else_hook::<B, F>);
+    }
+}
+
+/// A hook that runs whenever [`MaybeOrElse`] is added to an entity.
+///
+/// Generates a [`MaybeOrElseCommand`].
+fn maybe_or_else_hook<B: Bundle, F: FnOnce(&mut EntityWorldMut) + Send + Sync + 'static>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        MaybeOrElseCommand::<B, F>::new,
+    );
+}
+
+/// A [`Command`] that resolves the [`MaybeOrElse<B, F>`] component of an entity,
+/// inserting its bundle if it is [`Some`], or calling its fallback otherwise.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct MaybeOrElseCommand<B, F> {
+    /// The entity holding the [`MaybeOrElse`] component.
+    pub entity: Entity,
+    _phantom: PhantomData<(B, F)>,
+}
+
+impl<B, F> MaybeOrElseCommand<B, F> {
+    /// Creates a new [`MaybeOrElseCommand`] targeting the given entity.
+    pub const fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, F: FnOnce(&mut EntityWorldMut) + Send + Sync + 'static> Command
+    for MaybeOrElseCommand<B, F>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.entity) else {
+            on_missing(world, WithChildError::EntityMissing(self.entity));
+            return;
+        };
+
+        let Some(MaybeOrElse(maybe_bundle, fallback)) = entity_mut.take::<MaybeOrElse<B, F>>()
+        else {
+            on_cancelled("MaybeOrElse");
+            return;
+        };
+
+        match maybe_bundle {
+            Some(bundle) => {
+                entity_mut.insert(bundle);
+            }
+            None => fallback(&mut entity_mut),
+        }
+    }
+}
+
+impl<B: Bundle> Maybe<B> {
+    /// Attaches a finalizer to this `Maybe`, which runs on the entity once the `Maybe` has been resolved,
+    /// whether or not the bundle was inserted.
+    ///
+    /// This centralizes post-resolution side effects, such as logging or bookkeeping.
+    /// The finalizer can observe the inserted bundle, if any.
+    ///
+    /// ```rust
+    /// use bevy_ecs::prelude::*;
+    /// use i_cant_believe_its_not_bsn::Maybe;
+    ///
+    /// #[derive(Component)]
+    /// struct Armor(u32);
+    ///
+    /// let mut world = World::new();
+    /// world.spawn(Maybe::new(Armor(5)).with_finalizer(|entity_mut: &mut EntityWorldMut| {
+    ///     let armor = entity_mut.get::<Armor>().map_or(0, |armor| armor.0);
+    ///     println!("Spawned {:?} with {armor} armor", entity_mut.id());
+    /// }));
+    /// world.flush();
+    /// ```
+    pub fn with_finalizer<F: FnOnce(&mut EntityWorldMut)>(
+        self,
+        finalizer: F,
+    ) -> MaybeWithFinalizer<B, F> {
+        MaybeWithFinalizer(self, finalizer)
+    }
+}
+
+/// A component that resolves its [`Maybe`] just like a standalone [`Maybe`] component would,
+/// then calls its finalizer on the entity.
+///
+/// The finalizer only runs if the entity still exists when the command is applied:
+/// otherwise, the [`FailurePolicy`](crate::FailurePolicy) is followed.
+///
+/// Usually constructed via [`Maybe::with_finalizer`].
+#[derive(Clone, Copy, Debug)]
+pub struct MaybeWithFinalizer<B: Bundle, F: FnOnce(&mut EntityWorldMut)>(pub Maybe<B>, pub F);
+
+impl<B: Bundle, F: FnOnce(&mut EntityWorldMut) + Send + Sync + 'static> Component
+    for MaybeWithFinalizer<B, F>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(maybe_with_finalizer_hook::<B, F>);
+    }
+}
+
+/// A hook that runs whenever [`MaybeWithFinalizer`] is added to an entity.
+///
+/// Generates a [`MaybeWithFinalizerCommand`].
+fn maybe_with_finalizer_hook<B: Bundle, F: FnOnce(&mut EntityWorldMut) + Send + Sync + 'static>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        MaybeWithFinalizerCommand::<B, F>::new,
+    );
+}
+
+/// A [`Command`] that resolves the [`MaybeWithFinalizer<B, F>`] component of an entity,
+/// inserting its bundle if it is [`Some`], then calling its finalizer.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct MaybeWithFinalizerCommand<B, F> {
+    /// The entity holding the [`MaybeWithFinalizer`] component.
+    pub entity: Entity,
+    _phantom: PhantomData<(B, F)>,
+}
+
+impl<B, F> MaybeWithFinalizerCommand<B, F> {
+    /// Creates a new [`MaybeWithFinalizerCommand`] targeting the given entity.
+    pub const fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, F: FnOnce(&mut EntityWorldMut) + Send + Sync + 'static> Command
+    for MaybeWithFinalizerCommand<B, F>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.entity) else {
+            on_missing(world, WithChildError::EntityMissing(self.entity));
+            return;
+        };
+
+        let Some(MaybeWithFinalizer(maybe_component, finalizer)) =
+            entity_mut.take::<MaybeWithFinalizer<B, F>>()
+        else {
+            on_cancelled("MaybeWithFinalizer");
+            return;
+        };
+
+        if let Some(bundle) = maybe_component.into_inner() {
+            entity_mut.insert(bundle);
+        }
+        finalizer(&mut entity_mut);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Component)]
+    struct A;
+
+    #[derive(Bundle)]
+    struct TestBundle {
+        maybe_a: Maybe<A>,
+    }
+
+    #[test]
+    fn maybe_some() {
+        let mut world = World::new();
+        let entity = world
+            .spawn(TestBundle {
+                maybe_a: Maybe::new(A),
+            })
+            .id();
+
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(world.get::<A>(entity).is_some());
+        assert!(world.get::<Maybe<A>>(entity).is_none());
+    }
+
+    #[test]
+    fn observers_see_resolved_maybe() {
+        #[derive(Component)]
+        struct B;
+
+        #[derive(Component)]
+        struct Marker;
+
+        /// Records, for each observer, whether the entity had `A` and `B` when it ran.
+        #[derive(Resource, Default)]
+        struct Seen(Vec<(&'static str, bool, bool)>);
+
+        let mut world = World::new();
+        world.init_resource::<Seen>();
+        world.observe(
+            |trigger: Trigger<OnAdd, Marker>,
+             query: Query<(Has<A>, Has<B>)>,
+             mut seen: ResMut<Seen>| {
+                let (a, b) = query.get(trigger.entity()).unwrap();
+                seen.0.push(("marker", a, b));
+            },
+        );
+        world.observe(
+            |trigger: Trigger<OnAdd, B>, query: Query<(Has<A>, Has<B>)>, mut seen: ResMut<Seen>| {
+                let (a, b) = query.get(trigger.entity()).unwrap();
+                seen.0.push(("b", a, b));
+            },
+        );
+
+        world.spawn((Marker, Maybe::new(A), Maybe::new(B)));
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(
+            world.resource::<Seen>().0,
+            [("marker", false, false), ("b", true, true)]
+        );
+    }
+
+    #[test]
+    fn maybe_none() {
+        let mut world = World::new();
+        let entity = world
+            .spawn(TestBundle {
+                maybe_a: Maybe::NONE,
+            })
+            .id();
+
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(world.get::<A>(entity).is_none());
+        assert!(world.get::<Maybe<A>>(entity).is_none());
+    }
+
+    #[test]
+    fn maybe_from_bool() {
+        let mut world = World::new();
+        let entity_true = world.spawn(Maybe::from_bool(true, A)).id();
+        let entity_false = world.spawn(maybe_if(false, A)).id();
+
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(world.get::<A>(entity_true).is_some());
+        assert!(world.get::<Maybe<A>>(entity_true).is_none());
+        assert!(world.get::<A>(entity_false).is_none());
+        assert!(world.get::<Maybe<A>>(entity_false).is_none());
+    }
+
+    #[test]
+    fn maybe_const_from_bool() {
+        #[derive(Component, Clone, Copy)]
+        struct C;
+
+        const SOME: Maybe<C> = Maybe::const_from_bool(true, C);
+        const NONE: Maybe<C> = Maybe::const_from_bool(false, C);
+
+        assert!(SOME.into_inner().is_some());
+        assert!(NONE.into_inner().is_none());
+    }
+
+    #[test]
+    fn maybe_take_and_replace() {
+        #[derive(Component, PartialEq, Debug)]
+        struct B(u8);
+
+        let mut world = World::new();
+        let mut query = world.query::<&mut Maybe<B>>();
+
+        // Modify the component before the command generated by its hook is applied
+        let replaced = world.spawn(Maybe::new(B(1))).id();
+        let mut maybe_b = query.get_mut(&mut world, replaced).unwrap();
+        assert_eq!(maybe_b.replace(B(3)), Some(B(1)));
+
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(world.get::<B>(replaced), Some(&B(3)));
+        assert!(world.get::<Maybe<B>>(replaced).is_none());
+
+        let taken = world.spawn(Maybe::new(B(2))).id();
+        let mut maybe_b = query.get_mut(&mut world, taken).unwrap();
+        assert_eq!(maybe_b.take(), Some(B(2)));
+        assert_eq!(maybe_b.take(), None);
+
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(world.get::<B>(taken), None);
+        assert!(world.get::<Maybe<B>>(taken).is_none());
+    }
+
+    #[test]
+    fn maybe_nested() {
+        #[derive(Component, PartialEq, Debug, Clone)]
+        struct B(u8);
+
+        let mut world = World::new();
+
+        let cases = [
+            (Maybe::new(Maybe::new(B(1))), Some(&B(1))),
+            (Maybe::new(Maybe::NONE), None),
+            (Maybe::NONE, None),
+        ];
+
+        for (nested, expected) in cases {
+            assert_eq!(nested.clone().flatten().0.as_ref(), expected);
+
+            let entity = world.spawn(nested).id();
+            // Both layers are resolved in a single flush
+            // FIXME: this should not be needed!
+            world.flush();
+
+            assert_eq!(world.get::<B>(entity), expected);
+            assert!(world.get::<Maybe<Maybe<B>>>(entity).is_none());
+            assert!(world.get::<Maybe<B>>(entity).is_none());
+        }
+    }
+
+    #[test]
+    fn maybe_chain() {
+        #[derive(Component)]
+        struct B(u8);
+
+        #[derive(Component)]
+        struct C;
+
+        #[derive(Component)]
+        struct D;
+
+        let mut world = World::new();
+        let entity = world
+            .spawn(MaybeChain((Some(A), None::<B>, Some((C, B(2))), None::<D>)))
+            .id();
+
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let entity_ref = world.entity(entity);
+        assert!(entity_ref.contains::<A>());
+        assert_eq!(entity_ref.get::<B>().unwrap().0, 2);
+        assert!(entity_ref.contains::<C>());
+        assert!(!entity_ref.contains::<D>());
+        assert!(
+            !entity_ref.contains::<MaybeChain<(Option<A>, Option<B>, Option<(C, B)>, Option<D>)>>()
+        );
+    }
+
+    #[test]
+    fn either() {
+        #[derive(Component)]
+        struct B;
+
+        let mut world = World::new();
+        let left = world.spawn(Either::<A, B>::left(A)).id();
+        let right = world.spawn(Either::<A, B>::right(B)).id();
+
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(world.entity(left).contains::<A>());
+        assert!(!world.entity(left).contains::<B>());
+        assert!(!world.entity(left).contains::<Either<A, B>>());
+
+        assert!(!world.entity(right).contains::<A>());
+        assert!(world.entity(right).contains::<B>());
+        assert!(!world.entity(right).contains::<Either<A, B>>());
+    }
+
+    #[test]
+    fn maybe_if_present() {
+        #[derive(Component)]
+        struct Trigger;
+
+        let mut world = World::new();
+        let triggered = world
+            .spawn((Trigger, MaybeIfPresent::<Trigger, A>::new(A)))
+            .id();
+        let untriggered = world.spawn(MaybeIfPresent::<Trigger, A>::new(A)).id();
+
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(world.entity(triggered).contains::<A>());
+        assert!(!world
+            .entity(triggered)
+            .contains::<MaybeIfPresent<Trigger, A>>());
+
+        assert!(!world.entity(untriggered).contains::<A>());
+        assert!(!world
+            .entity(untriggered)
+            .contains::<MaybeIfPresent<Trigger, A>>());
+    }
+
+    #[test]
+    fn maybe_remove() {
+        #[derive(Component, PartialEq, Debug)]
+        struct B(u8);
+
+        let mut world = World::new();
+        let removed = world.spawn((B(1), MaybeRemove::<B>::new(true))).id();
+        let kept = world.spawn((B(2), MaybeRemove::<B>::new(false))).id();
+        // Removing an absent component is a no-op
+        let absent = world.spawn(MaybeRemove::<B>::new(true)).id();
+
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(world.get::<B>(removed), None);
+        assert_eq!(world.get::<B>(kept), Some(&B(2)));
+        assert_eq!(world.get::<B>(absent), None);
+        for entity in [removed, kept, absent] {
+            assert!(!world.entity(entity).contains::<MaybeRemove<B>>());
+        }
+    }
+
+    #[test]
+    fn maybe_command_idempotent() {
+        #[derive(Component, PartialEq, Debug)]
+        struct Counter(u8);
+
+        let mut world = World::new();
+        world.insert_resource(crate::FailurePolicy::Panic);
+
+        let entity = world.spawn(Counter(0)).id();
+        world.commands().add(MaybeCommand::<Counter>::new(entity));
+        world.commands().add(MaybeCommand::<Counter>::new(entity));
+        // Inserting queues a third command via the hook
+        world.entity_mut(entity).insert(Maybe::new(Counter(1)));
+        world.flush();
+
+        assert_eq!(world.get::<Counter>(entity), Some(&Counter(1)));
+        assert!(!world.entity(entity).contains::<Maybe<Counter>>());
+    }
+
+    #[test]
+    fn maybe_or_else() {
+        #[derive(Component, PartialEq, Debug)]
+        struct B(u8);
+
+        #[derive(Component)]
+        struct Fallback;
+
+        fn insert_fallback(entity_mut: &mut EntityWorldMut) {
+            entity_mut.insert(Fallback);
+        }
+
+        let mut world = World::new();
+        let some = world.spawn(MaybeOrElse(Some(B(1)), insert_fallback)).id();
+        let none = world.spawn(MaybeOrElse(None::<B>, insert_fallback)).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(world.get::<B>(some), Some(&B(1)));
+        assert!(!world.entity(some).contains::<Fallback>());
+
+        assert!(world.get::<B>(none).is_none());
+        assert!(world.entity(none).contains::<Fallback>());
+    }
+
+    #[test]
+    fn maybe_with_finalizer() {
+        #[derive(Component, PartialEq, Debug)]
+        struct B(u8);
+
+        #[derive(Component, PartialEq, Debug)]
+        struct Observed(Option<u8>);
+
+        fn observe_b(entity_mut: &mut EntityWorldMut) {
+            let observed = Observed(entity_mut.get::<B>().map(|b| b.0));
+            entity_mut.insert(observed);
+        }
+
+        let mut world = World::new();
+        let some = world.spawn(Maybe::new(B(7)).with_finalizer(observe_b)).id();
+        let none = world.spawn(Maybe::<B>::NONE.with_finalizer(observe_b)).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(world.get::<Observed>(some), Some(&Observed(Some(7))));
+        assert_eq!(world.get::<Observed>(none), Some(&Observed(None)));
+        assert!(world.get::<B>(none).is_none());
+    }
+
+    #[test]
+    fn maybe_child() {
+        use crate::deps::bevy_hierarchy::{Children, Parent};
+
+        let mut world = World::new();
+        let parent = world.spawn_empty().id();
+        let child = world.spawn(MaybeChild(Some((parent, A)))).id();
+        let root = world.spawn(MaybeChild::<A>(None)).id();
+
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(world.entity(child).contains::<A>());
+        assert_eq!(world.get::<Parent>(child).unwrap().get(), parent);
+        assert_eq!(&**world.get::<Children>(parent).unwrap(), &[child]);
+        assert!(!world.entity(child).contains::<MaybeChild<A>>());
+
+        assert!(!world.entity(root).contains::<A>());
+        assert!(world.get::<Parent>(root).is_none());
+        assert!(!world.entity(root).contains::<MaybeChild<A>>());
+    }
+
+    #[test]
+    fn maybe_on_child() {
+        use crate::deps::bevy_hierarchy::Children;
+        use crate::WithChild;
+
+        #[derive(Component, PartialEq, Debug)]
+        struct Label;
+
+        let mut world = World::new();
+        let parent = world.spawn((WithChild(Label), MaybeOnChild(Some(A)))).id();
+        let childless = world.spawn(MaybeOnChild(Some(A))).id();
+        let unset = world
+            .spawn((WithChild(Label), MaybeOnChild::<A>(None)))
+            .id();
+
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let child = world.get::<Children>(parent).unwrap()[0];
+        assert!(world.entity(child).contains::<A>());
+        assert!(!world.entity(parent).contains::<A>());
+        assert!(!world.entity(parent).contains::<MaybeOnChild<A>>());
+
+        assert!(!world.entity(childless).contains::<A>());
+        assert!(!world.entity(childless).contains::<MaybeOnChild<A>>());
+
+        let unset_child = world.get::<Children>(unset).unwrap()[0];
+        assert!(!world.entity(unset_child).contains::<A>());
+    }
+
+    #[test]
+    fn maybe_child_map_entities() {
+        use crate::deps::bevy_hierarchy::{Children, Parent};
+
+        struct SwapMapper(Entity, Entity);
+
+        impl EntityMapper for SwapMapper {
+            fn map_entity(&mut self, entity: Entity) -> Entity {
+                if entity == self.0 {
+                    self.1
+                } else {
+                    entity
+                }
+            }
+        }
+
+        let mut world = World::new();
+        let old_parent = world.spawn_empty().id();
+        let new_parent = world.spawn_empty().id();
+
+        let mut maybe_child = MaybeChild(Some((old_parent, A)));
+        maybe_child.map_entities(&mut SwapMapper(old_parent, new_parent));
+
+        let child = world.spawn(maybe_child).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_eq!(world.get::<Parent>(child).unwrap().get(), new_parent);
+        assert!(world.get::<Children>(old_parent).is_none());
+
+        let mut root = MaybeChild::<A>(None);
+        root.map_entities(&mut SwapMapper(old_parent, new_parent));
+        assert!(root.0.is_none());
+    }
+
+    #[test]
+    fn maybe_commands_are_coalesced() {
+        use crate::OutstandingCommands;
+
+        #[derive(Component)]
+        struct B;
+
+        #[derive(Component)]
+        struct C;
+
+        let mut world = World::new();
+        // Resolving a first Maybe enables coalescing for this world
+        world.spawn(Maybe::new(A));
+        world.flush();
+        world.init_resource::<OutstandingCommands>();
+
+        let entity = world
+            .spawn((Maybe::new(A), Maybe::<B>::NONE, Maybe::new(C)))
+            .id();
+        // Later insertions before the next flush join the same batch
+        world.entity_mut(entity).insert(Maybe::new((A, B)));
+        assert_eq!(world.resource::<OutstandingCommands>().0, 1);
+
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(world.entity(entity).contains::<A>());
+        assert!(world.entity(entity).contains::<B>());
+        assert!(world.entity(entity).contains::<C>());
+        assert!(!crate::is_pending(&world, entity));
+        assert!(world.resource::<MaybeBatches>().0.is_empty());
+    }
+
+    #[test]
+    fn nested_maybe_in_batch() {
+        #[derive(Component)]
+        struct B;
+
+        let mut world = World::new();
+        world.spawn(Maybe::new(A));
+        world.flush();
+
+        // Resolving the outer Maybe adds another Maybe to the entity, which needs a batch of its own
+        let entity = world.spawn((Maybe::new(Maybe::new(A)), Maybe::new(B))).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(world.entity(entity).contains::<A>());
+        assert!(world.entity(entity).contains::<B>());
+        assert!(!crate::is_pending(&world, entity));
+    }
+
+    #[test]
+    #[should_panic(expected = "Entity not found")]
+    fn maybe_missing_entity() {
+        let mut world = World::new();
+        world.insert_resource(crate::FailurePolicy::Panic);
+
+        let entity = world.spawn_empty().id();
+        world.despawn(entity);
+        MaybeCommand::<A>::new(entity).apply(&mut world);
+    }
+
+    #[test]
+    #[should_panic(expected = "Parent entity not found")]
+    fn maybe_child_missing_parent() {
+        let mut world = World::new();
+        world.insert_resource(crate::FailurePolicy::Panic);
+
+        let parent = world.spawn_empty().id();
+        world.despawn(parent);
+        world.spawn(MaybeChild(Some((parent, A))));
+        world.flush();
     }
 
     #[test]
     fn maybe_system() {
-        use bevy_ecs::system::RunSystemOnce;
+        use crate::deps::bevy_ecs::system::RunSystemOnce;
 
         let mut world = World::new();
 
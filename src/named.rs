@@ -0,0 +1,333 @@
+use core::marker::PhantomData;
+use std::collections::{HashMap, HashSet};
+
+use crate::deps::bevy_ecs::{
+    component::{ComponentHooks, ComponentId, StorageType},
+    entity::{EntityMapper, MapEntities},
+    prelude::*,
+    world::{Command, DeferredWorld},
+};
+use crate::{
+    mark_deferred, on_cancelled, on_missing, queue_deferred, Link, LinkChild, WithChildError,
+};
+
+/// A component that records the entities of named children, keyed by name.
+///
+/// This is added to (or updated on) the parent entity by [`WithNamedChild`],
+/// allowing other systems to look up a particular child without knowing the order in which children were spawned.
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct NamedChildren(pub HashMap<&'static str, Entity>);
+
+/// Remaps each recorded child entity, so that [`NamedChildren`] remains valid when loaded into a different world.
+impl MapEntities for NamedChildren {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        for child_entity in self.0.values_mut() {
+            *child_entity = entity_mapper.map_entity(*child_entity);
+        }
+    }
+}
+
+impl NamedChildren {
+    /// Returns the entity of the child with the given name, if any.
+    pub fn get(&self, name: &str) -> Option<Entity> {
+        self.0.get(name).copied()
+    }
+}
+
+/// A component that, when added to an entity, will add a child entity with the given bundle,
+/// and record it in the parent's [`NamedChildren`] under the given name.
+///
+/// Otherwise, this behaves just like [`WithChild`](crate::WithChild).
+/// If a child with the same name has already been recorded, the record is overwritten.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::{NamedChildren, WithNamedChild};
+///
+/// #[derive(Component)]
+/// struct Header;
+///
+/// #[derive(Component)]
+/// struct Footer;
+///
+/// let mut world = World::new();
+/// let parent = world
+///     .spawn((WithNamedChild("header", Header), WithNamedChild("footer", Footer)))
+///     .id();
+/// world.flush();
+///
+/// let header = world.get::<NamedChildren>(parent).unwrap().get("header").unwrap();
+/// assert!(world.entity(header).contains::<Header>());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WithNamedChild<B: Bundle>(pub &'static str, pub B);
+
+impl<B: Bundle> Component for WithNamedChild<B> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_named_child_hook::<B>);
+    }
+}
+
+/// A hook that runs whenever [`WithNamedChild`] is added to an entity.
+///
+/// Generates a [`WithNamedChildCommand`].
+fn with_named_child_hook<B: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithNamedChildCommand::<B>::new,
+    );
+}
+
+/// A [`Command`] that spawns the child stored in the [`WithNamedChild<B>`] component of an entity,
+/// and records it in the parent's [`NamedChildren`].
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithNamedChildCommand<B> {
+    /// The entity holding the [`WithNamedChild`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> WithNamedChildCommand<B> {
+    /// Creates a new [`WithNamedChildCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle> Command for WithNamedChildCommand<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithNamedChild(name, bundle)) = entity_mut.take::<WithNamedChild<B>>() else {
+            on_cancelled("WithNamedChild");
+            return;
+        };
+
+        let child_entity = Link::spawn_child(world, bundle).id();
+        if !Link::link_child(world, self.parent_entity, child_entity) {
+            return;
+        }
+        let mut parent_mut = world.entity_mut(self.parent_entity);
+        record_named_child(&mut parent_mut, name, child_entity);
+    }
+}
+
+/// A component that, when added to an entity, will add a child entity for each named bundle generated by the iterator,
+/// and record each of them in the parent's [`NamedChildren`].
+///
+/// This is the plural form of [`WithNamedChild`].
+/// Duplicate names within the same iterator are a logic error: they cause a panic in debug builds,
+/// and later entries overwrite earlier ones in release builds.
+///
+/// Usually constructed via [`spawn_children_named`].
+#[derive(Debug, Clone, Default)]
+pub struct WithNamedChildren<B: Bundle, I: IntoIterator<Item = (&'static str, B)>>(pub I);
+
+impl<B: Bundle, I: IntoIterator<Item = (&'static str, B)> + Send + Sync + 'static> Component
+    for WithNamedChildren<B, I>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_named_children_hook::<B, I>);
+    }
+}
+
+/// A hook that runs whenever [`WithNamedChildren`] is added to an entity.
+///
+/// Generates a [`WithNamedChildrenCommand`].
+fn with_named_children_hook<
+    B: Bundle,
+    I: IntoIterator<Item = (&'static str, B)> + Send + Sync + 'static,
+>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithNamedChildrenCommand::<B, I>::new,
+    );
+}
+
+/// A [`Command`] that spawns the children generated by the [`WithNamedChildren<B, I>`] component of an entity,
+/// and records them in the parent's [`NamedChildren`].
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithNamedChildrenCommand<B, I> {
+    /// The entity holding the [`WithNamedChildren`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, I)>,
+}
+
+impl<B, I> WithNamedChildrenCommand<B, I> {
+    /// Creates a new [`WithNamedChildrenCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = (&'static str, B)> + Send + Sync + 'static> Command
+    for WithNamedChildrenCommand<B, I>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithNamedChildren(entries)) = entity_mut.take::<WithNamedChildren<B, I>>() else {
+            on_cancelled("WithNamedChildren");
+            return;
+        };
+
+        let mut seen_names = HashSet::new();
+        for (name, bundle) in entries {
+            let is_new_name = seen_names.insert(name);
+            debug_assert!(
+                is_new_name,
+                "Duplicate child name {name:?} in WithNamedChildren"
+            );
+
+            let child_entity = Link::spawn_child(world, bundle).id();
+            if !Link::link_child(world, self.parent_entity, child_entity) {
+                return;
+            }
+            let mut parent_mut = world.entity_mut(self.parent_entity);
+            record_named_child(&mut parent_mut, name, child_entity);
+        }
+    }
+}
+
+/// Creates a [`WithNamedChildren`] component, which spawns a child for each `(name, bundle)` entry,
+/// and records them in the parent's [`NamedChildren`].
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::{spawn_children_named, NamedChildren};
+///
+/// #[derive(Component)]
+/// struct Panel(u8);
+///
+/// let mut world = World::new();
+/// let parent = world
+///     .spawn(spawn_children_named([("left", Panel(0)), ("right", Panel(1))]))
+///     .id();
+/// world.flush();
+///
+/// let right = world.get::<NamedChildren>(parent).unwrap().get("right").unwrap();
+/// assert_eq!(world.get::<Panel>(right).unwrap().0, 1);
+/// ```
+pub fn spawn_children_named<B: Bundle, I: IntoIterator<Item = (&'static str, B)>>(
+    entries: I,
+) -> WithNamedChildren<B, I> {
+    WithNamedChildren(entries)
+}
+
+/// Records a named child in the parent's [`NamedChildren`], inserting the component if needed.
+fn record_named_child(parent_mut: &mut EntityWorldMut, name: &'static str, child_entity: Entity) {
+    match parent_mut.get_mut::<NamedChildren>() {
+        Some(mut named_children) => {
+            named_children.0.insert(name, child_entity);
+        }
+        None => {
+            parent_mut.insert(NamedChildren(HashMap::from([(name, child_entity)])));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::bevy_hierarchy::Children;
+
+    use super::*;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct A;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct B(u8);
+
+    #[test]
+    fn named_children() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn((WithNamedChild("a", A), WithNamedChild("b", B(2))))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world.entity(parent).contains::<WithNamedChild<A>>());
+        assert!(!world.entity(parent).contains::<WithNamedChild<B>>());
+
+        let named_children = world.get::<NamedChildren>(parent).unwrap();
+        let a = named_children.get("a").unwrap();
+        let b = named_children.get("b").unwrap();
+        assert_ne!(a, b);
+        assert_eq!(named_children.get("c"), None);
+
+        assert_eq!(world.get::<A>(a), Some(&A));
+        assert_eq!(world.get::<B>(b), Some(&B(2)));
+        assert_eq!(world.get::<Children>(parent).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn named_children_plural() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(spawn_children_named(vec![
+                ("zero", B(0)),
+                ("one", B(1)),
+                ("two", B(2)),
+            ]))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let named_children = world.get::<NamedChildren>(parent).unwrap();
+        assert_eq!(named_children.0.len(), 3);
+        for (name, value) in [("zero", 0), ("one", 1), ("two", 2)] {
+            let child = named_children.get(name).unwrap();
+            assert_eq!(world.get::<B>(child), Some(&B(value)));
+        }
+        assert_eq!(world.get::<Children>(parent).unwrap().len(), 3);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Duplicate child name")]
+    fn duplicate_names_panic() {
+        let mut world = World::default();
+
+        world.spawn(spawn_children_named(vec![("a", A), ("a", A)]));
+        world.flush();
+    }
+}
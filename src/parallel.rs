@@ -0,0 +1,200 @@
+use core::marker::PhantomData;
+
+use crate::deps::bevy_ecs::{
+    component::{ComponentHooks, ComponentId, StorageType},
+    prelude::*,
+    world::{Command, DeferredWorld},
+};
+use bevy_tasks::{ComputeTaskPool, TaskPool};
+
+use crate::{
+    mark_deferred, on_cancelled, on_missing, queue_deferred, Link, LinkChild, WithChildError,
+};
+
+/// A component that, when added to an entity, will add `count` children to it,
+/// building the bundle of each child from its index in parallel.
+///
+/// The bundles are built by calling the function on the [`ComputeTaskPool`],
+/// splitting the indices `0..count` into one contiguous chunk per thread.
+/// Once every bundle has been built, the children are spawned one after another, as spawning requires exclusive world access.
+/// This pays off when building each bundle is expensive, such as when generating meshes or running simulations;
+/// for cheap bundles, the cost of scheduling tasks outweighs the gain, and [`WithChildren`](crate::WithChildren) is faster.
+///
+/// Children are always spawned in index order, regardless of which thread built them or when they finished,
+/// so the entity's children end up in the same order as with `WithChildren((0..count).map(f))`.
+///
+/// If the [`ComputeTaskPool`] hasn't been initialized, such as when not using `TaskPoolPlugin`,
+/// it is initialized with the default settings.
+///
+/// This is only available with the `parallel` feature.
+///
+/// Usually constructed via [`spawn_children_parallel`].
+#[derive(Debug, Clone, Default)]
+pub struct WithChildrenParallel<B, F>(pub usize, pub F, pub PhantomData<B>);
+
+impl<B: Bundle, F: Fn(usize) -> B + Send + Sync + 'static> Component
+    for WithChildrenParallel<B, F>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_children_parallel_hook::<B, F>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildrenParallel`] is added to an entity.
+///
+/// Generates a [`WithChildrenParallelCommand`].
+fn with_children_parallel_hook<B: Bundle, F: Fn(usize) -> B + Send + Sync + 'static>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildrenParallelCommand::<B, F>::new,
+    );
+}
+
+/// A [`Command`] that builds the bundles of the [`WithChildrenParallel<B, F>`] component of an entity in parallel,
+/// then spawns them as children of that entity.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildrenParallelCommand<B, F> {
+    /// The entity holding the [`WithChildrenParallel`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, F)>,
+}
+
+impl<B, F> WithChildrenParallelCommand<B, F> {
+    /// Creates a new [`WithChildrenParallelCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, F: Fn(usize) -> B + Send + Sync + 'static> Command
+    for WithChildrenParallelCommand<B, F>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithChildrenParallel(count, f, _)) =
+            entity_mut.take::<WithChildrenParallel<B, F>>()
+        else {
+            on_cancelled("WithChildrenParallel");
+            return;
+        };
+
+        let bundles = build_parallel(count, &f);
+
+        Link::spawn_linked(world, self.parent_entity, bundles, |_| {});
+    }
+}
+
+/// Calls `f` for each index in `0..count` on the [`ComputeTaskPool`], returning the results in index order.
+fn build_parallel<B: Send + 'static, F: Fn(usize) -> B + Sync>(
+    count: usize,
+    f: &F,
+) -> impl Iterator<Item = B> {
+    let task_pool = ComputeTaskPool::get_or_init(TaskPool::default);
+    let chunk_size = count.div_ceil(task_pool.thread_num().max(1)).max(1);
+
+    // The results of a scope are returned in the order their tasks were spawned
+    let chunks = task_pool.scope(|scope| {
+        for start in (0..count).step_by(chunk_size) {
+            let end = (start + chunk_size).min(count);
+            scope.spawn(async move { (start..end).map(f).collect::<Vec<B>>() });
+        }
+    });
+    chunks.into_iter().flatten()
+}
+
+/// Creates a [`WithChildrenParallel`] component, which spawns `count` children,
+/// building the bundle of the child at each index with `f` in parallel.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::spawn_children_parallel;
+///
+/// #[derive(Component)]
+/// struct Chunk {
+///     heights: Vec<f32>,
+/// }
+///
+/// // Expensive to compute, but independent for each chunk
+/// fn generate_chunk(index: usize) -> Chunk {
+///     Chunk {
+///         heights: (0..256).map(|i| ((index * 256 + i) as f32).sin()).collect(),
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// let terrain = world.spawn(spawn_children_parallel(16, generate_chunk)).id();
+/// world.flush();
+///
+/// let children = world.get::<Children>(terrain).unwrap();
+/// assert_eq!(children.len(), 16);
+/// // Children are in index order
+/// assert_eq!(world.get::<Chunk>(children[1]).unwrap().heights[0], 256f32.sin());
+/// ```
+pub fn spawn_children_parallel<B: Bundle, F: Fn(usize) -> B + Send + Sync + 'static>(
+    count: usize,
+    f: F,
+) -> WithChildrenParallel<B, F> {
+    WithChildrenParallel(count, f, PhantomData)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_hierarchy;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct B(usize);
+
+    #[test]
+    fn children_are_in_index_order() {
+        let mut world = World::default();
+
+        let build: fn(usize) -> B = B;
+        let parent = world.spawn(spawn_children_parallel(100, build)).id();
+        assert!(world
+            .entity(parent)
+            .contains::<WithChildrenParallel<B, fn(usize) -> B>>());
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world
+            .entity(parent)
+            .contains::<WithChildrenParallel<B, fn(usize) -> B>>());
+        let values: Vec<usize> = Link::children(&world, parent)
+            .iter()
+            .map(|&child| world.get::<B>(child).unwrap().0)
+            .collect();
+        assert_eq!(values, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn no_children() {
+        let mut world = World::default();
+
+        let parent = world.spawn(spawn_children_parallel(0, B)).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_hierarchy!(world, parent, []);
+    }
+}
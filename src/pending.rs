@@ -0,0 +1,418 @@
+use core::any::TypeId;
+use core::fmt::Debug;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::deps::bevy_ecs::{
+    component::{ComponentId, ComponentInfo},
+    prelude::*,
+    query::QueryBuilder,
+    world::{Command, DeferredWorld},
+};
+use crate::deps::bevy_utils::tracing::warn;
+
+use crate::record_command_applied;
+
+/// Counts how many commands queued by this crate's component hooks have not been applied yet.
+///
+/// This is only tracked for commands queued while the resource exists, which [`resolve_all_pending`] ensures.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct OutstandingCommands(pub(crate) u64);
+
+/// A resource that switches this crate's deferred components to manual resolution.
+///
+/// By default, the commands generated by components such as [`WithChild`](crate::WithChild) and [`Maybe`](crate::Maybe)
+/// are applied whenever the world is next flushed.
+/// While this resource exists, the components are instead left in place,
+/// and nothing is spawned or inserted until [`resolve_deferred`] is run.
+/// This gives full control over when resolution happens in the schedule,
+/// which is useful for deterministic simulations such as rollback networking.
+///
+/// Components added before this resource was inserted are also left for [`resolve_deferred`],
+/// unless the world was flushed in between.
+/// If this resource is removed, any components that are still unresolved stay on their entities
+/// until it is inserted again and [`resolve_deferred`] is run.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{resolve_deferred, ManualResolution, WithChild};
+///
+/// #[derive(Component)]
+/// struct A;
+///
+/// let mut world = World::new();
+/// world.init_resource::<ManualResolution>();
+///
+/// let parent = world.spawn(WithChild(A)).id();
+/// world.flush();
+/// assert!(world.get::<Children>(parent).is_none());
+///
+/// resolve_deferred(&mut world);
+/// assert_eq!(world.get::<Children>(parent).unwrap().len(), 1);
+/// ```
+#[derive(Resource, Default)]
+pub struct ManualResolution {
+    /// How to resolve each kind of deferred component seen so far, in the order they were first seen.
+    resolvers: Vec<(ComponentId, Resolver)>,
+}
+
+/// Resolves the deferred component of a single entity, by applying the command its hook would have queued.
+type Resolver = Arc<dyn Fn(&mut World, Entity) + Send + Sync>;
+
+impl ManualResolution {
+    /// Records how to resolve the given component, if it hasn't been seen before.
+    fn register<C: Command>(&mut self, component_id: ComponentId, command: fn(Entity) -> C) {
+        if self.resolvers.iter().all(|(id, _)| *id != component_id) {
+            let resolver: Resolver = Arc::new(move |world: &mut World, entity| {
+                command(entity).apply(world);
+                record_command_applied(world);
+            });
+            self.resolvers.push((component_id, resolver));
+        }
+    }
+}
+
+impl Debug for ManualResolution {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ManualResolution")
+            .field(
+                "component_ids",
+                &self.resolvers.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// An exclusive system that resolves all of this crate's deferred components left in place by [`ManualResolution`].
+///
+/// The pending components are found by querying the world,
+/// and are resolved one kind of component at a time, in the order each kind was first added.
+/// Resolving a component may add further deferred components (such as a [`WithChild`](crate::WithChild) nested inside another),
+/// so this keeps going until none remain, leaving each hierarchy complete.
+/// As a result, bundles that replicate themselves indefinitely will never finish resolving.
+///
+/// Does nothing if the [`ManualResolution`] resource does not exist.
+pub fn resolve_deferred(world: &mut World) {
+    loop {
+        let Some(manual_resolution) = world.get_resource::<ManualResolution>() else {
+            return;
+        };
+        let resolvers = manual_resolution.resolvers.clone();
+
+        let mut resolved_any = false;
+        for (component_id, resolve) in resolvers {
+            let mut query = QueryBuilder::<Entity>::new(world)
+                .with_id(component_id)
+                .build();
+            let entities: Vec<Entity> = query.iter(world).collect();
+
+            for entity in entities {
+                // An earlier resolution may have already removed the component, or its entity
+                if world
+                    .get_entity(entity)
+                    .is_some_and(|entity_ref| entity_ref.contains_id(component_id))
+                {
+                    resolve(world, entity);
+                    resolved_any = true;
+                }
+            }
+        }
+
+        if !resolved_any {
+            return;
+        }
+    }
+}
+
+/// The types of this crate's deferred components, sorted so that they can be binary searched.
+///
+/// Whether a type is deferred is a property of the type itself, and is the same in every world.
+static DEFERRED_TYPES: RwLock<Vec<TypeId>> = RwLock::new(Vec::new());
+
+/// Marks `T` as one of this crate's deferred components, which are resolved by a command queued from their hook.
+///
+/// Every component whose hook calls [`queue_deferred`] must call this from [`Component::register_component_hooks`],
+/// which runs once when the component is registered in a world, so that hooks themselves pay nothing.
+pub(crate) fn mark_deferred<T: Component>() {
+    let type_id = TypeId::of::<T>();
+    let mut types = DEFERRED_TYPES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Err(index) = types.binary_search(&type_id) {
+        types.insert(index, type_id);
+    }
+}
+
+/// Returns `true` if the component was marked with [`mark_deferred`].
+fn is_deferred(types: &[TypeId], info: &ComponentInfo) -> bool {
+    info.type_id()
+        .is_some_and(|type_id| types.binary_search(&type_id).is_ok())
+}
+
+/// Queues the command that resolves one of this crate's deferred components, generated by its hook.
+///
+/// All hooks should go through this function, passing the id of the component that triggered them
+/// and the constructor of the command that resolves it for a given entity,
+/// so that [`resolve_all_pending`] can tell when work remains.
+/// If the [`ManualResolution`] resource exists, either now or when the command is applied,
+/// the component is instead left in place for [`resolve_deferred`].
+pub(crate) fn queue_deferred<C: Command>(
+    world: &mut DeferredWorld,
+    component_id: ComponentId,
+    entity: Entity,
+    command: fn(Entity) -> C,
+) {
+    debug_assert!(
+        world
+            .components()
+            .get_info(component_id)
+            .is_some_and(|info| is_deferred(
+                &DEFERRED_TYPES
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner),
+                info
+            )),
+        "deferred components must be marked with `mark_deferred`"
+    );
+
+    if let Some(mut manual_resolution) = world.get_resource_mut::<ManualResolution>() {
+        manual_resolution.register(component_id, command);
+        return;
+    }
+
+    let counted = match world.get_resource_mut::<OutstandingCommands>() {
+        Some(mut outstanding) => {
+            outstanding.0 += 1;
+            true
+        }
+        None => false,
+    };
+    world.commands().add(move |world: &mut World| {
+        if counted {
+            if let Some(mut outstanding) = world.get_resource_mut::<OutstandingCommands>() {
+                outstanding.0 = outstanding.0.saturating_sub(1);
+            }
+        }
+
+        // Manual resolution may have been enabled since this command was queued
+        if let Some(mut manual_resolution) = world.get_resource_mut::<ManualResolution>() {
+            manual_resolution.register(component_id, command);
+            return;
+        }
+
+        command(entity).apply(world);
+        record_command_applied(world);
+    });
+}
+
+/// Flushes the world until all of this crate's deferred components have been resolved.
+///
+/// Each command applied during a flush is followed by a flush of its own,
+/// so nested components (such as a [`WithChild`](crate::WithChild) inside a [`Maybe`](crate::Maybe))
+/// are resolved by the same flush as the component that added them, however deep the nesting goes.
+/// A single flush is therefore normally enough: this function checks that no command queued by this crate is still outstanding afterwards,
+/// removing the guesswork about whether a call to [`World::flush`] was sufficient.
+///
+/// `max_iterations` caps the number of flushes, not the nesting depth.
+/// Bundles that replicate themselves indefinitely never let a single flush complete, so this cannot guard against them.
+/// Returns `true` if everything was resolved, or `false` (after logging a warning) if commands were still outstanding after the last flush.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{resolve_all_pending, WithChild};
+///
+/// #[derive(Component)]
+/// struct A;
+///
+/// let mut world = World::new();
+/// let parent = world.spawn(WithChild((A, WithChild(A)))).id();
+/// assert!(resolve_all_pending(&mut world, 1));
+///
+/// let child = world.get::<Children>(parent).unwrap()[0];
+/// assert_eq!(world.get::<Children>(child).unwrap().len(), 1);
+/// ```
+pub fn resolve_all_pending(world: &mut World, max_iterations: usize) -> bool {
+    world.init_resource::<OutstandingCommands>();
+
+    for _ in 0..max_iterations {
+        world.flush();
+        if world.resource::<OutstandingCommands>().0 == 0 {
+            return true;
+        }
+    }
+
+    warn!("resolve_all_pending gave up after {max_iterations} iterations: deferred commands are still outstanding");
+    false
+}
+
+/// Returns `true` if the given entity still holds any of this crate's deferred components,
+/// such as [`WithChild`](crate::WithChild) or [`Maybe`](crate::Maybe), which have not yet been resolved.
+///
+/// These components are resolved by commands, which are only applied when the world is next flushed
+/// (typically at the end of the system that spawned them, or at the next [`apply_deferred`]).
+/// Until then, the entity's children have not been spawned and its optional components have not been inserted.
+/// This is useful for synchronizing tests, or for systems that must wait for a hierarchy to be complete.
+///
+/// Returns `false` if the entity does not exist.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::{is_pending, WithChild};
+///
+/// #[derive(Component)]
+/// struct A;
+///
+/// let mut world = World::new();
+/// let parent = world.spawn(WithChild(A)).id();
+/// assert!(is_pending(&world, parent));
+///
+/// world.flush();
+/// assert!(!is_pending(&world, parent));
+/// ```
+pub fn is_pending(world: &World, entity: Entity) -> bool {
+    let Some(entity_ref) = world.get_entity(entity) else {
+        return false;
+    };
+
+    let types = DEFERRED_TYPES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner);
+    let pending = entity_ref.archetype().components().any(|component_id| {
+        world
+            .components()
+            .get_info(component_id)
+            .is_some_and(|info| is_deferred(&types, info))
+    });
+    pending
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::bevy_hierarchy::Children;
+
+    use super::*;
+    use crate::{Maybe, WithChild};
+
+    #[derive(Component, PartialEq, Debug)]
+    struct A;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct B(u8);
+
+    #[test]
+    fn resolve_grandchildren() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(Maybe::new(WithChild((A, WithChild((A, B(3)))))))
+            .id();
+        assert!(resolve_all_pending(&mut world, 8));
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 1);
+
+        let grandchildren = world.get::<Children>(children[0]).unwrap();
+        assert_eq!(grandchildren.len(), 1);
+        assert_eq!(world.get::<B>(grandchildren[0]), Some(&B(3)));
+    }
+
+    #[test]
+    fn pending_until_flushed() {
+        let mut world = World::default();
+
+        let unrelated = world.spawn(A).id();
+        // Spawning flushes the world, so this must come last
+        let parent = world.spawn((A, WithChild(B(1)), Maybe::new(B(2)))).id();
+        assert!(is_pending(&world, parent));
+        assert!(!is_pending(&world, unrelated));
+
+        world.flush();
+        assert!(!is_pending(&world, parent));
+        assert!(world.get::<Children>(parent).is_some());
+    }
+
+    #[test]
+    fn unmarked_components_are_not_pending() {
+        /// A sparse set component that lives in this crate, but isn't resolved by a command
+        #[derive(Component)]
+        #[component(storage = "SparseSet")]
+        struct Sparse;
+
+        let mut world = World::default();
+        let entity = world.spawn(Sparse).id();
+        assert!(!is_pending(&world, entity));
+    }
+
+    #[test]
+    fn iteration_cap() {
+        let mut world = World::default();
+
+        world.spawn(WithChild(A));
+        assert!(!resolve_all_pending(&mut world, 0));
+        assert!(resolve_all_pending(&mut world, 1));
+    }
+
+    #[test]
+    fn nested_chain_resolves_in_one_flush() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(WithChild((A, WithChild((A, WithChild(B(3)))))))
+            .id();
+        // The iteration cap does not limit the nesting depth
+        assert!(resolve_all_pending(&mut world, 1));
+
+        let child = world.get::<Children>(parent).unwrap()[0];
+        let grandchild = world.get::<Children>(child).unwrap()[0];
+        let great_grandchild = world.get::<Children>(grandchild).unwrap()[0];
+        assert_eq!(world.get::<B>(great_grandchild), Some(&B(3)));
+    }
+
+    #[test]
+    fn manual_resolution() {
+        let mut world = World::default();
+        world.init_resource::<ManualResolution>();
+
+        let parent = world
+            .spawn((WithChild((A, WithChild(B(2)))), Maybe::new(B(1))))
+            .id();
+        // Flushing the world has no effect in manual mode
+        world.flush();
+        assert!(is_pending(&world, parent));
+        assert!(world.get::<Children>(parent).is_none());
+        assert!(world.get::<B>(parent).is_none());
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(resolve_deferred);
+        schedule.run(&mut world);
+
+        assert!(!is_pending(&world, parent));
+        assert_eq!(world.get::<B>(parent), Some(&B(1)));
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 1);
+        let grandchildren = world.get::<Children>(children[0]).unwrap();
+        assert_eq!(world.get::<B>(grandchildren[0]), Some(&B(2)));
+    }
+
+    #[test]
+    fn manual_resolution_picks_up_earlier_components() {
+        let mut world = World::default();
+
+        // This component's command is queued before manual resolution is enabled
+        let parent = world.spawn(WithChild((A, WithChild(B(2))))).id();
+        world.init_resource::<ManualResolution>();
+        world.flush();
+        assert!(is_pending(&world, parent));
+        assert!(world.get::<Children>(parent).is_none());
+
+        resolve_deferred(&mut world);
+        assert!(!is_pending(&world, parent));
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 1);
+        let grandchildren = world.get::<Children>(children[0]).unwrap();
+        assert_eq!(world.get::<B>(grandchildren[0]), Some(&B(2)));
+    }
+}
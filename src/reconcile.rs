@@ -0,0 +1,655 @@
+use core::hash::Hash;
+use core::iter::Map;
+use core::marker::PhantomData;
+use std::collections::{btree_map, hash_map, BTreeMap, HashMap};
+
+use crate::deps::bevy_ecs::{
+    component::{ComponentHooks, ComponentId, StorageType},
+    prelude::*,
+    world::{Command, DeferredWorld},
+};
+
+use crate::{
+    mark_deferred, on_cancelled, on_missing, queue_deferred, ChildBuffer, Link, LinkChild,
+    WithChildError, WithChildren,
+};
+
+/// A component that, when added to an entity, will reconcile that entity's existing children
+/// against the supplied list of bundles, rather than spawning a fresh set of children.
+///
+/// This is intended for retained-mode workflows (such as UI), where the same parent is repeatedly
+/// given a new desired set of children and churning entities would be wasteful.
+///
+/// Reconciliation is length-based: the `i`th desired bundle is inserted onto the `i`th existing child,
+/// overwriting any components of the same type.
+/// Surplus desired bundles are spawned as new children, and surplus existing children are despawned recursively.
+/// Components on existing children that are not part of the new bundle are left untouched.
+///
+/// See [`ReconcileKeyedChildren`] for a version that matches children by a user-defined key.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::ReconcileChildren;
+///
+/// #[derive(Component)]
+/// struct Row(u32);
+///
+/// let mut world = World::new();
+/// let parent = world.spawn(ReconcileChildren(vec![Row(1), Row(2), Row(3)])).id();
+/// world.flush();
+/// let first_child = world.get::<Children>(parent).unwrap()[0];
+///
+/// world.entity_mut(parent).insert(ReconcileChildren(vec![Row(4), Row(5)]));
+/// world.flush();
+///
+/// let children = world.get::<Children>(parent).unwrap();
+/// assert_eq!(children.len(), 2);
+/// // The first child was reused, rather than respawned
+/// assert_eq!(children[0], first_child);
+/// assert_eq!(world.get::<Row>(first_child).unwrap().0, 4);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ReconcileChildren<B: Bundle, I: IntoIterator<Item = B>>(pub I);
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Component
+    for ReconcileChildren<B, I>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(reconcile_children_hook::<B, I>);
+    }
+}
+
+/// A hook that runs whenever [`ReconcileChildren`] is added to an entity.
+///
+/// Generates a [`ReconcileChildrenCommand`].
+fn reconcile_children_hook<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        ReconcileChildrenCommand::<B, I>::new,
+    );
+}
+
+/// A [`Command`] that reconciles the children of an entity against its [`ReconcileChildren<B, I>`] component.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct ReconcileChildrenCommand<B, I> {
+    /// The entity holding the [`ReconcileChildren`] component, whose children will be reconciled.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, I)>,
+}
+
+impl<B, I> ReconcileChildrenCommand<B, I> {
+    /// Creates a new [`ReconcileChildrenCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Command
+    for ReconcileChildrenCommand<B, I>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(reconcile_component) = entity_mut.take::<ReconcileChildren<B, I>>() else {
+            on_cancelled("ReconcileChildren");
+            return;
+        };
+
+        let existing_children = ChildBuffer::from(Link::children(world, self.parent_entity));
+        let mut existing_children = existing_children.into_iter();
+
+        for child_bundle in reconcile_component.0 {
+            match existing_children.next() {
+                Some(child_entity) => {
+                    world.entity_mut(child_entity).insert(child_bundle);
+                }
+                None => {
+                    let child_entity = Link::spawn_child(world, child_bundle).id();
+                    if !Link::link_child(world, self.parent_entity, child_entity) {
+                        return;
+                    }
+                }
+            }
+        }
+
+        for surplus_child in existing_children {
+            Link::despawn_recursive(world, surplus_child);
+        }
+    }
+}
+
+/// A component that, when added to an entity, will despawn all of that entity's existing children (recursively),
+/// then spawn a new child for each item generated by the iterator.
+///
+/// This makes re-running spawning logic on an already-populated parent idempotent,
+/// whereas adding [`WithChildren`] again would accumulate children.
+/// Note that *all* existing children are cleared, including those that were not spawned by this crate.
+/// The children cleared are those the entity has when the command is applied:
+/// children added after this component was inserted, but before it was resolved, are cleared too.
+///
+/// Unlike [`ReconcileChildren`], no entities are reused: prefer that when preserving entity identity matters.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::ReplaceChildren;
+///
+/// #[derive(Component)]
+/// struct Item(u32);
+///
+/// let mut world = World::new();
+/// let parent = world.spawn(ReplaceChildren(vec![Item(1), Item(2)])).id();
+/// world.flush();
+///
+/// world.entity_mut(parent).insert(ReplaceChildren(vec![Item(3), Item(4)]));
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(parent).unwrap().len(), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ReplaceChildren<B: Bundle, I: IntoIterator<Item = B>>(pub I);
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Component
+    for ReplaceChildren<B, I>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(replace_children_hook::<B, I>);
+    }
+}
+
+/// A hook that runs whenever [`ReplaceChildren`] is added to an entity.
+///
+/// Generates a [`ReplaceChildrenCommand`].
+fn replace_children_hook<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        ReplaceChildrenCommand::<B, I>::new,
+    );
+}
+
+/// A [`Command`] that replaces the children of an entity with those generated by its [`ReplaceChildren<B, I>`] component.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct ReplaceChildrenCommand<B, I> {
+    /// The entity holding the [`ReplaceChildren`] component, whose children will be replaced.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, I)>,
+}
+
+impl<B, I> ReplaceChildrenCommand<B, I> {
+    /// Creates a new [`ReplaceChildrenCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Command
+    for ReplaceChildrenCommand<B, I>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(replace_component) = entity_mut.take::<ReplaceChildren<B, I>>() else {
+            on_cancelled("ReplaceChildren");
+            return;
+        };
+
+        let existing_children = ChildBuffer::from(Link::children(world, self.parent_entity));
+        for child_entity in existing_children {
+            Link::despawn_recursive(world, child_entity);
+        }
+
+        for child_bundle in replace_component.0 {
+            let child_entity = Link::spawn_child(world, child_bundle).id();
+            if !Link::link_child(world, self.parent_entity, child_entity) {
+                return;
+            }
+        }
+    }
+}
+
+/// A bundle that can be identified by a key, for use with [`ReconcileKeyedChildren`].
+///
+/// Two bundles with equal keys are considered to describe the same child entity.
+pub trait Keyed {
+    /// The type of key used to identify this bundle.
+    type Key: Eq + Hash + Clone + Send + Sync + 'static;
+
+    /// Returns the key that identifies this bundle.
+    fn key(&self) -> Self::Key;
+}
+
+/// A component that stores the key of a child entity.
+///
+/// This is inserted onto every child spawned or updated by [`ReconcileKeyedChildren`],
+/// and is used to match desired bundles to existing children.
+/// It is also inserted onto each child spawned by [`spawn_children_from_map`] and [`spawn_children_from_map_sorted`].
+#[derive(Component, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChildKey<K: Send + Sync + 'static>(pub K);
+
+/// A [`WithChildren`] component that spawns a child for each entry of a [`HashMap`].
+///
+/// Constructed via [`spawn_children_from_map`].
+pub type WithChildrenFromMap<K, V> =
+    WithChildren<(ChildKey<K>, V), Map<hash_map::IntoIter<K, V>, fn((K, V)) -> (ChildKey<K>, V)>>;
+
+/// A [`WithChildren`] component that spawns a child for each entry of a [`BTreeMap`], in ascending key order.
+///
+/// Constructed via [`spawn_children_from_map_sorted`].
+pub type WithChildrenFromSortedMap<K, V> =
+    WithChildren<(ChildKey<K>, V), Map<btree_map::IntoIter<K, V>, fn((K, V)) -> (ChildKey<K>, V)>>;
+
+/// Converts a map entry into a bundle containing both its key and its value.
+fn keyed_entry<K: Send + Sync + 'static, V>((key, value): (K, V)) -> (ChildKey<K>, V) {
+    (ChildKey(key), value)
+}
+
+/// Creates a [`WithChildren`] component that spawns a child for each entry of `map`.
+///
+/// Each child carries the entry's key as a [`ChildKey`], alongside the value's bundle.
+/// As the iteration order of a [`HashMap`] is unspecified, so is the order of the resulting children:
+/// use [`spawn_children_from_map_sorted`] if a deterministic order is required.
+///
+/// ```rust
+/// use std::collections::HashMap;
+///
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{spawn_children_from_map, ChildKey};
+///
+/// #[derive(Component)]
+/// struct Price(u32);
+///
+/// let prices = HashMap::from([("apple", Price(3)), ("pear", Price(5))]);
+///
+/// let mut world = World::new();
+/// let shop = world.spawn(spawn_children_from_map(prices)).id();
+/// world.flush();
+///
+/// for &child in world.get::<Children>(shop).unwrap() {
+///     let key = world.get::<ChildKey<&str>>(child).unwrap().0;
+///     let price = world.get::<Price>(child).unwrap().0;
+///     assert_eq!(price, if key == "apple" { 3 } else { 5 });
+/// }
+/// ```
+pub fn spawn_children_from_map<K: Send + Sync + 'static, V: Bundle>(
+    map: HashMap<K, V>,
+) -> WithChildrenFromMap<K, V> {
+    WithChildren(
+        map.into_iter()
+            .map(keyed_entry as fn((K, V)) -> (ChildKey<K>, V)),
+    )
+}
+
+/// Creates a [`WithChildren`] component that spawns a child for each entry of `map`, in ascending key order.
+///
+/// Each child carries the entry's key as a [`ChildKey`], alongside the value's bundle.
+/// Unlike [`spawn_children_from_map`], the order of the resulting children is deterministic.
+pub fn spawn_children_from_map_sorted<K: Ord + Send + Sync + 'static, V: Bundle>(
+    map: impl IntoIterator<Item = (K, V)>,
+) -> WithChildrenFromSortedMap<K, V> {
+    let map: BTreeMap<K, V> = map.into_iter().collect();
+    WithChildren(
+        map.into_iter()
+            .map(keyed_entry as fn((K, V)) -> (ChildKey<K>, V)),
+    )
+}
+
+/// A component that, when added to an entity, will reconcile that entity's existing children
+/// against the supplied list of [`Keyed`] bundles.
+///
+/// Existing children are matched to desired bundles using their [`ChildKey`].
+/// Matched children are kept (and have the new bundle inserted onto them),
+/// unmatched bundles are spawned as new children,
+/// and existing keyed children whose key no longer appears are despawned recursively.
+/// Children without a [`ChildKey`] of the matching type are left untouched.
+///
+/// After reconciliation, the keyed children are moved to the end of the parent's [`Children`](bevy_hierarchy::Children),
+/// in the order they were supplied.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{Keyed, ReconcileKeyedChildren};
+///
+/// #[derive(Component)]
+/// struct Item(&'static str);
+///
+/// impl Keyed for Item {
+///     type Key = &'static str;
+///
+///     fn key(&self) -> &'static str {
+///         self.0
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// let parent = world.spawn(ReconcileKeyedChildren(vec![Item("a"), Item("b")])).id();
+/// world.flush();
+/// let b = world.get::<Children>(parent).unwrap()[1];
+///
+/// world.entity_mut(parent).insert(ReconcileKeyedChildren(vec![Item("b"), Item("c")]));
+/// world.flush();
+///
+/// let children = world.get::<Children>(parent).unwrap();
+/// assert_eq!(children.len(), 2);
+/// // "b" kept its entity, and moved to the front
+/// assert_eq!(children[0], b);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ReconcileKeyedChildren<B: Bundle + Keyed, I: IntoIterator<Item = B>>(pub I);
+
+impl<B: Bundle + Keyed, I: IntoIterator<Item = B> + Send + Sync + 'static> Component
+    for ReconcileKeyedChildren<B, I>
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(reconcile_keyed_children_hook::<B, I>);
+    }
+}
+
+/// A hook that runs whenever [`ReconcileKeyedChildren`] is added to an entity.
+///
+/// Generates a [`ReconcileKeyedChildrenCommand`].
+fn reconcile_keyed_children_hook<
+    B: Bundle + Keyed,
+    I: IntoIterator<Item = B> + Send + Sync + 'static,
+>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        ReconcileKeyedChildrenCommand::<B, I>::new,
+    );
+}
+
+/// A [`Command`] that reconciles the children of an entity against its [`ReconcileKeyedChildren<B, I>`] component.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct ReconcileKeyedChildrenCommand<B, I> {
+    /// The entity holding the [`ReconcileKeyedChildren`] component, whose children will be reconciled.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, I)>,
+}
+
+impl<B, I> ReconcileKeyedChildrenCommand<B, I> {
+    /// Creates a new [`ReconcileKeyedChildrenCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle + Keyed, I: IntoIterator<Item = B> + Send + Sync + 'static> Command
+    for ReconcileKeyedChildrenCommand<B, I>
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(reconcile_component) = entity_mut.take::<ReconcileKeyedChildren<B, I>>() else {
+            on_cancelled("ReconcileKeyedChildren");
+            return;
+        };
+
+        let existing_children = ChildBuffer::from(Link::children(world, self.parent_entity));
+
+        let mut existing_keyed_children: HashMap<B::Key, Entity> = existing_children
+            .into_iter()
+            .filter_map(|child_entity| {
+                let key = world.get::<ChildKey<B::Key>>(child_entity)?;
+                Some((key.0.clone(), child_entity))
+            })
+            .collect();
+
+        let mut ordered_children = Vec::new();
+        for child_bundle in reconcile_component.0 {
+            let key = ChildKey(child_bundle.key());
+            let child_entity = match existing_keyed_children.remove(&key.0) {
+                Some(child_entity) => {
+                    world.entity_mut(child_entity).insert((child_bundle, key));
+                    child_entity
+                }
+                None => Link::spawn_child(world, (child_bundle, key)).id(),
+            };
+            ordered_children.push(child_entity);
+        }
+
+        for (_, stale_child) in existing_keyed_children {
+            Link::despawn_recursive(world, stale_child);
+        }
+
+        Link::link_children(world, self.parent_entity, &ordered_children);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::bevy_hierarchy::{BuildWorldChildren, Children};
+
+    use super::*;
+    use crate::{resolve_deferred, ManualResolution};
+
+    #[derive(Component, PartialEq, Debug)]
+    struct B(u8);
+
+    impl Keyed for B {
+        type Key = u8;
+
+        fn key(&self) -> u8 {
+            self.0
+        }
+    }
+
+    #[derive(Component, PartialEq, Debug)]
+    struct Label(&'static str);
+
+    fn children(world: &World, parent: Entity) -> Vec<Entity> {
+        world
+            .get::<Children>(parent)
+            .map(|children| children.to_vec())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn reconcile_by_length() {
+        let mut world = World::default();
+
+        let parent = world.spawn(ReconcileChildren(vec![B(0), B(1)])).id();
+        world.flush();
+        let original_children = children(&world, parent);
+        assert_eq!(original_children.len(), 2);
+
+        // Growing keeps the existing children and spawns the delta
+        world
+            .entity_mut(parent)
+            .insert(ReconcileChildren(vec![B(10), B(11), B(12)]));
+        world.flush();
+        let grown_children = children(&world, parent);
+        assert_eq!(grown_children.len(), 3);
+        assert_eq!(grown_children[..2], original_children[..]);
+        for (i, child_entity) in grown_children.iter().enumerate() {
+            assert_eq!(world.get::<B>(*child_entity), Some(&B(10 + i as u8)));
+        }
+
+        // Shrinking despawns the surplus children
+        world
+            .entity_mut(parent)
+            .insert(ReconcileChildren(vec![B(20)]));
+        world.flush();
+        assert_eq!(children(&world, parent), vec![original_children[0]]);
+        assert_eq!(world.get::<B>(original_children[0]), Some(&B(20)));
+        assert!(world.get_entity(grown_children[1]).is_none());
+        assert!(world.get_entity(grown_children[2]).is_none());
+        assert!(!world
+            .entity(parent)
+            .contains::<ReconcileChildren<B, Vec<B>>>());
+    }
+
+    #[test]
+    fn replace_children() {
+        let mut world = World::default();
+
+        let parent = world.spawn(ReplaceChildren(vec![B(0), B(1)])).id();
+        world.flush();
+        let original_children = children(&world, parent);
+        assert_eq!(original_children.len(), 2);
+
+        // Running the same spawning logic again doesn't accumulate children
+        world
+            .entity_mut(parent)
+            .insert(ReplaceChildren(vec![B(0), B(1)]));
+        world.flush();
+        let replaced_children = children(&world, parent);
+        assert_eq!(replaced_children.len(), 2);
+        for child_entity in original_children {
+            assert!(world.get_entity(child_entity).is_none());
+        }
+        for (i, child_entity) in replaced_children.iter().enumerate() {
+            assert_eq!(world.get::<B>(*child_entity), Some(&B(i as u8)));
+        }
+    }
+
+    #[test]
+    fn replace_children_reads_current_children() {
+        let mut world = World::default();
+        world.init_resource::<ManualResolution>();
+
+        let parent = world.spawn(ReplaceChildren(vec![B(0), B(1)])).id();
+        world.flush();
+
+        // A child is added after the hook ran, but before the command is applied
+        let late_child = world.spawn(B(2)).id();
+        world.entity_mut(parent).add_child(late_child);
+        resolve_deferred(&mut world);
+
+        assert!(world.get_entity(late_child).is_none());
+        let replaced_children = children(&world, parent);
+        assert_eq!(replaced_children.len(), 2);
+        for (i, child_entity) in replaced_children.iter().enumerate() {
+            assert_eq!(world.get::<B>(*child_entity), Some(&B(i as u8)));
+        }
+    }
+
+    #[test]
+    fn reconcile_by_key() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn(ReconcileKeyedChildren(vec![B(1), B(2), B(3)]))
+            .id();
+        world.flush();
+        let original_children = children(&world, parent);
+        assert_eq!(original_children.len(), 3);
+
+        // Unkeyed children are left alone
+        let unkeyed_child = world.spawn(Label("unkeyed")).id();
+        world.entity_mut(parent).add_child(unkeyed_child);
+
+        world
+            .entity_mut(parent)
+            .insert(ReconcileKeyedChildren(vec![B(3), B(4), B(1)]));
+        world.flush();
+
+        let new_children = children(&world, parent);
+        assert_eq!(new_children.len(), 4);
+        assert_eq!(new_children[0], unkeyed_child);
+        assert_eq!(new_children[1], original_children[2]);
+        assert!(!original_children.contains(&new_children[2]));
+        assert_eq!(new_children[3], original_children[0]);
+        assert_eq!(world.get::<B>(new_children[2]), Some(&B(4)));
+        assert!(world.get_entity(original_children[1]).is_none());
+    }
+
+    #[test]
+    fn children_from_map() {
+        let mut world = World::default();
+
+        let map = HashMap::from([("one", B(1)), ("two", B(2)), ("three", B(3))]);
+        let parent = world.spawn(spawn_children_from_map(map)).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let children = children(&world, parent);
+        assert_eq!(children.len(), 3);
+        for child in children {
+            let key = world.get::<ChildKey<&str>>(child).unwrap().0;
+            let expected = match key {
+                "one" => B(1),
+                "two" => B(2),
+                "three" => B(3),
+                _ => unreachable!(),
+            };
+            assert_eq!(world.get::<B>(child), Some(&expected));
+        }
+    }
+
+    #[test]
+    fn children_from_map_sorted() {
+        let mut world = World::default();
+
+        let map = HashMap::from([(2, Label("b")), (0, Label("z")), (1, Label("a"))]);
+        let parent = world.spawn(spawn_children_from_map_sorted(map)).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let entries: Vec<_> = children(&world, parent)
+            .into_iter()
+            .map(|child| {
+                (
+                    world.get::<ChildKey<i32>>(child).unwrap().0,
+                    world.get::<Label>(child).unwrap().0,
+                )
+            })
+            .collect();
+        assert_eq!(entries, [(0, "z"), (1, "a"), (2, "b")]);
+    }
+}
@@ -0,0 +1,104 @@
+//! Debug-only detection of accidental recursion through [`WithChild`](crate::WithChild).
+//!
+//! This is enabled by the `recursion_guard` feature, and compiled out entirely in release builds.
+
+use core::any::{type_name, TypeId};
+
+use crate::deps::bevy_ecs::prelude::*;
+use crate::deps::bevy_hierarchy::Parent;
+use crate::deps::bevy_utils::tracing::warn;
+
+/// Records which [`WithChild`](crate::WithChild) bundle type an entity was spawned from.
+#[derive(Component, Debug)]
+pub(crate) struct SpawnedByWithChild(TypeId);
+
+/// Warns if a child spawned from a `WithChild<B>` has an ancestor that was also spawned from a `WithChild<B>`.
+///
+/// This usually means that a bundle (or a hook reacting to it) re-introduces itself,
+/// causing the hierarchy to grow each time the child is spawned.
+pub(crate) fn check_recursion<B: Bundle>(world: &mut World, child_entity: Entity) {
+    let bundle_type = TypeId::of::<B>();
+    let mut path = vec![child_entity];
+    let mut recursive = false;
+
+    let mut current = world.get::<Parent>(child_entity).map(Parent::get);
+    while let Some(ancestor) = current {
+        path.push(ancestor);
+        if world
+            .get::<SpawnedByWithChild>(ancestor)
+            .is_some_and(|spawned_by| spawned_by.0 == bundle_type)
+        {
+            recursive = true;
+        }
+        current = world.get::<Parent>(ancestor).map(Parent::get);
+    }
+
+    if recursive {
+        path.reverse();
+        warn!(
+            "WithChild<{}> was spawned inside a hierarchy that it already created, which may recurse indefinitely. Entity path: {path:?}",
+            type_name::<B>()
+        );
+    }
+
+    world
+        .entity_mut(child_entity)
+        .insert(SpawnedByWithChild(bundle_type));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::bevy_ecs::{
+        component::{ComponentHooks, StorageType},
+        world::DeferredWorld,
+    };
+
+    use crate::test_utils::capture_logs;
+    use crate::WithChild;
+
+    use super::*;
+
+    /// A component that spawns a child with one less depth, until reaching zero.
+    struct Depth(u8);
+
+    impl Component for Depth {
+        const STORAGE_TYPE: StorageType = StorageType::Table;
+
+        fn register_component_hooks(hooks: &mut ComponentHooks) {
+            hooks.on_add(|mut world: DeferredWorld, entity, _| {
+                let depth = world.get::<Depth>(entity).unwrap().0;
+                if depth > 0 {
+                    world
+                        .commands()
+                        .entity(entity)
+                        .insert(WithChild(Depth(depth - 1)));
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn warns_on_recursion() {
+        let logs = capture_logs(|| {
+            let mut world = World::default();
+            world.spawn(Depth(3));
+            world.flush();
+        });
+
+        assert!(logs.contains("may recurse indefinitely"));
+    }
+
+    #[test]
+    fn distinct_types_do_not_warn() {
+        #[derive(Component)]
+        struct A;
+
+        let logs = capture_logs(|| {
+            let mut world = World::default();
+            world.spawn(WithChild((A, WithChild(Depth(0)))));
+            world.flush();
+        });
+
+        assert!(logs.is_empty());
+    }
+}
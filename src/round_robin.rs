@@ -0,0 +1,170 @@
+use crate::deps::bevy_ecs::{prelude::*, world::Command};
+
+use crate::{on_missing, ChildBuffer, Link, LinkChild, WithChildError};
+
+/// A [`Command`] that spawns a child for each item generated by the iterator,
+/// distributing them across several parents in turn.
+///
+/// The first child is linked to the first parent, the second child to the second parent, and so on,
+/// wrapping back around to the first parent once every parent has received a child.
+/// Each parent's children keep the order in which they were generated,
+/// so items are spread evenly, with earlier parents receiving one extra child when they don't divide exactly.
+///
+/// Every parent is checked before anything is spawned:
+/// if any of them are missing, no children are spawned and the [`FailurePolicy`](crate::FailurePolicy) is followed.
+/// If there are no parents, the bundles are dropped without being spawned.
+/// Parents are checked again once the children are spawned, as spawning may apply commands that despawn them:
+/// the children of any parent that has disappeared by then are despawned, and the [`FailurePolicy`](crate::FailurePolicy) is followed,
+/// while the other parents still receive theirs.
+///
+/// Usually constructed via [`spawn_children_round_robin`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpawnChildrenRoundRobin<I> {
+    /// The entities that will become the parents of the spawned children, in the order they receive them.
+    pub parent_entities: Vec<Entity>,
+    /// The iterator generating the bundles of the children.
+    pub iter: I,
+}
+
+impl<I> SpawnChildrenRoundRobin<I> {
+    /// Creates a new [`SpawnChildrenRoundRobin`] command, distributing the children of `iter` across the given parents.
+    pub const fn new(parent_entities: Vec<Entity>, iter: I) -> Self {
+        Self {
+            parent_entities,
+            iter,
+        }
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + 'static> Command for SpawnChildrenRoundRobin<I> {
+    fn apply(self, world: &mut World) {
+        if let Some(&missing_entity) = self
+            .parent_entities
+            .iter()
+            .find(|&&parent_entity| world.get_entity(parent_entity).is_none())
+        {
+            on_missing(world, WithChildError::ParentMissing(missing_entity));
+            return;
+        }
+
+        if self.parent_entities.is_empty() {
+            return;
+        }
+
+        let mut assigned = vec![ChildBuffer::new(); self.parent_entities.len()];
+        for (i, bundle) in self.iter.into_iter().enumerate() {
+            let child_entity = Link::spawn_child(world, bundle).id();
+            assigned[i % self.parent_entities.len()].push(child_entity);
+        }
+
+        for (&parent_entity, children) in self.parent_entities.iter().zip(&assigned) {
+            Link::link_children(world, parent_entity, children);
+        }
+    }
+}
+
+/// Creates a [`SpawnChildrenRoundRobin`] command, which spawns a child for each item generated by `iter`,
+/// assigning them to each of `parents` in turn.
+///
+/// This is useful for load-balancing entities across several containers, such as the columns of a grid.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::spawn_children_round_robin;
+///
+/// #[derive(Component)]
+/// struct Card(u8);
+///
+/// let mut world = World::new();
+/// let columns = [world.spawn_empty().id(), world.spawn_empty().id()];
+///
+/// world
+///     .commands()
+///     .add(spawn_children_round_robin(&columns, (0..5).map(Card)));
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(columns[0]).unwrap().len(), 3);
+/// assert_eq!(world.get::<Children>(columns[1]).unwrap().len(), 2);
+/// ```
+pub fn spawn_children_round_robin<B: Bundle, I: IntoIterator<Item = B>>(
+    parents: &[Entity],
+    iter: I,
+) -> SpawnChildrenRoundRobin<I> {
+    SpawnChildrenRoundRobin::new(parents.to_vec(), iter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assert_hierarchy, FailurePolicy};
+
+    #[derive(Component, PartialEq, Debug)]
+    struct B(u8);
+
+    #[test]
+    fn five_items_across_two_parents() {
+        let mut world = World::default();
+
+        let parents = [world.spawn_empty().id(), world.spawn_empty().id()];
+        world
+            .commands()
+            .add(spawn_children_round_robin(&parents, (0..5).map(B)));
+        world.flush();
+
+        assert_hierarchy!(world, parents[0], [] {
+            [B = B(0)],
+            [B = B(2)],
+            [B = B(4)],
+        });
+        assert_hierarchy!(world, parents[1], [] {
+            [B = B(1)],
+            [B = B(3)],
+        });
+    }
+
+    #[test]
+    fn missing_parent_spawns_nothing() {
+        let mut world = World::default();
+        world.insert_resource(FailurePolicy::Ignore);
+
+        let parents = [world.spawn_empty().id(), world.spawn_empty().id()];
+        world.despawn(parents[1]);
+        world
+            .commands()
+            .add(spawn_children_round_robin(&parents, (0..5).map(B)));
+        world.flush();
+
+        assert_hierarchy!(world, parents[0], []);
+        assert_eq!(world.query::<&B>().iter(&world).count(), 0);
+    }
+
+    #[test]
+    fn parent_despawned_while_spawning() {
+        /// Queues a despawn of the second parent, then immediately spawns the children
+        struct DespawnThenSpawnChildren([Entity; 2]);
+
+        impl Command for DespawnThenSpawnChildren {
+            fn apply(self, world: &mut World) {
+                world.commands().entity(self.0[1]).despawn();
+                // The despawn is applied when spawning the first child flushes the world
+                spawn_children_round_robin(&self.0, (0..5).map(B)).apply(world);
+            }
+        }
+
+        let mut world = World::default();
+        world.insert_resource(FailurePolicy::Ignore);
+
+        let parents = [world.spawn_empty().id(), world.spawn_empty().id()];
+        world.commands().add(DespawnThenSpawnChildren(parents));
+        world.flush();
+
+        assert!(world.get_entity(parents[1]).is_none());
+        assert_hierarchy!(world, parents[0], [] {
+            [B = B(0)],
+            [B = B(2)],
+            [B = B(4)],
+        });
+        assert_eq!(world.query::<&B>().iter(&world).count(), 3);
+    }
+}
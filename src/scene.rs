@@ -0,0 +1,132 @@
+use crate::deps::bevy_ecs::{
+    component::{ComponentHooks, ComponentId, StorageType},
+    prelude::*,
+    world::{Command, DeferredWorld},
+};
+use bevy_asset::Handle;
+use bevy_scene::{Scene, SceneSpawner};
+
+use crate::{mark_deferred, on_cancelled, on_missing, queue_deferred, WithChildError};
+
+/// A component that, when added to an entity, will spawn an instance of the given [`Scene`] as children of that entity.
+///
+/// This is useful for instancing small prefabs declaratively, alongside [`WithChild`](crate::WithChild).
+/// The root entities of the scene become children of the entity.
+///
+/// This component is resolved like any other, but the scene itself is spawned by the [`SceneSpawner`]:
+/// this happens when its `scene_spawner_system` next runs (typically in `SpawnScene`),
+/// and only once the scene asset has finished loading, which may take several frames.
+/// If the parent entity is despawned before then, the scene is never spawned.
+///
+/// Requires the [`SceneSpawner`] resource, which is added by `ScenePlugin`.
+///
+/// ```rust
+/// use bevy_asset::Handle;
+/// use bevy_ecs::prelude::*;
+/// use bevy_scene::Scene;
+/// use i_cant_believe_its_not_bsn::WithSceneChild;
+///
+/// #[derive(Component)]
+/// struct Chest;
+///
+/// fn spawn_chest(mut commands: Commands, chest_prefab: Handle<Scene>) {
+///     commands.spawn((Chest, WithSceneChild(chest_prefab)));
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WithSceneChild(pub Handle<Scene>);
+
+impl Component for WithSceneChild {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_scene_child_hook);
+    }
+}
+
+/// A hook that runs whenever [`WithSceneChild`] is added to an entity.
+///
+/// Generates a [`WithSceneChildCommand`].
+fn with_scene_child_hook(mut world: DeferredWorld<'_>, entity: Entity, component_id: ComponentId) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(&mut world, component_id, entity, WithSceneChildCommand::new);
+}
+
+/// A [`Command`] that asks the [`SceneSpawner`] to spawn the scene stored in the [`WithSceneChild`] component of an entity,
+/// as children of that entity.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithSceneChildCommand {
+    /// The entity holding the [`WithSceneChild`] component, which will become the parent of the scene's roots.
+    pub parent_entity: Entity,
+}
+
+impl WithSceneChildCommand {
+    /// Creates a new [`WithSceneChildCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self { parent_entity }
+    }
+}
+
+impl Command for WithSceneChildCommand {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithSceneChild(scene)) = entity_mut.take::<WithSceneChild>() else {
+            on_cancelled("WithSceneChild");
+            return;
+        };
+
+        let Some(mut scene_spawner) = world.get_resource_mut::<SceneSpawner>() else {
+            on_missing(world, WithChildError::ResourceMissing("SceneSpawner"));
+            return;
+        };
+
+        scene_spawner.spawn_as_child(scene, self.parent_entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::bevy_ecs::{event::Events, reflect::AppTypeRegistry};
+    use crate::deps::bevy_hierarchy::{Children, Parent};
+    use bevy_asset::{AssetEvent, Assets};
+    use bevy_scene::{scene_spawner_system, DynamicScene};
+
+    use super::*;
+
+    #[test]
+    fn scene_roots_are_parented() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world.init_resource::<Assets<Scene>>();
+        world.init_resource::<Assets<DynamicScene>>();
+        world.init_resource::<Events<AssetEvent<DynamicScene>>>();
+        world.init_resource::<SceneSpawner>();
+
+        let mut scene_world = World::new();
+        scene_world.spawn_empty();
+        scene_world.spawn_empty();
+        let scene = world
+            .resource_mut::<Assets<Scene>>()
+            .add(Scene::new(scene_world));
+
+        let parent = world.spawn(WithSceneChild(scene)).id();
+        // FIXME: this should not be needed!
+        world.flush();
+        assert!(!world.entity(parent).contains::<WithSceneChild>());
+
+        scene_spawner_system(&mut world);
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 2);
+        for &child in children {
+            assert_eq!(world.get::<Parent>(child).unwrap().get(), parent);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::deps::bevy_ecs::{prelude::*, system::SystemParam};
+
+use crate::{on_missing, WithChild, WithChildError, WithChildren};
+
+/// A [`SystemParam`] for imperatively spawning children of existing entities from within systems.
+///
+/// Each method queues a command that adds the matching [`WithChild`] or [`WithChildren`] component
+/// to the parent entity, so children are spawned by exactly the same logic as when those components are
+/// added directly.
+/// This avoids needing to insert components onto a throwaway entity just to reach an existing parent.
+///
+/// If the parent entity no longer exists when the command is applied,
+/// the [`FailurePolicy`](crate::FailurePolicy) is followed.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::ChildSpawner;
+///
+/// #[derive(Component)]
+/// struct Tree;
+///
+/// #[derive(Component)]
+/// struct Fruit;
+///
+/// fn grow_fruit(trees: Query<Entity, With<Tree>>, mut spawner: ChildSpawner) {
+///     for tree in trees.iter() {
+///         spawner.spawn_child_of(tree, Fruit);
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct ChildSpawner<'w, 's> {
+    commands: Commands<'w, 's>,
+}
+
+impl<'w, 's> ChildSpawner<'w, 's> {
+    /// Spawns a child entity with the given bundle under `parent`.
+    ///
+    /// This is equivalent to adding a [`WithChild`] component to `parent`.
+    pub fn spawn_child_of<B: Bundle>(&mut self, parent: Entity, bundle: B) {
+        self.commands.add(move |world: &mut World| {
+            insert_on_parent(world, parent, WithChild(bundle));
+        });
+    }
+
+    /// Spawns a child entity under `parent` for each bundle generated by the iterator.
+    ///
+    /// This is equivalent to adding a [`WithChildren`] component to `parent`.
+    pub fn spawn_children_of<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static>(
+        &mut self,
+        parent: Entity,
+        iter: I,
+    ) {
+        self.commands.add(move |world: &mut World| {
+            insert_on_parent(world, parent, WithChildren(iter));
+        });
+    }
+}
+
+/// Inserts the given component onto the parent entity, following the missing entity policy if it doesn't exist.
+fn insert_on_parent(world: &mut World, parent: Entity, component: impl Component) {
+    let Some(mut parent_mut) = world.get_entity_mut(parent) else {
+        on_missing(world, WithChildError::ParentMissing(parent));
+        return;
+    };
+
+    parent_mut.insert(component);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::bevy_ecs::system::RunSystemOnce;
+    use crate::deps::bevy_hierarchy::Children;
+
+    use super::*;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct A;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct B(u8);
+
+    #[test]
+    fn child_spawner() {
+        fn spawn_children(parents: Query<Entity, With<A>>, mut spawner: ChildSpawner) {
+            for parent in parents.iter() {
+                spawner.spawn_child_of(parent, B(0));
+                spawner.spawn_child_of(parent, B(1));
+                spawner.spawn_children_of(parent, (2..4).map(B));
+            }
+        }
+
+        let mut world = World::new();
+        let parent = world.spawn(A).id();
+        world.run_system_once(spawn_children);
+
+        assert!(!world.entity(parent).contains::<WithChild<B>>());
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(children.len(), 4);
+        for (i, &child) in children.iter().enumerate() {
+            assert_eq!(world.get::<B>(child), Some(&B(i as u8)));
+        }
+    }
+}
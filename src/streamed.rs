@@ -0,0 +1,288 @@
+use core::iter::Peekable;
+use core::marker::PhantomData;
+
+use crate::deps::bevy_ecs::{
+    component::{ComponentHooks, ComponentId, StorageType},
+    prelude::*,
+    world::{Command, DeferredWorld},
+};
+
+use crate::{
+    mark_deferred, on_cancelled, on_missing, queue_deferred, Link, LinkChild, SpawnLinkedCommand,
+    WithChildError,
+};
+
+/// A component that, when added to an entity, will add a child entity for each item generated by the iterator,
+/// spawning at most `budget` of them per frame.
+///
+/// This smooths out the cost of spawning very large sets of children, which would otherwise cause a frame spike.
+/// The first `budget` children are spawned as soon as the component is resolved.
+/// If any items remain, the iterator is moved into a [`StreamedChildren`] component on the parent:
+/// add the [`stream_children`] system to your schedule to spawn the next `budget` children each time it runs.
+///
+/// As a result, the parent's [`Children`](bevy_hierarchy::Children) grows incrementally over several frames,
+/// although children always end up in iteration order.
+/// A budget of zero is treated as a budget of one, so that every child is eventually spawned.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{stream_children, WithChildrenStreamed};
+///
+/// #[derive(Component)]
+/// struct Tile(u32);
+///
+/// let mut world = World::new();
+/// let mut schedule = Schedule::default();
+/// schedule.add_systems(stream_children::<Tile, Vec<Tile>>);
+///
+/// let map = world
+///     .spawn(WithChildrenStreamed((0..1000).map(Tile).collect::<Vec<_>>(), 100))
+///     .id();
+/// world.flush();
+/// assert_eq!(world.get::<Children>(map).unwrap().len(), 100);
+///
+/// schedule.run(&mut world);
+/// assert_eq!(world.get::<Children>(map).unwrap().len(), 200);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WithChildrenStreamed<B: Bundle, I: IntoIterator<Item = B>>(pub I, pub usize);
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Component
+    for WithChildrenStreamed<B, I>
+where
+    I::IntoIter: Send + Sync + 'static,
+{
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_children_streamed_hook::<B, I>);
+    }
+}
+
+/// A hook that runs whenever [`WithChildrenStreamed`] is added to an entity.
+///
+/// Generates a [`WithChildrenStreamedCommand`].
+fn with_children_streamed_hook<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) where
+    I::IntoIter: Send + Sync + 'static,
+{
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithChildrenStreamedCommand::<B, I>::new,
+    );
+}
+
+/// A [`Command`] that spawns the first batch of children generated by the [`WithChildrenStreamed<B, I>`] component of an entity.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithChildrenStreamedCommand<B, I> {
+    /// The entity holding the [`WithChildrenStreamed`] component, which will become the parent.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<(B, I)>,
+}
+
+impl<B, I> WithChildrenStreamedCommand<B, I> {
+    /// Creates a new [`WithChildrenStreamedCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle, I: IntoIterator<Item = B> + Send + Sync + 'static> Command
+    for WithChildrenStreamedCommand<B, I>
+where
+    I::IntoIter: Send + Sync + 'static,
+{
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithChildrenStreamed(iter, budget)) =
+            entity_mut.take::<WithChildrenStreamed<B, I>>()
+        else {
+            on_cancelled("WithChildrenStreamed");
+            return;
+        };
+
+        let budget = budget.max(1);
+        let mut iter = iter.into_iter().peekable();
+        let parent_exists = Link::spawn_linked(
+            world,
+            self.parent_entity,
+            iter.by_ref().take(budget),
+            |_| {},
+        );
+
+        if parent_exists && iter.peek().is_some() {
+            world
+                .entity_mut(self.parent_entity)
+                .insert(StreamedChildren(iter, budget));
+        }
+    }
+}
+
+/// A component holding the remaining items of a [`WithChildrenStreamed`] iterator,
+/// which will be spawned as children of this entity by the [`stream_children`] system, `budget` at a time.
+///
+/// This is added by [`WithChildrenStreamed`], and removed once the iterator is exhausted.
+/// As with [`WithChildrenStreamed`], a budget of zero is treated as a budget of one.
+#[derive(Component, Debug)]
+pub struct StreamedChildren<B: Bundle, I: Iterator<Item = B> + Send + Sync + 'static>(
+    pub Peekable<I>,
+    pub usize,
+);
+
+/// A system that spawns the next batch of children for each [`StreamedChildren`] component
+/// created by a [`WithChildrenStreamed<B, I>`].
+///
+/// Once an iterator is exhausted, its [`StreamedChildren`] component is removed.
+pub fn stream_children<B: Bundle, I: IntoIterator<Item = B>>(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut StreamedChildren<B, I::IntoIter>)>,
+) where
+    I::IntoIter: Send + Sync + 'static,
+{
+    for (parent_entity, mut streamed_children) in query.iter_mut() {
+        let StreamedChildren(iter, budget) = &mut *streamed_children;
+
+        let bundles: Vec<B> = iter.by_ref().take((*budget).max(1)).collect();
+        if !bundles.is_empty() {
+            commands.add(SpawnLinkedCommand {
+                parent_entity,
+                bundles,
+            });
+        }
+
+        if iter.peek().is_none() {
+            commands
+                .entity(parent_entity)
+                .remove::<StreamedChildren<B, I::IntoIter>>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::bevy_ecs::component::{ComponentHooks, StorageType};
+    use crate::deps::bevy_hierarchy::{Children, DespawnRecursiveExt};
+
+    use super::*;
+    use crate::FailurePolicy;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct B(u8);
+
+    fn children_values(world: &World, parent: Entity) -> Vec<u8> {
+        world
+            .get::<Children>(parent)
+            .map(|children| {
+                children
+                    .iter()
+                    .map(|&child| world.get::<B>(child).unwrap().0)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn streamed_over_several_frames() {
+        let mut world = World::default();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(stream_children::<B, Vec<B>>);
+
+        let parent = world
+            .spawn(WithChildrenStreamed((0..5).map(B).collect::<Vec<_>>(), 2))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world
+            .entity(parent)
+            .contains::<WithChildrenStreamed<B, Vec<B>>>());
+        assert_eq!(children_values(&world, parent), [0, 1]);
+
+        schedule.run(&mut world);
+        assert_eq!(children_values(&world, parent), [0, 1, 2, 3]);
+        assert!(world
+            .entity(parent)
+            .contains::<StreamedChildren<B, std::vec::IntoIter<B>>>());
+
+        schedule.run(&mut world);
+        assert_eq!(children_values(&world, parent), [0, 1, 2, 3, 4]);
+        assert!(!world
+            .entity(parent)
+            .contains::<StreamedChildren<B, std::vec::IntoIter<B>>>());
+    }
+
+    #[test]
+    fn zero_budget_still_streams() {
+        let mut world = World::default();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(stream_children::<B, Vec<B>>);
+
+        let parent = world
+            .spawn(WithChildrenStreamed((0..2).map(B).collect::<Vec<_>>(), 0))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+        assert_eq!(children_values(&world, parent), [0]);
+
+        schedule.run(&mut world);
+        assert_eq!(children_values(&world, parent), [0, 1]);
+        assert!(!world
+            .entity(parent)
+            .contains::<StreamedChildren<B, std::vec::IntoIter<B>>>());
+    }
+
+    #[test]
+    fn parent_despawned_while_streaming() {
+        /// A component whose hook despawns the given parent via commands.
+        struct DespawnsParent(Entity);
+
+        impl Component for DespawnsParent {
+            const STORAGE_TYPE: StorageType = StorageType::Table;
+
+            fn register_component_hooks(hooks: &mut ComponentHooks) {
+                hooks.on_add(|mut world, entity, _| {
+                    let parent = world.get::<DespawnsParent>(entity).unwrap().0;
+                    if let Some(parent_commands) = world.commands().get_entity(parent) {
+                        parent_commands.despawn_recursive();
+                    }
+                });
+            }
+        }
+
+        let mut world = World::default();
+        world.insert_resource(FailurePolicy::Ignore);
+
+        // The despawn is applied when spawning the next child flushes the world
+        let parent = world.spawn_empty().id();
+        world.entity_mut(parent).insert(WithChildrenStreamed(
+            vec![
+                DespawnsParent(parent),
+                DespawnsParent(parent),
+                DespawnsParent(parent),
+            ],
+            2,
+        ));
+        world.flush();
+
+        assert!(world.get_entity(parent).is_none());
+        let mut query = world.query::<&DespawnsParent>();
+        assert_eq!(query.iter(&world).count(), 0);
+    }
+}
@@ -0,0 +1,254 @@
+use core::marker::PhantomData;
+
+use crate::deps::bevy_ecs::{
+    component::{ComponentHooks, ComponentId, StorageType},
+    prelude::*,
+    world::{Command, DeferredWorld},
+};
+
+use crate::{
+    mark_deferred, on_cancelled, queue_deferred, FailurePolicy, ManualResolution, WithChildError,
+};
+
+/// A wrapper that resolves one of this crate's components with [`FailurePolicy::Panic`],
+/// regardless of the [`FailurePolicy`] resource.
+///
+/// This makes specific, critical spawns loud, even in release builds,
+/// while leaving the rest of the app lenient.
+/// The wrapper is removed, and the wrapped component is inserted onto the entity and resolved straight away,
+/// along with any components its resolution adds in turn (such as a nested [`WithChild`](crate::WithChild)).
+/// Afterwards, the [`FailurePolicy`] resource is restored to its previous state,
+/// even if resolving the wrapped component panicked.
+///
+/// Like every other component in this crate, the wrapper itself is deferred:
+/// with [`ManualResolution`], it is resolved by [`resolve_deferred`](crate::resolve_deferred).
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{FailurePolicy, Strict, WithChild};
+///
+/// #[derive(Component)]
+/// struct SaveIndicator;
+///
+/// let mut world = World::new();
+/// world.insert_resource(FailurePolicy::Ignore);
+///
+/// let hud = world.spawn(Strict(WithChild(SaveIndicator))).id();
+/// world.flush();
+///
+/// assert_eq!(world.get::<Children>(hud).unwrap().len(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Strict<C: Component>(pub C);
+
+impl<C: Component> Component for Strict<C> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(strict_hook::<C>);
+    }
+}
+
+/// A hook that runs whenever [`Strict`] is added to an entity.
+///
+/// Generates a [`StrictCommand`].
+fn strict_hook<C: Component>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(&mut world, component_id, entity, StrictCommand::<C>::new);
+}
+
+/// A [`Command`] that unwraps the [`Strict<C>`] component of an entity,
+/// then resolves the wrapped component with [`FailurePolicy::Panic`].
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+///
+/// If the [`Strict<C>`] component was removed before this command was applied, this does nothing.
+///
+/// # Panics
+///
+/// Panics if the entity is missing,
+/// or if resolving the wrapped component fails for any reason covered by the [`FailurePolicy`].
+pub struct StrictCommand<C> {
+    /// The entity holding the [`Strict`] component.
+    pub entity: Entity,
+    _phantom: PhantomData<C>,
+}
+
+impl<C> StrictCommand<C> {
+    /// Creates a new [`StrictCommand`] targeting the given entity.
+    pub const fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<C: Component> Command for StrictCommand<C> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.entity) else {
+            panic!("{}", WithChildError::EntityMissing(self.entity));
+        };
+
+        let Some(Strict(component)) = entity_mut.take::<Strict<C>>() else {
+            on_cancelled("Strict");
+            return;
+        };
+
+        let scope = StrictScope::enter(world);
+        scope.world.entity_mut(self.entity).insert(component);
+        // Only applies the commands queued since this command started: those resolving the wrapped component
+        scope.world.flush();
+    }
+}
+
+/// Overrides the world's [`FailurePolicy`] with [`FailurePolicy::Panic`], and suspends [`ManualResolution`],
+/// until it is dropped.
+///
+/// Restoring the previous state on drop, rather than at the end of [`StrictCommand::apply`],
+/// means that it is also restored when resolution panics, as it is meant to.
+struct StrictScope<'w> {
+    world: &'w mut World,
+    previous_policy: Option<FailurePolicy>,
+    manual_resolution: Option<ManualResolution>,
+}
+
+impl<'w> StrictScope<'w> {
+    fn enter(world: &'w mut World) -> Self {
+        let previous_policy = world.remove_resource::<FailurePolicy>();
+        world.insert_resource(FailurePolicy::Panic);
+        // The wrapped component must be resolved now, rather than whenever manual resolution next happens
+        let manual_resolution = world.remove_resource::<ManualResolution>();
+
+        Self {
+            world,
+            previous_policy,
+            manual_resolution,
+        }
+    }
+}
+
+impl Drop for StrictScope<'_> {
+    fn drop(&mut self) {
+        if let Some(manual_resolution) = self.manual_resolution.take() {
+            self.world.insert_resource(manual_resolution);
+        }
+        match self.previous_policy {
+            Some(policy) => self.world.insert_resource(policy),
+            None => {
+                self.world.remove_resource::<FailurePolicy>();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::bevy_hierarchy::Children;
+
+    use super::*;
+    use crate::{resolve_deferred, MaybeChild, WithChild};
+
+    #[derive(Component, PartialEq, Debug)]
+    struct A;
+
+    #[test]
+    fn strict_with_child() {
+        let mut world = World::default();
+        world.insert_resource(FailurePolicy::Warn);
+
+        let parent = world.spawn(Strict(WithChild(WithChild(A)))).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world.entity(parent).contains::<Strict<WithChild<A>>>());
+        let child = world.get::<Children>(parent).unwrap()[0];
+        let grandchild = world.get::<Children>(child).unwrap()[0];
+        assert_eq!(world.get::<A>(grandchild), Some(&A));
+        assert_eq!(
+            world.get_resource::<FailurePolicy>(),
+            Some(&FailurePolicy::Warn)
+        );
+    }
+
+    #[test]
+    fn strict_manual_resolution() {
+        let mut world = World::default();
+        world.init_resource::<ManualResolution>();
+
+        let parent = world.spawn(Strict(WithChild(A))).id();
+        world.flush();
+        assert!(world.get::<Children>(parent).is_none());
+
+        resolve_deferred(&mut world);
+        assert_eq!(world.get::<Children>(parent).unwrap().len(), 1);
+        assert!(world.get_resource::<FailurePolicy>().is_none());
+        assert!(world.get_resource::<ManualResolution>().is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "Entity not found")]
+    fn strict_missing_entity_panics() {
+        let mut world = World::default();
+        // The release default: failures would otherwise be silently ignored
+        world.insert_resource(FailurePolicy::Ignore);
+
+        let missing = world.spawn_empty().id();
+        world.despawn(missing);
+        StrictCommand::<WithChild<A>>::new(missing).apply(&mut world);
+    }
+
+    #[test]
+    #[should_panic(expected = "Parent entity not found")]
+    fn strict_overrides_policy() {
+        let mut world = World::default();
+        world.insert_resource(FailurePolicy::Ignore);
+
+        let missing_parent = world.spawn_empty().id();
+        world.despawn(missing_parent);
+        world.spawn(Strict(MaybeChild(Some((missing_parent, A)))));
+        world.flush();
+    }
+
+    #[test]
+    fn strict_cancelled() {
+        let mut world = World::default();
+
+        let entity = world.spawn(Strict(WithChild(A))).id();
+        world.entity_mut(entity).remove::<Strict<WithChild<A>>>();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(world.get::<Children>(entity).is_none());
+    }
+
+    #[test]
+    fn strict_restores_policy_after_panic() {
+        let mut world = World::default();
+        world.insert_resource(FailurePolicy::Ignore);
+        world.init_resource::<ManualResolution>();
+
+        let missing_parent = world.spawn_empty().id();
+        world.despawn(missing_parent);
+        let entity = world
+            .spawn(Strict(MaybeChild(Some((missing_parent, A)))))
+            .id();
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            StrictCommand::<MaybeChild<A>>::new(entity).apply(&mut world);
+        }));
+        assert!(result.is_err());
+        assert_eq!(
+            world.get_resource::<FailurePolicy>(),
+            Some(&FailurePolicy::Ignore)
+        );
+        assert!(world.get_resource::<ManualResolution>().is_some());
+    }
+}
@@ -0,0 +1,34 @@
+//! Helpers shared between this crate's unit tests.
+
+use std::sync::{Arc, Mutex};
+
+use crate::deps::bevy_utils::tracing::subscriber::with_default;
+
+/// Collects formatted log output into a shared buffer.
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs `f`, returning everything it logged.
+pub(crate) fn capture_logs(f: impl FnOnce()) -> String {
+    let buffer = LogBuffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(move || writer.clone())
+        .with_ansi(false)
+        .finish();
+    with_default(subscriber, f);
+
+    let logs = buffer.0.lock().unwrap();
+    String::from_utf8_lossy(&logs).into_owned()
+}
@@ -0,0 +1,258 @@
+//! Assertions for checking the shape of spawned hierarchies in tests.
+
+use core::fmt::Debug;
+
+use crate::deps::bevy_ecs::prelude::*;
+use crate::deps::bevy_hierarchy::Children;
+use crate::deps::bevy_utils::get_short_name;
+
+use crate::dump_hierarchy;
+
+/// Asserts that the hierarchy rooted at an entity matches an expected tree.
+///
+/// The macro takes a [`World`], the root [`Entity`] and the expected tree.
+/// Each node of the tree is written as a bracketed list of component types,
+/// optionally followed by a braced, comma-separated list of its child nodes:
+///
+/// ```text
+/// [Menu] {
+///     [Button, Label = Label("Play")],
+///     [Button] { [Icon] },
+/// }
+/// ```
+///
+/// A component written as a bare type only needs to be present on the entity,
+/// while `Type = value` also requires it to be equal to `value`,
+/// which requires the component to implement [`PartialEq`] and [`Debug`].
+/// Components that aren't listed are ignored, but the number and order of children must match exactly:
+/// a node without braces must have no children.
+///
+/// On failure, the panic message describes the path to the mismatching entity
+/// and includes the actual hierarchy, as rendered by [`dump_hierarchy`].
+///
+/// This is only available with the `testing` feature.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use i_cant_believe_its_not_bsn::{assert_hierarchy, WithChild, WithChildren};
+///
+/// #[derive(Component)]
+/// struct Menu;
+///
+/// #[derive(Component)]
+/// struct Button;
+///
+/// #[derive(Component, PartialEq, Debug)]
+/// struct Label(&'static str);
+///
+/// let mut world = World::new();
+/// let root = world
+///     .spawn((
+///         Menu,
+///         WithChildren(["Play", "Quit"].map(|text| (Button, WithChild(Label(text))))),
+///     ))
+///     .id();
+/// world.flush();
+///
+/// assert_hierarchy!(world, root, [Menu] {
+///     [Button] { [Label = Label("Play")] },
+///     [Button] { [Label = Label("Quit")] },
+/// });
+/// ```
+#[macro_export]
+macro_rules! assert_hierarchy {
+    ($world:expr, $root:expr, $($tree:tt)+) => {
+        $crate::assert_hierarchy_matches(&$world, $root, &$crate::__expected_node!($($tree)+))
+    };
+}
+
+/// Builds an [`ExpectedNode`] from a single node of the [`assert_hierarchy!`] syntax.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __expected_node {
+    ([$($component:ty $(= $value:expr)?),* $(,)?] $({ $($children:tt)* })?) => {
+        $crate::ExpectedNode {
+            components: vec![$($crate::__expected_component!($component $(= $value)?)),*],
+            children: $crate::__expected_children!([] $($($children)*)?),
+        }
+    };
+}
+
+/// Builds an [`ExpectedComponent`] from a single component of the [`assert_hierarchy!`] syntax.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __expected_component {
+    ($component:ty) => {
+        $crate::ExpectedComponent::present::<$component>()
+    };
+    ($component:ty = $value:expr) => {
+        $crate::ExpectedComponent::equal_to::<$component>($value)
+    };
+}
+
+/// Collects a comma-separated list of nodes of the [`assert_hierarchy!`] syntax into a [`Vec`] of [`ExpectedNode`]s.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __expected_children {
+    ([$($done:expr),*]) => {
+        vec![$($done),*]
+    };
+    ([$($done:expr),*] [$($node:tt)*] { $($children:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::__expected_children!(
+            [$($done,)* $crate::__expected_node!([$($node)*] { $($children)* })]
+            $($($rest)*)?
+        )
+    };
+    ([$($done:expr),*] [$($node:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::__expected_children!(
+            [$($done,)* $crate::__expected_node!([$($node)*])]
+            $($($rest)*)?
+        )
+    };
+}
+
+/// A node of the tree expected by [`assert_hierarchy!`].
+///
+/// This is usually built by the macro, but can be constructed by hand and passed to [`assert_hierarchy_matches`].
+pub struct ExpectedNode {
+    /// The components that the entity must have.
+    pub components: Vec<ExpectedComponent>,
+    /// The expected children of the entity, in order.
+    pub children: Vec<ExpectedNode>,
+}
+
+/// Checks a single component of an entity, returning a description of the mismatch if there is one.
+type ComponentCheck = Box<dyn Fn(&World, Entity) -> Result<(), String>>;
+
+/// A component that an [`ExpectedNode`] must have.
+pub struct ExpectedComponent {
+    name: String,
+    check: ComponentCheck,
+}
+
+impl ExpectedComponent {
+    /// Expects a component of type `C` to be present, regardless of its value.
+    pub fn present<C: Component>() -> Self {
+        Self {
+            name: get_short_name(core::any::type_name::<C>()),
+            check: Box::new(|world, entity| match world.get::<C>(entity) {
+                Some(_) => Ok(()),
+                None => Err("is missing".to_string()),
+            }),
+        }
+    }
+
+    /// Expects a component of type `C` to be present and equal to `expected`.
+    pub fn equal_to<C: Component + PartialEq + Debug>(expected: C) -> Self {
+        Self {
+            name: get_short_name(core::any::type_name::<C>()),
+            check: Box::new(move |world, entity| match world.get::<C>(entity) {
+                Some(actual) if *actual == expected => Ok(()),
+                Some(actual) => Err(format!("is {actual:?}, expected {expected:?}")),
+                None => Err("is missing".to_string()),
+            }),
+        }
+    }
+}
+
+/// Asserts that the hierarchy rooted at `root` matches `expected`.
+///
+/// This is the function behind [`assert_hierarchy!`], which should generally be preferred.
+///
+/// # Panics
+///
+/// Panics if the hierarchy doesn't match, describing the first mismatch found.
+#[track_caller]
+pub fn assert_hierarchy_matches(world: &World, root: Entity, expected: &ExpectedNode) {
+    if let Err(mismatch) = check_node(world, root, expected, "root") {
+        panic!(
+            "hierarchy mismatch: {mismatch}\nactual hierarchy:\n{}",
+            dump_hierarchy(world, root)
+        );
+    }
+}
+
+fn check_node(
+    world: &World,
+    entity: Entity,
+    expected: &ExpectedNode,
+    path: &str,
+) -> Result<(), String> {
+    if world.get_entity(entity).is_none() {
+        return Err(format!("{path} ({entity}) does not exist"));
+    }
+
+    for component in &expected.components {
+        (component.check)(world, entity)
+            .map_err(|reason| format!("{} on {path} ({entity}) {reason}", component.name))?;
+    }
+
+    let children = world
+        .get::<Children>(entity)
+        .map_or(&[][..], |children| &children[..]);
+    if children.len() != expected.children.len() {
+        return Err(format!(
+            "{path} ({entity}) has {} children, expected {}",
+            children.len(),
+            expected.children.len()
+        ));
+    }
+
+    for (i, (&child, expected_child)) in children.iter().zip(&expected.children).enumerate() {
+        check_node(world, child, expected_child, &format!("{path} > child {i}"))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{WithChild, WithChildren};
+
+    #[derive(Component)]
+    struct A;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct B(u8);
+
+    #[test]
+    fn matching_hierarchy() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn((A, WithChildren(vec![(A, B(1)), (A, B(2))])))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_hierarchy!(world, parent, [A] {
+            [A, B = B(1)],
+            [B],
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "B on root > child 0 > child 0")]
+    fn mismatched_value() {
+        let mut world = World::default();
+
+        let parent = world.spawn(WithChild(WithChild(B(1)))).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_hierarchy!(world, parent, [] { [] { [B = B(2)] } });
+    }
+
+    #[test]
+    #[should_panic(expected = "has 2 children, expected 1")]
+    fn mismatched_child_count() {
+        let mut world = World::default();
+
+        let parent = world.spawn(WithChildren(vec![A, A])).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert_hierarchy!(world, parent, [] { [A] });
+    }
+}
@@ -0,0 +1,134 @@
+//! Immediate [`GlobalTransform`] computation for spawned children, avoiding a frame at the wrong position.
+//!
+//! This is enabled by the `bevy_transform` feature,
+//! and applied by [`Link`](crate::Link) whenever it links a child, so that it covers every command in this crate.
+
+use crate::deps::bevy_ecs::prelude::*;
+use bevy_transform::components::{GlobalTransform, Transform};
+
+/// Computes the [`GlobalTransform`] of children with a [`Transform`] as soon as they are spawned.
+///
+/// Children are spawned by commands, which may be applied after transform propagation has run for this frame.
+/// Without this, a child with a [`Transform`] but a missing or default [`GlobalTransform`]
+/// is rendered at the origin until propagation runs again, causing a one-frame flash.
+///
+/// The computed value is exactly what propagation would produce if the parent's [`GlobalTransform`] is up to date,
+/// so propagation keeps it unchanged.
+pub(crate) struct ChildTransforms {
+    parent_global: GlobalTransform,
+}
+
+impl ChildTransforms {
+    /// Creates a [`ChildTransforms`] for children of the given parent.
+    ///
+    /// Parents without a [`GlobalTransform`] are treated as being at the origin.
+    pub(crate) fn new(world: &World, parent: Entity) -> Self {
+        Self {
+            parent_global: world
+                .get::<GlobalTransform>(parent)
+                .copied()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Sets the [`GlobalTransform`] of `child` from its [`Transform`], if it has one.
+    ///
+    /// Returns `child`, so that it can be passed on.
+    pub(crate) fn propagate_to<'w>(&self, mut child: EntityWorldMut<'w>) -> EntityWorldMut<'w> {
+        if let Some(&transform) = child.get::<Transform>() {
+            child.insert(self.parent_global.mul_transform(transform));
+        }
+        child
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::bevy_ecs::system::RunSystemOnce;
+    use crate::deps::bevy_hierarchy::Children;
+    use bevy_transform::systems::{propagate_transforms, sync_simple_transforms};
+
+    use super::*;
+    use crate::{EntityWorldMutExt, WithChild, WithChildAt};
+
+    #[derive(Component)]
+    struct A;
+
+    #[test]
+    fn child_global_transform_is_immediate() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn((
+                Transform::from_xyz(1., 0., 0.),
+                GlobalTransform::from_xyz(1., 0., 0.),
+                WithChild((
+                    Transform::from_xyz(0., 2., 0.),
+                    WithChild(Transform::from_xyz(0., 0., 3.)),
+                )),
+            ))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let child = world.get::<Children>(parent).unwrap()[0];
+        let grandchild = world.get::<Children>(child).unwrap()[0];
+        let expected_child = GlobalTransform::from_xyz(1., 2., 0.);
+        let expected_grandchild = GlobalTransform::from_xyz(1., 2., 3.);
+        assert_eq!(world.get::<GlobalTransform>(child), Some(&expected_child));
+        assert_eq!(
+            world.get::<GlobalTransform>(grandchild),
+            Some(&expected_grandchild)
+        );
+
+        // Propagation agrees with the values computed on spawn
+        world.run_system_once(sync_simple_transforms);
+        world.run_system_once(propagate_transforms);
+        assert_eq!(world.get::<GlobalTransform>(child), Some(&expected_child));
+        assert_eq!(
+            world.get::<GlobalTransform>(grandchild),
+            Some(&expected_grandchild)
+        );
+    }
+
+    #[test]
+    fn children_without_transforms_are_untouched() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn((GlobalTransform::from_xyz(1., 0., 0.), WithChild(A)))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        let child = world.get::<Children>(parent).unwrap()[0];
+        assert!(!world.entity(child).contains::<GlobalTransform>());
+    }
+
+    #[test]
+    fn every_link_is_covered() {
+        let mut world = World::default();
+
+        let parent = world
+            .spawn((
+                GlobalTransform::from_xyz(1., 0., 0.),
+                WithChildAt(0, Transform::from_xyz(0., 2., 0.)),
+            ))
+            .id();
+        // FIXME: this should not be needed!
+        world.flush();
+        world
+            .entity_mut(parent)
+            .with_children_now([Transform::from_xyz(0., 0., 3.)]);
+
+        let children = world.get::<Children>(parent).unwrap();
+        assert_eq!(
+            world.get::<GlobalTransform>(children[0]),
+            Some(&GlobalTransform::from_xyz(1., 2., 0.))
+        );
+        assert_eq!(
+            world.get::<GlobalTransform>(children[1]),
+            Some(&GlobalTransform::from_xyz(1., 0., 3.))
+        );
+    }
+}
@@ -0,0 +1,348 @@
+use core::marker::PhantomData;
+
+use crate::deps::bevy_ecs::{
+    component::{ComponentHooks, ComponentId, StorageType},
+    prelude::*,
+    world::{Command, DeferredWorld},
+};
+
+use crate::{
+    mark_deferred, on_cancelled, on_missing, queue_deferred, Link, LinkChild, WithChildError,
+};
+
+/// A node in a tree of bundles, which can be spawned as an entity hierarchy using [`spawn_tree`].
+///
+/// Unlike nesting [`WithChild`](crate::WithChild) and [`WithChildren`](crate::WithChildren),
+/// whose shape is fixed by their types, the shape of a [`TreeNode`] can be decided entirely at runtime,
+/// making it a good fit for tree-shaped data such as parsed documents or procedurally generated structures.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TreeNode<B: Bundle> {
+    /// The bundle to insert on the entity spawned for this node.
+    pub bundle: B,
+    /// The nodes to spawn as children of this node, in order.
+    pub children: Vec<TreeNode<B>>,
+}
+
+impl<B: Bundle> TreeNode<B> {
+    /// Creates a new [`TreeNode`] with no children.
+    pub const fn leaf(bundle: B) -> Self {
+        Self {
+            bundle,
+            children: Vec::new(),
+        }
+    }
+
+    /// Creates a new [`TreeNode`] with the given children.
+    pub fn new(bundle: B, children: impl IntoIterator<Item = TreeNode<B>>) -> Self {
+        Self {
+            bundle,
+            children: children.into_iter().collect(),
+        }
+    }
+}
+
+/// A component that, when added to an entity, will spawn the given [`TreeNode`] as a child of that entity,
+/// along with all of its descendants.
+///
+/// The whole subtree is spawned by a single command, so it is complete after one flush, however deep it is.
+///
+/// Usually constructed via [`spawn_tree`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct WithDescendants<B: Bundle>(pub TreeNode<B>);
+
+impl<B: Bundle> Component for WithDescendants<B> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(with_descendants_hook::<B>);
+    }
+}
+
+/// A hook that runs whenever [`WithDescendants`] is added to an entity.
+///
+/// Generates a [`WithDescendantsCommand`].
+fn with_descendants_hook<B: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        WithDescendantsCommand::<B>::new,
+    );
+}
+
+/// A [`Command`] that spawns the tree stored in the [`WithDescendants<B>`] component of an entity.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct WithDescendantsCommand<B> {
+    /// The entity holding the [`WithDescendants`] component, which will become the parent of the tree's root.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> WithDescendantsCommand<B> {
+    /// Creates a new [`WithDescendantsCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle> Command for WithDescendantsCommand<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(WithDescendants(root)) = entity_mut.take::<WithDescendants<B>>() else {
+            on_cancelled("WithDescendants");
+            return;
+        };
+
+        spawn_node(world, self.parent_entity, root);
+    }
+}
+
+/// A component that, when added to an entity, will spawn the given [`TreeNode`] and all of its descendants
+/// as a subtree beneath that entity if it is [`Some`], or nothing if it is [`None`].
+///
+/// This is the conditional counterpart of [`WithDescendants`], as [`Maybe`](crate::Maybe) is for components:
+/// it allows entire branches of a declaratively built hierarchy to be included or left out at runtime.
+/// In both cases, the component is removed once it has been resolved.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{MaybeSubtree, TreeNode};
+///
+/// #[derive(Component)]
+/// enum Menu {
+///     Settings,
+///     Debug,
+///     ShowColliders,
+/// }
+///
+/// let debug_menu = |enabled: bool| {
+///     MaybeSubtree(enabled.then(|| TreeNode::new(Menu::Debug, [TreeNode::leaf(Menu::ShowColliders)])))
+/// };
+///
+/// let mut world = World::new();
+/// let release = world.spawn((Menu::Settings, debug_menu(false))).id();
+/// let development = world.spawn((Menu::Settings, debug_menu(true))).id();
+/// world.flush();
+///
+/// assert!(world.get::<Children>(release).is_none());
+/// assert_eq!(world.get::<Children>(development).unwrap().len(), 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct MaybeSubtree<B: Bundle>(pub Option<TreeNode<B>>);
+
+impl<B: Bundle> Component for MaybeSubtree<B> {
+    /// This is a sparse set component as it's only ever added and removed, never iterated over.
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        mark_deferred::<Self>();
+        hooks.on_add(maybe_subtree_hook::<B>);
+    }
+}
+
+/// A hook that runs whenever [`MaybeSubtree`] is added to an entity.
+///
+/// Generates a [`MaybeSubtreeCommand`].
+fn maybe_subtree_hook<B: Bundle>(
+    mut world: DeferredWorld<'_>,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    // Component hooks can't perform structural changes, so we need to rely on commands.
+    queue_deferred(
+        &mut world,
+        component_id,
+        entity,
+        MaybeSubtreeCommand::<B>::new,
+    );
+}
+
+/// A [`Command`] that spawns the tree stored in the [`MaybeSubtree<B>`] component of an entity, if there is one.
+///
+/// This is queued automatically when the component is added, but can also be queued manually.
+pub struct MaybeSubtreeCommand<B> {
+    /// The entity holding the [`MaybeSubtree`] component, which will become the parent of the tree's root.
+    pub parent_entity: Entity,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> MaybeSubtreeCommand<B> {
+    /// Creates a new [`MaybeSubtreeCommand`] targeting the given entity.
+    pub const fn new(parent_entity: Entity) -> Self {
+        Self {
+            parent_entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: Bundle> Command for MaybeSubtreeCommand<B> {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity_mut) = world.get_entity_mut(self.parent_entity) else {
+            on_missing(world, WithChildError::ParentMissing(self.parent_entity));
+            return;
+        };
+
+        let Some(MaybeSubtree(maybe_root)) = entity_mut.take::<MaybeSubtree<B>>() else {
+            on_cancelled("MaybeSubtree");
+            return;
+        };
+
+        if let Some(root) = maybe_root {
+            spawn_node(world, self.parent_entity, root);
+        }
+    }
+}
+
+/// A node in a tree of bundles, which can be spawned along with all of its descendants by [`spawn_node`].
+pub(crate) trait SpawnableNode: Sized {
+    /// Spawns the entity for this node, without linking it to a parent,
+    /// returning it along with the nodes to spawn as its children.
+    fn spawn_entity(self, world: &mut World) -> (Entity, Vec<Self>);
+}
+
+impl<B: Bundle> SpawnableNode for TreeNode<B> {
+    fn spawn_entity(self, world: &mut World) -> (Entity, Vec<Self>) {
+        let TreeNode { bundle, children } = self;
+        (Link::spawn_child(world, bundle).id(), children)
+    }
+}
+
+/// Spawns `node` as a child of `parent_entity`, then recursively spawns its children beneath it.
+pub(crate) fn spawn_node(world: &mut World, parent_entity: Entity, node: impl SpawnableNode) {
+    let (node_entity, children) = node.spawn_entity(world);
+
+    if !Link::link_child(world, parent_entity, node_entity) {
+        return;
+    }
+
+    for child in children {
+        spawn_node(world, node_entity, child);
+    }
+}
+
+/// Creates a [`WithDescendants`] component, which spawns `root` and all of its descendants
+/// as a subtree beneath the entity it is added to.
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+/// use bevy_hierarchy::Children;
+/// use i_cant_believe_its_not_bsn::{spawn_tree, TreeNode};
+///
+/// #[derive(Component)]
+/// struct Directory(&'static str);
+///
+/// let tree = TreeNode::new(
+///     Directory("src"),
+///     [TreeNode::leaf(Directory("bin")), TreeNode::leaf(Directory("tests"))],
+/// );
+///
+/// let mut world = World::new();
+/// let workspace = world.spawn(spawn_tree(tree)).id();
+/// world.flush();
+///
+/// let src = world.get::<Children>(workspace).unwrap()[0];
+/// assert_eq!(world.get::<Children>(src).unwrap().len(), 2);
+/// ```
+pub fn spawn_tree<B: Bundle>(root: TreeNode<B>) -> WithDescendants<B> {
+    WithDescendants(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::bevy_hierarchy::Children;
+
+    use super::*;
+    use crate::{assert_hierarchy, dump_hierarchy};
+
+    #[derive(Component, PartialEq, Debug)]
+    struct N(u8);
+
+    #[test]
+    fn unbalanced_tree() {
+        let mut world = World::default();
+
+        let tree = TreeNode::new(
+            N(0),
+            [
+                TreeNode::new(N(1), [TreeNode::leaf(N(3)), TreeNode::leaf(N(4))]),
+                TreeNode::leaf(N(2)),
+            ],
+        );
+        let parent = world.spawn(spawn_tree(tree)).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world.entity(parent).contains::<WithDescendants<N>>());
+        assert_eq!(
+            dump_hierarchy(&world, parent),
+            "- <empty>\n  - N\n    - N\n      - N\n      - N\n    - N\n"
+        );
+
+        let children = |entity: Entity| -> Vec<Entity> {
+            world
+                .get::<Children>(entity)
+                .map(|children| children.to_vec())
+                .unwrap_or_default()
+        };
+        let values = |entities: Vec<Entity>| -> Vec<u8> {
+            entities
+                .into_iter()
+                .map(|entity| world.get::<N>(entity).unwrap().0)
+                .collect()
+        };
+
+        let root = children(parent);
+        assert_eq!(values(root.clone()), [0]);
+        let level_two = children(root[0]);
+        assert_eq!(values(level_two.clone()), [1, 2]);
+        assert_eq!(values(children(level_two[0])), [3, 4]);
+        assert!(children(level_two[1]).is_empty());
+    }
+
+    #[test]
+    fn maybe_subtree() {
+        let mut world = World::default();
+
+        let tree = || {
+            TreeNode::new(
+                N(0),
+                [
+                    TreeNode::new(N(1), [TreeNode::leaf(N(3))]),
+                    TreeNode::leaf(N(2)),
+                ],
+            )
+        };
+        let with_subtree = world.spawn(MaybeSubtree(Some(tree()))).id();
+        let without_subtree = world.spawn(MaybeSubtree::<N>(None)).id();
+        // FIXME: this should not be needed!
+        world.flush();
+
+        assert!(!world.entity(with_subtree).contains::<MaybeSubtree<N>>());
+        assert_hierarchy!(world, with_subtree, [] {
+            [N = N(0)] {
+                [N = N(1)] { [N = N(3)] },
+                [N = N(2)],
+            },
+        });
+
+        assert!(!world.entity(without_subtree).contains::<MaybeSubtree<N>>());
+        assert_hierarchy!(world, without_subtree, []);
+    }
+}
@@ -0,0 +1,66 @@
+//! Tests for the derive macros, which are only available with the `derive` feature.
+#![cfg(feature = "derive")]
+
+use bevy_ecs::prelude::*;
+use i_cant_believe_its_not_bsn::{MaybeBundle, MaybeChain};
+
+#[derive(Component, PartialEq, Debug)]
+struct A(u8);
+
+#[derive(Component, PartialEq, Debug)]
+struct B(u8);
+
+#[derive(Component, PartialEq, Debug)]
+struct C(u8);
+
+#[derive(MaybeBundle)]
+struct Partial {
+    a: Option<A>,
+    b: Option<B>,
+    c: Option<(C,)>,
+}
+
+#[derive(MaybeBundle)]
+struct PartialTuple(Option<A>, Option<B>);
+
+#[test]
+fn derived_maybe_bundle() {
+    let mut world = World::new();
+
+    let all = world
+        .spawn(
+            Partial {
+                a: Some(A(1)),
+                b: Some(B(2)),
+                c: Some((C(3),)),
+            }
+            .into_maybe_chain(),
+        )
+        .id();
+    let some = world
+        .spawn(MaybeChain::from(Partial {
+            a: None,
+            b: Some(B(4)),
+            c: None,
+        }))
+        .id();
+    let tuple = world
+        .spawn(PartialTuple(Some(A(5)), None).into_maybe_chain())
+        .id();
+    world.flush();
+
+    let entity = world.entity(all);
+    assert_eq!(entity.get::<A>(), Some(&A(1)));
+    assert_eq!(entity.get::<B>(), Some(&B(2)));
+    assert_eq!(entity.get::<C>(), Some(&C(3)));
+    assert!(!entity.contains::<MaybeChain<(Option<A>, Option<B>, Option<(C,)>)>>());
+
+    let entity = world.entity(some);
+    assert!(!entity.contains::<A>());
+    assert_eq!(entity.get::<B>(), Some(&B(4)));
+    assert!(!entity.contains::<C>());
+
+    let entity = world.entity(tuple);
+    assert_eq!(entity.get::<A>(), Some(&A(5)));
+    assert!(!entity.contains::<B>());
+}
@@ -0,0 +1,43 @@
+//! Tests that components from this crate behave correctly when loaded from a scene.
+#![cfg(feature = "reflect")]
+
+use bevy_ecs::{prelude::*, reflect::AppTypeRegistry};
+use bevy_hierarchy::Children;
+use bevy_reflect::Reflect;
+use bevy_scene::{DynamicEntity, DynamicScene};
+use i_cant_believe_its_not_bsn::WithChild;
+
+#[derive(Component, Reflect, Default, PartialEq, Debug)]
+#[reflect(Component)]
+struct A(u8);
+
+#[test]
+fn with_child_from_scene() {
+    let mut world = World::new();
+    let type_registry = AppTypeRegistry::default();
+    {
+        let mut type_registry = type_registry.write();
+        type_registry.register::<A>();
+        type_registry.register::<WithChild<A>>();
+    }
+    world.insert_resource(type_registry);
+
+    let scene = DynamicScene {
+        resources: Vec::new(),
+        entities: vec![DynamicEntity {
+            entity: Entity::from_raw(0),
+            components: vec![Box::new(WithChild(A(7)))],
+        }],
+    };
+
+    let mut entity_map = Default::default();
+    scene.write_to_world(&mut world, &mut entity_map).unwrap();
+    world.flush();
+
+    let parent = entity_map[&Entity::from_raw(0)];
+    assert!(!world.entity(parent).contains::<WithChild<A>>());
+
+    let children = world.get::<Children>(parent).unwrap();
+    assert_eq!(children.len(), 1);
+    assert_eq!(world.get::<A>(children[0]), Some(&A(7)));
+}
@@ -0,0 +1,18 @@
+//! Compile-fail tests documenting which spawn-time patterns the type system rejects.
+//!
+//! Run with `TRYBUILD=overwrite cargo test --test ui` to regenerate the expected output
+//! after intentional changes to the compiler diagnostics.
+//!
+//! Note that spawning two components of the same type (such as two `WithChild<A>`) in one bundle
+//! is not a compile error: Bevy rejects duplicate components with a panic at runtime instead.
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/distinct_children.rs");
+    t.compile_fail("tests/ui/with_child_inherit_not_clone.rs");
+    t.compile_fail("tests/ui/with_children_not_send.rs");
+    t.compile_fail("tests/ui/with_children_borrowed_iterator.rs");
+    #[cfg(feature = "derive")]
+    t.compile_fail("tests/ui/maybe_bundle_not_option.rs");
+}
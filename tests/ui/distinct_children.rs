@@ -0,0 +1,14 @@
+//! Children of distinct bundle types can share a single spawn call.
+use bevy_ecs::prelude::*;
+use i_cant_believe_its_not_bsn::WithChild;
+
+#[derive(Component)]
+struct A;
+
+#[derive(Component)]
+struct B;
+
+fn main() {
+    let mut world = World::new();
+    world.spawn((WithChild(A), WithChild(B)));
+}
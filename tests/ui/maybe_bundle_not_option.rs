@@ -0,0 +1,17 @@
+use bevy_ecs::prelude::*;
+use i_cant_believe_its_not_bsn::MaybeBundle;
+
+#[derive(Component)]
+struct A;
+
+#[derive(Component)]
+struct B;
+
+// Every field must be optional
+#[derive(MaybeBundle)]
+struct Partial {
+    a: Option<A>,
+    b: B,
+}
+
+fn main() {}
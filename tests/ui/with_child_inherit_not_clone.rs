@@ -0,0 +1,14 @@
+//! Components inherited by `WithChildInherit` must be `Clone`, as the parent keeps its own copy.
+use bevy_ecs::prelude::*;
+use i_cant_believe_its_not_bsn::WithChildInherit;
+
+#[derive(Component)]
+struct A;
+
+#[derive(Component)]
+struct Team(u8);
+
+fn main() {
+    let mut world = World::new();
+    world.spawn((Team(1), WithChildInherit::<A, Team>::new(A)));
+}
@@ -0,0 +1,12 @@
+//! The iterator stored in `WithChildren` must be `'static`, so it can't borrow local data.
+use bevy_ecs::prelude::*;
+use i_cant_believe_its_not_bsn::WithChildren;
+
+#[derive(Component)]
+struct A(u8);
+
+fn main() {
+    let mut world = World::new();
+    let values = vec![1, 2, 3];
+    world.spawn(WithChildren(values.iter().map(|&value| A(value))));
+}
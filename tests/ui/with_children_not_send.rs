@@ -0,0 +1,16 @@
+//! The iterator stored in `WithChildren` must be thread-safe, as it's stored as a component.
+use std::rc::Rc;
+
+use bevy_ecs::prelude::*;
+use i_cant_believe_its_not_bsn::WithChildren;
+
+#[derive(Component)]
+struct A;
+
+fn main() {
+    let mut world = World::new();
+    let shared = Rc::new(());
+    world.spawn(WithChildren(core::iter::once(A).inspect(move |_| {
+        let _ = &shared;
+    })));
+}